@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn main() {
     // crate root directory, same one `build.rs` file is in
@@ -17,7 +17,7 @@ fn main() {
     copy_fmod_runtime_to_output_dir(&fmod_libs_path);
 }
 
-fn build_fmod_cpp_bridge(crate_root: &PathBuf, fmod_libs_path: &PathBuf) {
+fn build_fmod_cpp_bridge(crate_root: &Path, fmod_libs_path: &Path) {
     // link crate to shared libraries
     println!(
         "cargo:rustc-link-search=native={}",
@@ -45,7 +45,7 @@ fn build_fmod_cpp_bridge(crate_root: &PathBuf, fmod_libs_path: &PathBuf) {
     }
 }
 
-fn copy_fmod_runtime_to_output_dir(fmod_libs_path: &PathBuf) {
+fn copy_fmod_runtime_to_output_dir(fmod_libs_path: &Path) {
     let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
 
     for from in list_all_files(fmod_libs_path) {
@@ -62,10 +62,9 @@ fn copy_fmod_runtime_to_output_dir(fmod_libs_path: &PathBuf) {
 }
 
 /// List of all files and symlinks in directory, non-recursive
-fn list_all_files(source_path: &PathBuf) -> Vec<PathBuf> {
+fn list_all_files(source_path: &Path) -> Vec<PathBuf> {
     std::fs::read_dir(source_path)
         .unwrap()
-        .into_iter()
         .filter_map(|entry| {
             let entry = entry.unwrap();
             let ty = entry.file_type().unwrap();
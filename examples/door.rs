@@ -0,0 +1,69 @@
+//! Sliding door which occludes a sound behind it while closed.
+//!
+//! Listener is at the origin, looped tone is behind the door, which opens and
+//! closes every 2 seconds. Geometry moves with its transform, so the tone
+//! gets louder when the door slides away.
+
+use bevy::{app::ScheduleRunnerPlugin, prelude::*};
+use bevy_fmod_simple::*;
+use std::time::Duration;
+
+/// Seconds between door opening and closing
+const DOOR_PERIOD: f32 = 2.;
+
+#[derive(Component)]
+struct Door;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+                1. / 60.,
+            ))),
+            bevy::log::LogPlugin::default(),
+            AssetPlugin::default(),
+            TransformPlugin,
+            FmodAudioPlugin::default(),
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, move_door)
+        .run();
+}
+
+fn setup(mut sounds: ResMut<Assets<AudioSource>>, mut commands: Commands) {
+    commands.spawn((TransformBundle::default(), AudioListener));
+
+    let Ok(tone) = AudioSource::sine(440., Duration::from_secs(1)) else {
+        error!("Audio is disabled");
+        return;
+    };
+    commands.spawn((
+        sounds.add(tone),
+        AudioLoop,
+        TransformBundle::from_transform(Transform::from_xyz(0., 0., -5.)),
+    ));
+
+    commands.spawn((
+        AudioGeometry::cuboid(
+            Vec3::new(2., 2., 0.1),
+            AudioGeometryParams {
+                direct_occlusion: 0.9,
+                reverb_occlusion: 0.9,
+            },
+        ),
+        TransformBundle::from_transform(Transform::from_xyz(0., 0., -2.)),
+        Door,
+    ));
+}
+
+fn move_door(mut doors: Query<&mut Transform, With<Door>>, time: Res<Time>) {
+    let open = (time.elapsed_seconds() / DOOR_PERIOD) as u32 % 2 == 1;
+    let height = if open { 5. } else { 0. };
+
+    for mut transform in doors.iter_mut() {
+        if transform.translation.y != height {
+            info!("Door is {}", if open { "open" } else { "closed" });
+            transform.translation.y = height;
+        }
+    }
+}
@@ -1,6 +1,8 @@
 #include <cstdio>
 #include <cstdarg>
 #include <cstring>
+#include <cmath>
+#include <algorithm>
 
 #include "bridge.h"
 #include "../fmod/include/fmod_errors.h"
@@ -26,35 +28,52 @@
 	#define __attribute__(a)
 #endif
 
-/// Print log message
-static void report_msg(bool is_info, const char *fmt, ...) __attribute__ ((format (printf, 2, 3)));
-void report_msg(bool is_info, const char *fmt, ...) {
-	// do a printf to string
-	va_list va;
-	va_start(va, fmt);
+/// Print printf-formatted message into a string
+static std::string format_msg(const char *fmt, va_list va) {
 	const int sn = 4096; // max string length, should be more than enough
 	char s[sn] = {};
 	int n = vsnprintf(s, sn, fmt, va);
-	if (n < 0) {
-		n = strlen(fmt) + 1;
-		memcpy(s, fmt, n);
-	}
+	if (n < 0)
+		return fmt;
+	return std::string(s, std::min(n, sn - 1));
+}
 
-	// bridge_log_* functions are defined in Rust
-	(is_info ? bridge_log_info : bridge_log_error)(rust::Slice{reinterpret_cast<const uint8_t*>(s), static_cast<size_t>(n)});
+/// Print log message
+static void info_msg(const char *fmt, ...) __attribute__ ((format (printf, 1, 2)));
+void info_msg(const char *fmt, ...) {
+	va_list va;
+	va_start(va, fmt);
+	std::string s = format_msg(fmt, va);
 	va_end(va);
+
+	// defined in Rust
+	bridge_log_info(rust::Slice{reinterpret_cast<const uint8_t*>(s.data()), s.size()});
 }
 
-#define info_msg(...) report_msg(true, __VA_ARGS__)
-#define error_msg(...) report_msg(false, __VA_ARGS__)
+/// Report error, must be used only in Bridge methods
+#define error_msg(...) report_error(FMOD_OK, __VA_ARGS__)
+
+/// Check if result is a error and report it if it is. Must be used only in Bridge methods
+#define ERRCHECK(_result) check_result(_result, __LINE__)
+
+void Bridge::report_error(FMOD_RESULT code, const char *fmt, ...) {
+	va_list va;
+	va_start(va, fmt);
+	std::string s = format_msg(fmt, va);
+	va_end(va);
+
+	// defined in Rust
+	bridge_log_error(rust::Slice{reinterpret_cast<const uint8_t*>(s.data()), s.size()});
 
-/// Check if result is a error and log it if it is
-#define ERRCHECK(_result) ERRCHECK_fn(_result, __LINE__)
-static bool ERRCHECK_fn(FMOD_RESULT result, int line) {
-    if (result != FMOD_OK) {
-        error_msg("FMOD error (bridge.cpp:%d): %d - %s", line, result, FMOD_ErrorString(result));
+	if (errors.size() < MAX_ERRORS)
+		errors.push_back({std::move(s), code});
+}
+
+bool Bridge::check_result(FMOD_RESULT result, int line) {
+	if (result != FMOD_OK) {
+		report_error(result, "FMOD error (bridge.cpp:%d): %d - %s", line, result, FMOD_ErrorString(result));
 		return false;
-    }
+	}
 	return true;
 }
 
@@ -73,12 +92,35 @@ int sparse_array_insert(std::vector<T*>& objects, T* new_object) {
 
 //
 
+static FMOD_RESULT F_CALL debug_callback(FMOD_DEBUG_FLAGS flags, const char*, int, const char*, const char* message) {
+	auto level = LogLevel::Verbose;
+	if (flags & FMOD_DEBUG_LEVEL_ERROR)
+		level = LogLevel::Error;
+	else if (flags & FMOD_DEBUG_LEVEL_WARNING)
+		level = LogLevel::Warning;
+
+	bridge_log_fmod(level, rust::Slice{reinterpret_cast<const uint8_t*>(message), strlen(message)}); // defined in Rust
+	return FMOD_OK;
+}
+
 bool Bridge::init(InitParams params) {
 	//
 	// library initialization
 
 	info_msg("FMOD static library version: %d.%d.%d", FMOD_VERSION >> 16, (FMOD_VERSION >> 8) & 0xff, FMOD_VERSION & 0xff);
 
+	FMOD_DEBUG_FLAGS debug_level = FMOD_DEBUG_LEVEL_ERROR;
+	if (params.log_level == LogLevel::Warning)
+		debug_level = FMOD_DEBUG_LEVEL_WARNING;
+	else if (params.log_level == LogLevel::Verbose)
+		debug_level = FMOD_DEBUG_LEVEL_LOG;
+
+	result = FMOD::Debug_Initialize(debug_level, FMOD_DEBUG_MODE_CALLBACK, debug_callback);
+	if (result == FMOD_ERR_UNSUPPORTED)
+		info_msg("FMOD debug messages are not available, since it's not a logging version of the library");
+	else
+		ERRCHECK(result);
+
 	result = FMOD::System_Create(&system);
 	if (!ERRCHECK(result))
 		return false;
@@ -87,21 +129,127 @@ bool Bridge::init(InitParams params) {
 	result = system->getVersion(&fmod_version);
 	ERRCHECK(result);
 	if (fmod_version != FMOD_VERSION)
-		error_msg("FMOD dynamic library version differs! It is %d.%d.%d", fmod_version >> 16, (fmod_version >> 8) & 0xff, fmod_version & 0xff);
+		error_msg("FMOD dynamic library version differs from headers! It is %d.%d.%d, expected %d.%d.%d - expect bugs",
+			fmod_version >> 16, (fmod_version >> 8) & 0xff, fmod_version & 0xff,
+			FMOD_VERSION >> 16, (FMOD_VERSION >> 8) & 0xff, FMOD_VERSION & 0xff);
+
+	max_streams = params.max_streams;
+
+	if (params.output_type != OutputType::Auto) {
+		FMOD_OUTPUTTYPE output = FMOD_OUTPUTTYPE_AUTODETECT;
+		const char* name = "";
+		switch (params.output_type) {
+		case OutputType::NoSound: output = FMOD_OUTPUTTYPE_NOSOUND; name = "no sound"; break;
+		case OutputType::NoSoundNrt: output = FMOD_OUTPUTTYPE_NOSOUND_NRT; name = "no sound (non-realtime)"; break;
+		case OutputType::Wasapi: output = FMOD_OUTPUTTYPE_WASAPI; name = "WASAPI"; break;
+		case OutputType::Alsa: output = FMOD_OUTPUTTYPE_ALSA; name = "ALSA"; break;
+		case OutputType::PulseAudio: output = FMOD_OUTPUTTYPE_PULSEAUDIO; name = "PulseAudio"; break;
+		case OutputType::CoreAudio: output = FMOD_OUTPUTTYPE_COREAUDIO; name = "CoreAudio"; break;
+		case OutputType::WindowsSonic: output = FMOD_OUTPUTTYPE_WINSONIC; name = "Windows Sonic"; break;
+		case OutputType::WavWriter: output = FMOD_OUTPUTTYPE_WAVWRITER; name = "WAV writer"; break;
+		case OutputType::WavWriterNrt: output = FMOD_OUTPUTTYPE_WAVWRITER_NRT; name = "WAV writer (non-realtime)"; break;
+		default: break;
+		}
+
+		result = system->setOutput(output); // MUST be called before system->init!
+		if (!ERRCHECK(result)) {
+			error_msg("Output type \"%s\" is not supported on this platform", name);
+			return false;
+		}
+	}
 
 	result = system->setSoftwareChannels(params.max_active_channels); // MUST be called before system->init!
 	ERRCHECK(result);
 
+	if (params.dsp_buffer_length != 0 || params.dsp_buffer_count != 0) {
+		unsigned int length = 0;
+		int count = 0;
+		result = system->getDSPBufferSize(&length, &count);
+		ERRCHECK(result);
+
+		if (params.dsp_buffer_length != 0)
+			length = params.dsp_buffer_length;
+		if (params.dsp_buffer_count != 0)
+			count = params.dsp_buffer_count;
+
+		result = system->setDSPBufferSize(length, count); // MUST be called before system->init!
+		if (!ERRCHECK(result))
+			error_msg("Invalid DSP buffer size: %u samples x %d, using defaults", length, count);
+	}
+
+	if (params.sample_rate != 0 || params.speaker_mode != SpeakerMode::Default) {
+		int sample_rate = 0;
+		FMOD_SPEAKERMODE speaker_mode = {};
+		int raw_speakers = 0;
+		result = system->getSoftwareFormat(&sample_rate, &speaker_mode, &raw_speakers);
+		ERRCHECK(result);
+
+		if (params.sample_rate != 0)
+			sample_rate = params.sample_rate;
+
+		switch (params.speaker_mode) {
+		case SpeakerMode::Mono: speaker_mode = FMOD_SPEAKERMODE_MONO; break;
+		case SpeakerMode::Stereo: speaker_mode = FMOD_SPEAKERMODE_STEREO; break;
+		case SpeakerMode::Quad: speaker_mode = FMOD_SPEAKERMODE_QUAD; break;
+		case SpeakerMode::Surround51: speaker_mode = FMOD_SPEAKERMODE_5POINT1; break;
+		case SpeakerMode::Surround71: speaker_mode = FMOD_SPEAKERMODE_7POINT1; break;
+		case SpeakerMode::Raw:
+			speaker_mode = FMOD_SPEAKERMODE_RAW;
+			raw_speakers = params.raw_speakers;
+			break;
+		default: break;
+		}
+
+		result = system->setSoftwareFormat(sample_rate, speaker_mode, raw_speakers); // MUST be called before system->init!
+		if (!ERRCHECK(result))
+			error_msg("Invalid output format: sample rate %d Hz, speaker mode %d, %d raw speakers; using defaults", sample_rate, (int) speaker_mode, raw_speakers);
+	}
+
+	int profiler_port = 0;
+	if (params.profiler) {
+		FMOD_ADVANCEDSETTINGS settings = {};
+		settings.cbSize = sizeof(FMOD_ADVANCEDSETTINGS);
+
+		result = system->getAdvancedSettings(&settings);
+		ERRCHECK(result);
+
+		if (params.profiler_port != 0)
+			settings.profilePort = params.profiler_port;
+		profiler_port = settings.profilePort;
+
+		result = system->setAdvancedSettings(&settings); // port MUST be set before system->init!
+		ERRCHECK(result);
+	}
+
+	// for WAV writer it's the output filename
+	std::string wav_output_path(params.wav_output_path);
+	void* extra_driver_data = nullptr;
+	bool wav_writer = params.output_type == OutputType::WavWriter || params.output_type == OutputType::WavWriterNrt;
+	if (wav_writer && !wav_output_path.empty()) {
+		extra_driver_data = (void*) wav_output_path.c_str();
+		info_msg("Writing audio output to \"%s\"", wav_output_path.c_str());
+	}
+
+	bool non_realtime = params.output_type == OutputType::NoSoundNrt || params.output_type == OutputType::WavWriterNrt;
+
 	result = system->init(
 		params.max_virtual_channels,
 		FMOD_INIT_NORMAL |
 			FMOD_INIT_CHANNEL_LOWPASS | // required for 3D geometry occlusion?
 			FMOD_INIT_VOL0_BECOMES_VIRTUAL | // disables playback for sounds which have near-0 volume
-			FMOD_INIT_3D_RIGHTHANDED, // same coordinate system bevy uses
-		nullptr
+			FMOD_INIT_3D_RIGHTHANDED | // same coordinate system bevy uses
+			(non_realtime ? FMOD_INIT_STREAM_FROM_UPDATE : 0) | // otherwise stream thread can't keep up with the mixer
+			(params.profiler ? FMOD_INIT_PROFILE_ENABLE : 0),
+		extra_driver_data
 	);
 	if (!ERRCHECK(result))
 		return false;
+
+	if (params.profiler)
+		info_msg("FMOD profiler is listening on port %d", profiler_port);
+
+	result = system->setGeometrySettings(params.geometry_world_size); // should be set before any geometry is created
+	ERRCHECK(result);
 	
 	//
 	// apply settings
@@ -123,6 +271,13 @@ bool Bridge::init(InitParams params) {
 }
 
 Bridge::~Bridge() {
+	for (auto dsp : {master_limiter, master_lowpass, capture_dsp}) {
+		if (dsp) {
+			get_master_group()->removeDSP(dsp);
+			dsp->release();
+		}
+	}
+
 	for (auto& reverb : reverbs) {
 		if (reverb)
 			reverb->release();
@@ -138,9 +293,39 @@ Bridge::~Bridge() {
 			channel->stop();
 	}
 
-	for (auto& sound : sounds) {
-		if (sound)
-			sound->release();
+	for (auto& dsps : channel_dsps) {
+		for (auto& dsp : dsps.second)
+			dsp.second->release();
+	}
+
+	for (auto& stream : streams) {
+		for (size_t i=1; i<stream.second.instances.size(); ++i)
+			stream.second.instances[i]->release();
+	}
+
+	for (size_t i=0; i<sounds.size(); ++i) {
+		if (sounds[i] && !bank_entries.count(i)) // subsounds are released with the bank
+			sounds[i]->release();
+	}
+
+	for (auto& bank : banks) {
+		bank.second.sound->release();
+	}
+
+	for (auto& dsps : group_dsps) {
+		for (auto& dsp : dsps.second) {
+			groups[dsps.first]->removeDSP(dsp.second);
+			dsp.second->release();
+		}
+	}
+
+	for (auto& effects : group_effects) {
+		for (auto& effect : effects.second) {
+			if (!effect.second)
+				continue;
+			groups[effects.first]->removeDSP(effect.second);
+			effect.second->release();
+		}
 	}
 
 	for (auto& group : groups) {
@@ -159,9 +344,192 @@ FMOD::ChannelGroup* Bridge::get_group(int user_id) {
 	if (!group) { // create group with default parameters if it doesn't exist
 		GroupParams params;
 		params.user_id = user_id;
-		params.volume = 1.;
+		params.volume = default_group_volume;
+		params.pitch = 1.;
 		update_group(params);
+		default_groups.insert(user_id);
+	}
+	return group;
+}
+
+FMOD::Sound* Bridge::get_playable_sound(int id, int& stream_index) {
+	auto sound = sounds.at(id);
+
+	stream_index = -1;
+	auto it = streams.find(id);
+	if (it == streams.end())
+		return sound;
+	auto& stream = it->second;
+
+	// find instance which isn't being played
+	for (size_t i=0; i<stream.instances.size(); ++i) {
+		bool is_playing = false;
+		if (stream.channels[i])
+			stream.channels[i]->isPlaying(&is_playing); // fails if channel stopped or stolen
+
+		if (!is_playing) {
+			stream_index = i;
+			return stream.instances[i];
+		}
+	}
+
+	if (open_streams >= max_streams) {
+		error_msg("Too many streams are played at once (max is %d)", max_streams);
+		return nullptr;
+	}
+
+	auto instance = open_stream(stream);
+	if (!instance)
+		return nullptr;
+
+	stream.instances.push_back(instance);
+	stream.channels.push_back(nullptr);
+	stream_index = stream.instances.size() - 1;
+	return instance;
+}
+
+FMOD::Sound* Bridge::open_stream(const Stream& stream) {
+	FMOD_MODE flags = FMOD_3D | FMOD_LOOP_NORMAL | FMOD_CREATESTREAM; // same as in load_audio_file
+	FMOD::Sound* sound = nullptr;
+
+	if (!stream.filename.empty()) {
+		result = system->createSound(stream.filename.c_str(), flags, nullptr, &sound);
+		if (!ERRCHECK(result)) {
+			info_msg("Path to the file: \"%s\"", stream.filename.c_str());
+			return nullptr;
+		}
+	}
+	else {
+		FMOD_CREATESOUNDEXINFO exinfo = {};
+		exinfo.cbsize = sizeof(FMOD_CREATESOUNDEXINFO);
+		exinfo.length = stream.contents.size();
+
+		// memory isn't copied, it must outlive the sound
+		result = system->createSound((const char*) stream.contents.data(), flags | FMOD_OPENMEMORY_POINT, &exinfo, &sound);
+		if (!ERRCHECK(result))
+			return nullptr;
+	}
+
+	open_streams++;
+	return sound;
+}
+
+FMOD::DSP* Bridge::get_channel_dsp(int id, FMOD_DSP_TYPE type) {
+	auto& dsp = channel_dsps[id][type];
+	if (!dsp) {
+		result = system->createDSPByType(type, &dsp);
+		if (!ERRCHECK(result)) {
+			channel_dsps[id].erase(type);
+			return nullptr;
+		}
+
+		result = channels.at(id)->addDSP(FMOD_CHANNELCONTROL_DSP_TAIL, dsp);
+		if (!ERRCHECK(result)) {
+			dsp->release();
+			channel_dsps[id].erase(type);
+			return nullptr;
+		}
+	}
+	return dsp;
+}
+
+void Bridge::remove_channel_dsp(int id, FMOD_DSP_TYPE type) {
+	auto dsps = channel_dsps.find(id);
+	if (dsps == channel_dsps.end())
+		return;
+
+	auto dsp = dsps->second.find(type);
+	if (dsp == dsps->second.end())
+		return;
+
+	channels.at(id)->removeDSP(dsp->second); // fails if channel stopped or stolen
+
+	result = dsp->second->release();
+	ERRCHECK(result);
+
+	dsps->second.erase(dsp);
+	if (dsps->second.empty())
+		channel_dsps.erase(dsps);
+}
+
+FMOD::DSP* Bridge::get_group_dsp(int group_id, FMOD_DSP_TYPE type) {
+	auto& dsp = group_dsps[group_id][type];
+	if (!dsp) {
+		result = system->createDSPByType(type, &dsp);
+		if (!ERRCHECK(result)) {
+			group_dsps[group_id].erase(type);
+			return nullptr;
+		}
+
+		result = get_group(group_id)->addDSP(FMOD_CHANNELCONTROL_DSP_TAIL, dsp);
+		if (!ERRCHECK(result)) {
+			dsp->release();
+			group_dsps[group_id].erase(type);
+			return nullptr;
+		}
 	}
+	return dsp;
+}
+
+void Bridge::remove_group_dsp(int group_id, FMOD_DSP_TYPE type) {
+	auto dsps = group_dsps.find(group_id);
+	if (dsps == group_dsps.end())
+		return;
+
+	auto dsp = dsps->second.find(type);
+	if (dsp == dsps->second.end())
+		return;
+
+	result = get_group(group_id)->removeDSP(dsp->second);
+	ERRCHECK(result);
+
+	result = dsp->second->release();
+	ERRCHECK(result);
+
+	dsps->second.erase(dsp);
+	if (dsps->second.empty())
+		group_dsps.erase(dsps);
+}
+
+bool Bridge::steal_channel(bool oldest) {
+	int stolen = -1;
+	float stolen_audibility = 0.;
+
+	for (size_t i=0; i<channels.size(); ++i) {
+		auto channel = channels[i];
+		if (!channel)
+			continue;
+
+		bool is_playing = false, is_virtual = false;
+		channel->isPlaying(&is_playing); // fails if channel stopped or stolen
+		channel->isVirtual(&is_virtual);
+		if (!is_playing || is_virtual)
+			continue;
+
+		float audibility = 0.;
+		if (!oldest) {
+			result = channel->getAudibility(&audibility);
+			if (!ERRCHECK(result))
+				continue;
+		}
+
+		if (stolen == -1 || (oldest ? channel_ages[i] < channel_ages[stolen] : audibility < stolen_audibility)) {
+			stolen = i;
+			stolen_audibility = audibility;
+		}
+	}
+
+	if (stolen == -1)
+		return false;
+
+	stop_channel(stolen);
+	return true;
+}
+
+FMOD::ChannelGroup* Bridge::get_master_group() {
+	FMOD::ChannelGroup* group = nullptr;
+	result = system->getMasterChannelGroup(&group);
+	ERRCHECK(result); // can fail only if system is not initialized
 	return group;
 }
 	
@@ -170,97 +538,967 @@ void Bridge::update() {
 	ERRCHECK(result);
 }
 
+size_t Bridge::error_count() const {
+	return errors.size();
+}
+
+rust::Vec<BridgeError> Bridge::take_errors(size_t first) {
+	rust::Vec<BridgeError> taken;
+	first = std::min(first, errors.size());
+	for (size_t i = first; i < errors.size(); i++) {
+		BridgeError error = {};
+		error.message = errors[i].message;
+		error.code = errors[i].code;
+		taken.push_back(std::move(error));
+	}
+	errors.erase(errors.begin() + first, errors.end());
+	return taken;
+}
+
+EngineInfo Bridge::get_engine_info() {
+	EngineInfo info = {};
+
+	FMOD_SPEAKERMODE speaker_mode = {};
+	int raw_speakers = 0;
+	result = system->getSoftwareFormat(&info.sample_rate, &speaker_mode, &raw_speakers);
+	ERRCHECK(result);
+
+	switch (speaker_mode) {
+	case FMOD_SPEAKERMODE_MONO: info.speaker_mode = SpeakerMode::Mono; break;
+	case FMOD_SPEAKERMODE_STEREO: info.speaker_mode = SpeakerMode::Stereo; break;
+	case FMOD_SPEAKERMODE_QUAD: info.speaker_mode = SpeakerMode::Quad; break;
+	case FMOD_SPEAKERMODE_5POINT1: info.speaker_mode = SpeakerMode::Surround51; break;
+	case FMOD_SPEAKERMODE_7POINT1: info.speaker_mode = SpeakerMode::Surround71; break;
+	case FMOD_SPEAKERMODE_RAW: info.speaker_mode = SpeakerMode::Raw; break;
+	default: info.speaker_mode = SpeakerMode::Default; break;
+	}
+
+	if (speaker_mode == FMOD_SPEAKERMODE_RAW)
+		info.speaker_count = raw_speakers;
+	else {
+		result = system->getSpeakerModeChannels(speaker_mode, &info.speaker_count);
+		ERRCHECK(result);
+	}
+
+	unsigned int dsp_buffer_length = 0;
+	result = system->getDSPBufferSize(&dsp_buffer_length, &info.dsp_buffer_count);
+	ERRCHECK(result);
+	info.dsp_buffer_length = dsp_buffer_length;
+
+	result = system->getVersion(&info.version);
+	ERRCHECK(result);
+	info.header_version = FMOD_VERSION;
+
+	result = system->getGeometrySettings(&info.geometry_world_size);
+	ERRCHECK(result);
+
+	FMOD_OUTPUTTYPE output = {};
+	result = system->getOutput(&output);
+	ERRCHECK(result);
+
+	switch (output) {
+	case FMOD_OUTPUTTYPE_NOSOUND: info.output_type = OutputType::NoSound; break;
+	case FMOD_OUTPUTTYPE_NOSOUND_NRT: info.output_type = OutputType::NoSoundNrt; break;
+	case FMOD_OUTPUTTYPE_WASAPI: info.output_type = OutputType::Wasapi; break;
+	case FMOD_OUTPUTTYPE_ALSA: info.output_type = OutputType::Alsa; break;
+	case FMOD_OUTPUTTYPE_PULSEAUDIO: info.output_type = OutputType::PulseAudio; break;
+	case FMOD_OUTPUTTYPE_COREAUDIO: info.output_type = OutputType::CoreAudio; break;
+	case FMOD_OUTPUTTYPE_WINSONIC: info.output_type = OutputType::WindowsSonic; break;
+	case FMOD_OUTPUTTYPE_WAVWRITER: info.output_type = OutputType::WavWriter; break;
+	case FMOD_OUTPUTTYPE_WAVWRITER_NRT: info.output_type = OutputType::WavWriterNrt; break;
+	default: info.output_type = OutputType::Auto; break;
+	}
+
+	int driver = 0;
+	result = system->getDriver(&driver);
+	ERRCHECK(result);
+
+	char driver_name[256] = {};
+	result = system->getDriverInfo(driver, driver_name, sizeof(driver_name), nullptr, nullptr, nullptr, nullptr);
+	if (ERRCHECK(result))
+		info.driver_name = driver_name;
+
+	return info;
+}
+
+EngineStats Bridge::get_stats() {
+	EngineStats stats = {};
+
+	result = system->getChannelsPlaying(&stats.channels_playing, &stats.real_channels_playing);
+	ERRCHECK(result);
+
+	FMOD_CPU_USAGE usage = {};
+	result = system->getCPUUsage(&usage);
+	ERRCHECK(result);
+	stats.cpu_dsp = usage.dsp;
+	stats.cpu_stream = usage.stream;
+	stats.cpu_geometry = usage.geometry;
+	stats.cpu_update = usage.update;
+
+	return stats;
+}
+
+MemoryStats Bridge::get_memory_stats() {
+	MemoryStats stats = {};
+	result = FMOD::Memory_GetStats(&stats.current, &stats.max, false);
+	ERRCHECK(result);
+	return stats;
+}
+
 void Bridge::update_engine(EngineParams params) {
 	result = system->set3DSettings(params.doppler_scale, params.distance_scale, params.rolloff_scale);
 	ERRCHECK(result);
 
-	result = system->setGeometrySettings(params.max_world_size);
+}
+
+void Bridge::set_master_limiter(LimiterParams params) {
+	if (!params.enabled) {
+		if (master_limiter) {
+			result = get_master_group()->removeDSP(master_limiter);
+			ERRCHECK(result);
+
+			result = master_limiter->release();
+			ERRCHECK(result);
+
+			master_limiter = nullptr;
+		}
+		return;
+	}
+
+	if (!master_limiter) {
+		result = system->createDSPByType(FMOD_DSP_TYPE_LIMITER, &master_limiter);
+		if (!ERRCHECK(result))
+			return;
+
+		// head is the end of the chain, closest to output
+		result = get_master_group()->addDSP(FMOD_CHANNELCONTROL_DSP_HEAD, master_limiter);
+		if (!ERRCHECK(result)) {
+			master_limiter->release();
+			master_limiter = nullptr;
+			return;
+		}
+	}
+
+	result = master_limiter->setParameterFloat(FMOD_DSP_LIMITER_CEILING, params.ceiling);
+	ERRCHECK(result);
+
+	result = master_limiter->setParameterFloat(FMOD_DSP_LIMITER_RELEASETIME, params.release_time);
 	ERRCHECK(result);
 }
 	
-void Bridge::update_listener(ListenerParams params) {
+void Bridge::set_listener_count(int count) {
+	result = system->set3DNumListeners(count);
+	ERRCHECK(result);
+}
+
+void Bridge::update_listener(int index, ListenerParams params) {
 	auto position = vector(params.position);
 	auto velocity = vector(params.velocity);
 	auto forward = vector(params.forward);
 	auto up = vector(params.up);
 
-	result = system->set3DListenerAttributes(0, &position, &velocity, &forward, &up);
+	result = system->set3DListenerAttributes(index, &position, &velocity, &forward, &up);
+	ERRCHECK(result);
+}
+
+void Bridge::update_group(GroupParams params) {
+	auto& group = groups[params.user_id];
+	default_groups.erase(params.user_id);
+
+	// create group if needed
+	if (!group) {
+		const auto group_name = std::to_string(params.user_id);
+
+		result = system->createChannelGroup(group_name.c_str(), &group);
+		if (!ERRCHECK(result))
+			return;
+
+		// TODO(later): is it possible to reduce ramp duration?
+		result = group->setVolumeRamp(true); // enable smooth change of volume
+		ERRCHECK(result);
+	}
+
+	result = group->setVolume(params.volume);
+	ERRCHECK(result);
+
+	result = group->setPitch(params.pitch);
+	ERRCHECK(result);
+}
+
+void Bridge::set_default_group_volume(float volume) {
+	default_group_volume = volume;
+
+	for (int user_id : default_groups) {
+		result = groups[user_id]->setVolume(volume);
+		ERRCHECK(result);
+	}
+}
+
+int Bridge::load_plugin(rust::Str filename) {
+	std::string path(filename);
+	unsigned int handle = 0;
+	result = system->loadPlugin(path.c_str(), &handle);
+	if (!ERRCHECK(result)) {
+		info_msg("Path to the plugin: \"%s\"", path.c_str());
+		return -1;
+	}
+
+	plugins.push_back(handle);
+	return plugins.size() - 1;
+}
+
+void Bridge::set_group_effects(int group_id, rust::Slice<const GroupEffect> effects) {
+	auto group = get_group(group_id);
+	auto& current = group_effects[group_id];
+
+	bool same_plugins = current.size() == effects.size();
+	for (size_t i=0; same_plugins && i<effects.size(); ++i)
+		same_plugins = current[i].first == effects[i].plugin_id;
+
+	if (!same_plugins) {
+		for (auto& effect : current) {
+			if (!effect.second)
+				continue;
+
+			result = group->removeDSP(effect.second);
+			ERRCHECK(result);
+
+			result = effect.second->release();
+			ERRCHECK(result);
+		}
+		current.assign(effects.size(), {-1, nullptr});
+
+		// Each one is inserted in place of fader, which is moved towards tail (further from output),
+		// so they are added in reverse order to be processed in the specified one.
+		for (size_t i=effects.size(); i-- > 0;) {
+			current[i].first = effects[i].plugin_id;
+
+			FMOD::DSP* dsp = nullptr;
+			result = system->createDSPByPlugin(plugins.at(effects[i].plugin_id), &dsp);
+			if (!ERRCHECK(result))
+				continue;
+
+			result = group->addDSP(FMOD_CHANNELCONTROL_DSP_FADER, dsp);
+			if (!ERRCHECK(result)) {
+				dsp->release();
+				continue;
+			}
+
+			current[i].second = dsp;
+		}
+	}
+
+	for (size_t i=0; i<current.size(); ++i) {
+		if (!current[i].second)
+			continue;
+
+		auto& params = effects[i].params;
+		for (size_t j=0; j<params.size(); ++j) {
+			result = current[i].second->setParameterFloat(j, params[j]);
+			ERRCHECK(result);
+		}
+	}
+}
+
+void Bridge::set_group_eq(int group_id, rust::Slice<const EqBand> bands) {
+	if (bands.empty()) {
+		remove_group_dsp(group_id, FMOD_DSP_TYPE_MULTIBAND_EQ);
+		return;
+	}
+
+	auto dsp = get_group_dsp(group_id, FMOD_DSP_TYPE_MULTIBAND_EQ);
+	if (!dsp)
+		return;
+
+	// parameters of each band (filter, frequency, Q, gain) follow the previous one
+	const int band_params = FMOD_DSP_MULTIBAND_EQ_B_FILTER - FMOD_DSP_MULTIBAND_EQ_A_FILTER;
+	for (int i=0; i<5; ++i) {
+		int base = i * band_params;
+		bool enabled = size_t(i) < bands.size();
+
+		result = dsp->setParameterInt(base + FMOD_DSP_MULTIBAND_EQ_A_FILTER,
+			enabled ? FMOD_DSP_MULTIBAND_EQ_FILTER_PEAKING : FMOD_DSP_MULTIBAND_EQ_FILTER_DISABLED);
+		ERRCHECK(result);
+		if (!enabled)
+			continue;
+
+		auto& band = bands[i];
+		result = dsp->setParameterFloat(base + FMOD_DSP_MULTIBAND_EQ_A_FREQUENCY, std::clamp(band.frequency, 20.f, 22000.f));
+		ERRCHECK(result);
+		result = dsp->setParameterFloat(base + FMOD_DSP_MULTIBAND_EQ_A_Q, std::clamp(band.q, 0.1f, 10.f));
+		ERRCHECK(result);
+		result = dsp->setParameterFloat(base + FMOD_DSP_MULTIBAND_EQ_A_GAIN, std::clamp(band.gain, -30.f, 30.f));
+		ERRCHECK(result);
+	}
+}
+
+void Bridge::set_group_distortion(int group_id, float level) {
+	if (level <= 0.) {
+		remove_group_dsp(group_id, FMOD_DSP_TYPE_DISTORTION);
+		return;
+	}
+
+	auto dsp = get_group_dsp(group_id, FMOD_DSP_TYPE_DISTORTION);
+	if (!dsp)
+		return;
+
+	result = dsp->setParameterFloat(FMOD_DSP_DISTORTION_LEVEL, std::min(level, 1.f));
+	ERRCHECK(result);
+}
+
+rust::Vec<GroupStats> Bridge::get_group_stats() {
+	rust::Vec<GroupStats> stats;
+	for (auto& [user_id, group] : groups) {
+		if (!group)
+			continue;
+
+		GroupStats stat = {};
+		stat.user_id = user_id;
+
+		result = group->getAudibility(&stat.audibility);
+		ERRCHECK(result);
+
+		int count = 0;
+		result = group->getNumChannels(&count);
+		ERRCHECK(result);
+
+		for (int i=0; i<count; ++i) {
+			FMOD::Channel* channel = nullptr;
+			result = group->getChannel(i, &channel);
+			if (!ERRCHECK(result))
+				continue;
+
+			bool is_virtual = false;
+			result = channel->isVirtual(&is_virtual);
+			if (!ERRCHECK(result))
+				continue;
+
+			(is_virtual ? stat.virtualized : stat.playing)++;
+		}
+
+		stats.push_back(stat);
+	}
+	return stats;
+}
+
+void Bridge::set_master_pitch(float pitch) {
+	result = get_master_group()->setPitch(pitch);
+	ERRCHECK(result);
+}
+
+void Bridge::set_master_lowpass(float cutoff) {
+	if (cutoff <= 0.) {
+		if (master_lowpass) {
+			result = get_master_group()->removeDSP(master_lowpass);
+			ERRCHECK(result);
+
+			result = master_lowpass->release();
+			ERRCHECK(result);
+
+			master_lowpass = nullptr;
+		}
+		return;
+	}
+
+	if (!master_lowpass) {
+		result = system->createDSPByType(FMOD_DSP_TYPE_LOWPASS_SIMPLE, &master_lowpass);
+		if (!ERRCHECK(result))
+			return;
+
+		// inserted in place of fader, which is moved towards tail (further from output)
+		result = get_master_group()->addDSP(FMOD_CHANNELCONTROL_DSP_FADER, master_lowpass);
+		if (!ERRCHECK(result)) {
+			master_lowpass->release();
+			master_lowpass = nullptr;
+			return;
+		}
+	}
+
+	result = master_lowpass->setParameterFloat(FMOD_DSP_LOWPASS_SIMPLE_CUTOFF, cutoff);
+	ERRCHECK(result);
+}
+
+void Bridge::set_master_paused(bool paused) {
+	result = get_master_group()->setPaused(paused);
+	ERRCHECK(result);
+}
+
+void Bridge::set_mixer_suspended(bool suspended) {
+	if (suspended == mixer_suspended)
+		return;
+
+	result = suspended ? system->mixerSuspend() : system->mixerResume();
+	if (ERRCHECK(result))
+		mixer_suspended = suspended;
+}
+
+void Bridge::advance_mixer(uint32_t milliseconds) {
+	int sample_rate = 0;
+	result = system->getSoftwareFormat(&sample_rate, nullptr, nullptr);
+	if (!ERRCHECK(result))
+		return;
+
+	unsigned int buffer_length = 0;
+	result = system->getDSPBufferSize(&buffer_length, nullptr);
+	if (!ERRCHECK(result) || buffer_length == 0)
+		return;
+
+	// each update mixes one buffer
+	uint64_t samples = uint64_t(milliseconds) * sample_rate / 1000;
+	uint64_t updates = (samples + buffer_length - 1) / buffer_length;
+	for (uint64_t i=0; i<updates; ++i)
+		update();
+}
+
+static FMOD_RESULT F_CALLBACK capture_read(FMOD_DSP_STATE* state, float* inbuffer, float* outbuffer, unsigned int length, int inchannels, int* outchannels) {
+	size_t count = size_t(length) * inchannels;
+	std::memcpy(outbuffer, inbuffer, count * sizeof(float));
+	*outchannels = inchannels;
+
+	void* userdata = nullptr;
+	FMOD_RESULT result = ((FMOD::DSP*) state->instance)->getUserData(&userdata);
+	if (result != FMOD_OK || !userdata)
+		return FMOD_OK;
+
+	auto capture = (Bridge::OutputCapture*) userdata;
+	std::lock_guard<std::mutex> lock(capture->mutex);
+	if (capture->channels != inchannels) {
+		capture->samples.clear(); // can't mix different layouts
+		capture->channels = inchannels;
+	}
+	capture->samples.insert(capture->samples.end(), inbuffer, inbuffer + count);
+	return FMOD_OK;
+}
+
+void Bridge::set_output_capture(bool enabled) {
+	if (!enabled) {
+		if (capture_dsp) {
+			result = get_master_group()->removeDSP(capture_dsp);
+			ERRCHECK(result);
+
+			result = capture_dsp->release();
+			ERRCHECK(result);
+
+			capture_dsp = nullptr;
+		}
+		return;
+	}
+
+	if (capture_dsp)
+		return;
+
+	FMOD_DSP_DESCRIPTION desc = {};
+	desc.pluginsdkversion = FMOD_PLUGIN_SDK_VERSION;
+	std::strncpy(desc.name, "Output capture", sizeof(desc.name) - 1);
+	desc.numinputbuffers = 1;
+	desc.numoutputbuffers = 1;
+	desc.read = capture_read;
+	desc.userdata = capture.get();
+
+	result = system->createDSP(&desc, &capture_dsp);
+	if (!ERRCHECK(result))
+		return;
+
+	result = get_master_group()->addDSP(FMOD_CHANNELCONTROL_DSP_HEAD, capture_dsp);
+	if (!ERRCHECK(result)) {
+		capture_dsp->release();
+		capture_dsp = nullptr;
+	}
+}
+
+CapturedOutput Bridge::take_captured_output() {
+	CapturedOutput output = {};
+
+	result = system->getSoftwareFormat(&output.sample_rate, nullptr, nullptr);
+	ERRCHECK(result);
+
+	std::lock_guard<std::mutex> lock(capture->mutex);
+	output.channels = capture->channels;
+	output.samples.reserve(capture->samples.size());
+	for (float sample : capture->samples)
+		output.samples.push_back(sample);
+	capture->samples.clear();
+
+	return output;
+}
+
+int Bridge::load_audio_file(AudioFileParams params) {
+	int flags = FMOD_3D | FMOD_LOOP_NORMAL; // allow spatial usage and being looped
+	FMOD::Sound* sound = nullptr;
+
+	if (!params.filename.empty() || (params.stream_contents && !params.file_contents.empty())) {
+		// don't decode whole file at once
+
+		if (open_streams >= max_streams) {
+			error_msg("Too many streams are opened at once (max is %d)", max_streams);
+			return -1;
+		}
+
+		Stream stream;
+		stream.filename = std::string(params.filename);
+		if (stream.filename.empty())
+			stream.contents.assign(params.file_contents.begin(), params.file_contents.end());
+
+		sound = open_stream(stream);
+		if (!sound)
+			return -1;
+
+		stream.instances.push_back(sound);
+		stream.channels.push_back(nullptr);
+
+		int id = sparse_array_insert(sounds, sound);
+		streams[id] = std::move(stream); // moving vector doesn't move its contents
+		return id;
+	}
+	else if (!params.file_contents.empty()) {
+		flags |= FMOD_OPENMEMORY;
+		if (params.compressed)
+			flags |= FMOD_CREATECOMPRESSEDSAMPLE; // decode while playing
+
+		FMOD_CREATESOUNDEXINFO exinfo = {};
+		exinfo.cbsize = sizeof(FMOD_CREATESOUNDEXINFO);
+    	exinfo.length = params.file_contents.size();
+
+		if (params.nonblocking) {
+			// keep contents alive until FMOD is done with them
+			flags |= FMOD_NONBLOCKING;
+			std::vector<uint8_t> contents(params.file_contents.begin(), params.file_contents.end());
+
+			result = system->createSound((const char*) contents.data(), flags, &exinfo, &sound);
+			if (!ERRCHECK(result))
+				return -1;
+
+			int id = sparse_array_insert(sounds, sound);
+			loading_files[id] = std::move(contents); // moving vector doesn't move its contents
+			return id;
+		}
+
+		result = system->createSound((const char*) params.file_contents.data(), flags, &exinfo, &sound);
+		if (!ERRCHECK(result))
+			return -1;
+	}
+	else {
+		error_msg("No sound data");
+		return -1;
+	}
+	
+	return sparse_array_insert(sounds, sound);
+}
+
+void Bridge::free_audio_file(int i) {
+	auto& sound = sounds.at(i);
+
+	auto bank_entry = bank_entries.find(i);
+	if (bank_entry != bank_entries.end()) {
+		// subsound is released only with its bank
+		auto bank = banks.find(bank_entry->second);
+		bank_entries.erase(bank_entry);
+		sound = nullptr;
+
+		if (--bank->second.users == 0 && bank->second.freed) {
+			result = bank->second.sound->release();
+			ERRCHECK(result);
+			banks.erase(bank);
+		}
+		return;
+	}
+
+	auto stream = streams.find(i);
+	if (stream != streams.end()) {
+		for (size_t j=1; j<stream->second.instances.size(); ++j) {
+			result = stream->second.instances[j]->release();
+			ERRCHECK(result);
+		}
+		open_streams -= stream->second.instances.size();
+	}
+
+	// channels stop when their sound is released, but reloaded sound resumes from the same position
+	for (size_t j=0; j<channels.size(); ++j) {
+		FMOD::Sound* current = nullptr;
+		if (!channels[j] || channels[j]->getCurrentSound(&current) != FMOD_OK)
+			continue;
+
+		bool is_same = current == sound;
+		if (stream != streams.end()) {
+			auto& instances = stream->second.instances;
+			is_same = std::find(instances.begin(), instances.end(), current) != instances.end();
+		}
+		if (!is_same)
+			continue;
+
+		double position = get_channel_position(j);
+		if (position >= 0)
+			freed_positions[j] = position;
+	}
+
+	result = sound->release(); // blocks if sound is still loading
+	ERRCHECK(result);
+
+	sound = nullptr;
+
+	if (stream != streams.end())
+		streams.erase(stream); // after all instances are released, as they may point to its memory
+	loading_files.erase(i);
+	generators.erase(i); // after the sound is released, so callback can't be called anymore
+}
+
+static FMOD_RESULT F_CALLBACK generator_read(FMOD_SOUND* sound, void* data, unsigned int datalen) {
+	void* userdata = nullptr;
+	((FMOD::Sound*) sound)->getUserData(&userdata);
+
+	float* samples = (float*) data;
+	size_t count = datalen / sizeof(float);
+	size_t written = 0;
+	if (userdata)
+		written = generator_fill(*(AudioGeneratorBox*) userdata, rust::Slice<float>(samples, count));
+
+	std::fill(samples + written, samples + count, 0.f);
+	return FMOD_OK;
+}
+
+int Bridge::load_generator(GeneratorParams params, rust::Box<AudioGeneratorBox> generator) {
+	int flags = FMOD_3D | FMOD_LOOP_NORMAL | FMOD_OPENUSER | FMOD_CREATESTREAM;
+
+	FMOD_CREATESOUNDEXINFO exinfo = {};
+	exinfo.cbsize = sizeof(FMOD_CREATESOUNDEXINFO);
+	exinfo.numchannels = params.channels;
+	exinfo.defaultfrequency = params.sample_rate;
+	exinfo.format = FMOD_SOUND_FORMAT_PCMFLOAT;
+	exinfo.decodebuffersize = params.sample_rate / 20; // 50 ms of latency
+	exinfo.length = params.sample_rate * params.channels * sizeof(float); // 1 second, looped
+	exinfo.pcmreadcallback = generator_read;
+	exinfo.userdata = &*generator; // box contents don't move
+
+	FMOD::Sound* sound = nullptr;
+	result = system->createSound(nullptr, flags, &exinfo, &sound);
+	if (!ERRCHECK(result))
+		return -1;
+
+	int id = sparse_array_insert(sounds, sound);
+	generators.emplace(id, std::move(generator));
+	return id;
+}
+
+int Bridge::load_bank(rust::Slice<const uint8_t> file_contents) {
+	int flags = FMOD_3D | FMOD_LOOP_NORMAL | FMOD_OPENMEMORY | FMOD_CREATECOMPRESSEDSAMPLE; // banks are usually compressed
+	
+	FMOD_CREATESOUNDEXINFO exinfo = {};
+	exinfo.cbsize = sizeof(FMOD_CREATESOUNDEXINFO);
+	exinfo.length = file_contents.size();
+
+	FMOD::Sound* sound = nullptr;
+	result = system->createSound((const char*) file_contents.data(), flags, &exinfo, &sound);
+	if (!ERRCHECK(result))
+		return -1;
+
+	int id = bank_counter++;
+	banks[id].sound = sound;
+	return id;
+}
+
+void Bridge::free_bank(int id) {
+	auto& bank = banks.at(id);
+	if (bank.users != 0) {
+		bank.freed = true;
+		return;
+	}
+
+	result = bank.sound->release();
+	ERRCHECK(result);
+	banks.erase(id);
+}
+
+rust::Vec<rust::String> Bridge::get_bank_entry_names(int id) {
+	rust::Vec<rust::String> names;
+	auto bank = banks.at(id).sound;
+
+	int count = 0;
+	result = bank->getNumSubSounds(&count);
+	if (!ERRCHECK(result))
+		return names;
+
+	for (int i=0; i<count; ++i) {
+		char name[256] = {};
+		FMOD::Sound* subsound = nullptr;
+
+		result = bank->getSubSound(i, &subsound);
+		if (ERRCHECK(result) && subsound) {
+			result = subsound->getName(name, sizeof(name));
+			ERRCHECK(result);
+		}
+		names.push_back(name); // keep indices even on error
+	}
+	return names;
+}
+
+int Bridge::load_bank_entry(int bank_id, int index) {
+	auto& bank = banks.at(bank_id);
+
+	FMOD::Sound* subsound = nullptr;
+	result = bank.sound->getSubSound(index, &subsound);
+	if (!ERRCHECK(result) || !subsound)
+		return -1;
+
+	int id = sparse_array_insert(sounds, subsound);
+	bank_entries[id] = bank_id;
+	++bank.users;
+	return id;
+}
+
+FileOpenState Bridge::get_file_open_state(int id) {
+	FMOD_OPENSTATE state = FMOD_OPENSTATE_ERROR;
+
+	// if background loading failed, its error is returned here
+	result = sounds.at(id)->getOpenState(&state, nullptr, nullptr, nullptr);
+
+	if (state == FMOD_OPENSTATE_LOADING)
+		return FileOpenState::Loading;
+
+	loading_files.erase(id);
+
+	if (!ERRCHECK(result) || state == FMOD_OPENSTATE_ERROR)
+		return FileOpenState::Error;
+	return FileOpenState::Ready;
+}
+
+SoundInfo Bridge::get_sound_info(int id) {
+	SoundInfo info = {};
+	auto sound = sounds.at(id);
+
+	result = sound->getLength(&info.length_ms, FMOD_TIMEUNIT_MS);
+	if (!ERRCHECK(result))
+		info.length_ms = 0xffffffff;
+
+	FMOD_SOUND_TYPE type = FMOD_SOUND_TYPE_UNKNOWN;
+	FMOD_SOUND_FORMAT format = FMOD_SOUND_FORMAT_NONE;
+	result = sound->getFormat(&type, &format, &info.channels, &info.bits);
+	ERRCHECK(result);
+
+	switch (type) {
+	case FMOD_SOUND_TYPE_UNKNOWN: info.file_type = SoundType::Unknown; break;
+	case FMOD_SOUND_TYPE_AIFF: info.file_type = SoundType::Aiff; break;
+	case FMOD_SOUND_TYPE_FLAC: info.file_type = SoundType::Flac; break;
+	case FMOD_SOUND_TYPE_FSB: info.file_type = SoundType::Fsb; break;
+	case FMOD_SOUND_TYPE_MPEG: info.file_type = SoundType::Mpeg; break;
+	case FMOD_SOUND_TYPE_OGGVORBIS: info.file_type = SoundType::OggVorbis; break;
+	case FMOD_SOUND_TYPE_OPUS: info.file_type = SoundType::Opus; break;
+	case FMOD_SOUND_TYPE_WAV: info.file_type = SoundType::Wav; break;
+	default: info.file_type = SoundType::Other;
+	}
+
+	switch (format) {
+	case FMOD_SOUND_FORMAT_PCM8: info.sample_format = SampleFormat::Pcm8; break;
+	case FMOD_SOUND_FORMAT_PCM16: info.sample_format = SampleFormat::Pcm16; break;
+	case FMOD_SOUND_FORMAT_PCM24: info.sample_format = SampleFormat::Pcm24; break;
+	case FMOD_SOUND_FORMAT_PCM32: info.sample_format = SampleFormat::Pcm32; break;
+	case FMOD_SOUND_FORMAT_PCMFLOAT: info.sample_format = SampleFormat::PcmFloat; break;
+	case FMOD_SOUND_FORMAT_BITSTREAM: info.sample_format = SampleFormat::Compressed; break;
+	default: info.sample_format = SampleFormat::Unknown;
+	}
+
+	FMOD_MODE mode = 0;
+	result = sound->getMode(&mode);
+	ERRCHECK(result);
+	info.decoded_while_playing = mode & (FMOD_CREATESTREAM | FMOD_CREATECOMPRESSEDSAMPLE);
+
+	result = sound->getDefaults(&info.sample_rate, nullptr);
+	ERRCHECK(result);
+
+	return info;
+}
+
+rust::Vec<float> Bridge::read_sound_pcm(int id, uint32_t offset, uint32_t count) {
+	rust::Vec<float> samples;
+	auto sound = sounds.at(id);
+
+	FMOD_SOUND_FORMAT format = FMOD_SOUND_FORMAT_NONE;
+	int channels = 0, bits = 0;
+	result = sound->getFormat(nullptr, &format, &channels, &bits);
+	if (!ERRCHECK(result) || channels <= 0)
+		return samples;
+
+	switch (format) {
+	case FMOD_SOUND_FORMAT_PCM8:
+	case FMOD_SOUND_FORMAT_PCM16:
+	case FMOD_SOUND_FORMAT_PCM24:
+	case FMOD_SOUND_FORMAT_PCM32:
+	case FMOD_SOUND_FORMAT_PCMFLOAT:
+		break;
+	default:
+		error_msg("read_sound_pcm: sound %d is not PCM", id);
+		return samples;
+	}
+
+	FMOD_MODE mode = 0;
+	result = sound->getMode(&mode);
+	if (!ERRCHECK(result))
+		return samples;
+	if (mode & FMOD_CREATESTREAM) {
+		error_msg("read_sound_pcm: sound %d is streamed", id);
+		return samples;
+	}
+
+	unsigned length = 0;
+	result = sound->getLength(&length, FMOD_TIMEUNIT_PCM);
+	if (!ERRCHECK(result) || offset >= length)
+		return samples;
+	count = std::min(count, length - offset);
+
+	const unsigned frame_bytes = channels * bits / 8;
+	void *ptr1 = nullptr, *ptr2 = nullptr;
+	unsigned len1 = 0, len2 = 0;
+	result = sound->lock(offset * frame_bytes, count * frame_bytes, &ptr1, &ptr2, &len1, &len2);
+	if (!ERRCHECK(result))
+		return samples;
+
+	// lock may return data in two parts
+	std::vector<uint8_t> data(len1 + len2);
+	if (len1) memcpy(data.data(), ptr1, len1);
+	if (len2) memcpy(data.data() + len1, ptr2, len2);
+
+	result = sound->unlock(ptr1, ptr2, len1, len2);
 	ERRCHECK(result);
+
+	const size_t frames = data.size() / frame_bytes;
+	samples.reserve(frames);
+
+	const uint8_t* p = data.data();
+	for (size_t i=0; i<frames; ++i) {
+		float sum = 0;
+		for (int c=0; c<channels; ++c) {
+			switch (format) {
+			case FMOD_SOUND_FORMAT_PCM8:
+				sum += int8_t(p[0]) / 128.f;
+				break;
+			case FMOD_SOUND_FORMAT_PCM16: {
+				int16_t v; memcpy(&v, p, 2);
+				sum += v / 32768.f;
+				break; }
+			case FMOD_SOUND_FORMAT_PCM24: {
+				// shifted as unsigned, since shifting into the sign bit is UB
+				uint32_t u = (uint32_t(p[0]) << 8) | (uint32_t(p[1]) << 16) | (uint32_t(p[2]) << 24);
+				int32_t v = static_cast<int32_t>(u);
+				sum += (v >> 8) / 8388608.f;
+				break; }
+			case FMOD_SOUND_FORMAT_PCM32: {
+				int32_t v; memcpy(&v, p, 4);
+				sum += v / 2147483648.f;
+				break; }
+			default: {
+				float v; memcpy(&v, p, 4);
+				sum += v;
+				break; }
+			}
+			p += bits / 8;
+		}
+		samples.push_back(sum / channels);
+	}
+
+	return samples;
 }
 
-void Bridge::update_group(GroupParams params) {
-	auto& group = groups[params.user_id];
+uint32_t Bridge::get_sound_memory(int id) {
+	auto sound = sounds.at(id);
 
-	// create group if needed
-	if (!group) {
-		const auto group_name = std::to_string(params.user_id);
+	if (bank_entries.count(id))
+		return 0;
 
-		result = system->createChannelGroup(group_name.c_str(), &group);
-		if (!ERRCHECK(result))
-			return;
+	auto stream = streams.find(id);
+	if (stream != streams.end())
+		return stream->second.contents.size(); // empty if streamed from file
 
-		// TODO(later): is it possible to reduce ramp duration?
-		result = group->setVolumeRamp(true); // enable smooth change of volume
+	FMOD_MODE mode = 0;
+	result = sound->getMode(&mode);
+	if (!ERRCHECK(result))
+		return 0;
+
+	unsigned length = 0;
+	if (mode & FMOD_CREATECOMPRESSEDSAMPLE) {
+		result = sound->getLength(&length, FMOD_TIMEUNIT_RAWBYTES);
 		ERRCHECK(result);
+		return length;
 	}
 
-	result = group->setVolume(params.volume);
-	ERRCHECK(result);
-}
+	int channels = 0, bits = 0;
+	result = sound->getFormat(nullptr, nullptr, &channels, &bits);
+	if (!ERRCHECK(result))
+		return 0;
 
-int Bridge::load_audio_file(AudioFileParams params) {
-	int flags = FMOD_3D | FMOD_LOOP_NORMAL; // allow spatial usage and being looped
-	FMOD::Sound* sound = nullptr;
+	result = sound->getLength(&length, FMOD_TIMEUNIT_PCM);
+	if (!ERRCHECK(result))
+		return 0;
 
-	if (!params.filename.empty()) {
-		flags |= FMOD_CREATESTREAM; // don't load whole file into memory
+	return uint64_t(length) * channels * bits / 8;
+}
 
-		result = system->createSound(params.filename.c_str(), flags, nullptr, &sound);
-		if (!ERRCHECK(result)) {
-			info_msg("Path to the file: \"%s\"", params.filename.c_str());
-			return -1;
-		}
-	}
-	else if (!params.file_contents.empty()) {
-		flags |= FMOD_OPENMEMORY;
+rust::Vec<SoundTag> Bridge::get_sound_tags(int id) {
+	rust::Vec<SoundTag> tags;
+	auto sound = sounds.at(id);
 
-		FMOD_CREATESOUNDEXINFO exinfo = {};
-		exinfo.cbsize = sizeof(FMOD_CREATESOUNDEXINFO);
-    	exinfo.length = params.file_contents.size();
+	int count = 0;
+	result = sound->getNumTags(&count, nullptr);
+	if (!ERRCHECK(result))
+		return tags;
 
-		result = system->createSound((const char*) params.file_contents.data(), flags, &exinfo, &sound);
+	for (int i=0; i<count; ++i) {
+		FMOD_TAG tag = {};
+		result = sound->getTag(nullptr, i, &tag);
 		if (!ERRCHECK(result))
-			return -1;
-	}
-	else {
-		error_msg("No sound data");
-		return -1;
-	}
-	
-	return sparse_array_insert(sounds, sound);
-}
+			continue;
 
-void Bridge::free_audio_file(int i) {
-	auto& sound = sounds.at(i);
+		if (tag.datatype != FMOD_TAGDATATYPE_STRING && tag.datatype != FMOD_TAGDATATYPE_STRING_UTF8)
+			continue;
 
-	result = sound->release();
-	ERRCHECK(result);
+		// data may or may not be null-terminated
+		auto data = static_cast<const char*>(tag.data);
+		std::string value(data, strnlen(data, tag.datalen));
 
-	sound = nullptr;
+		SoundTag out;
+		out.name = rust::String::lossy(tag.name);
+		out.value = rust::String::lossy(value);
+		tags.push_back(std::move(out));
+	}
+	return tags;
 }
 
 int Bridge::play_channel(ChannelParams params) {
-	auto& source = sounds.at(params.file_id);
+	// checked before stream instance is opened, so it isn't left unused
+	if (params.reverse && streams.count(params.file_id)) {
+		error_msg("Streamed sound can't be played in reverse");
+		return -1;
+	}
+
+	int stream_index = -1;
+	auto source = get_playable_sound(params.file_id, stream_index);
+	if (!source)
+		return -1;
+
+	if (params.steal != StealBehavior::Priority) {
+		int real_playing = 0, max_real = 0;
+		result = system->getChannelsPlaying(nullptr, &real_playing);
+		ERRCHECK(result);
+		result = system->getSoftwareChannels(&max_real);
+		ERRCHECK(result);
+
+		if (real_playing >= max_real) {
+			if (params.steal == StealBehavior::FailNew)
+				return -1; // not an error
+			steal_channel(params.steal == StealBehavior::Oldest); // if there is nothing to steal, FMOD will handle it
+		}
+	}
 
 	FMOD::Channel* channel = nullptr;
 	result = system->playSound(source, get_group(params.group_id), true, &channel); // sound starts paused
 	if (!ERRCHECK(result))
 		return -1;
 
+	if (stream_index != -1)
+		streams.at(params.file_id).channels[stream_index] = channel;
+
 	// set all parameters (before unpausing the sound)
 
 	if (params.is_positional) {
-		result = channel->setMode(FMOD_3D);
+		result = channel->setMode(FMOD_3D | (params.ignore_geometry ? FMOD_3D_IGNOREGEOMETRY : 0));
 		ERRCHECK(result);
 
 		auto position = vector(params.position);
@@ -271,21 +1509,26 @@ int Bridge::play_channel(ChannelParams params) {
 
 		result = channel->set3DMinMaxDistance(params.min_distance, params.max_distance);
 		ERRCHECK(result);
+
+		result = channel->set3DSpread(params.spread);
+		ERRCHECK(result);
 	}
 	else {
 		result = channel->setMode(FMOD_2D);
 		ERRCHECK(result);
 	}
 
-	if (params.startup_delay) {
+	if (params.startup_delay || params.start_clock) {
 		// Delay is set used global clock (or clock of parent DSP).
 		// We need to get current clock value and convert delay into clock ticks.
 
-		unsigned long long parentclock = 0; // delay uses parent clock, not channel one
+		unsigned long long parentclock = params.start_clock; // delay uses parent clock, not channel one
 		int ticks_per_second = 0; // sample rate = clock ticks per second
 
-		result = channel->getDSPClock(nullptr, &parentclock);
-		ERRCHECK(result);
+		if (!parentclock) {
+			result = channel->getDSPClock(nullptr, &parentclock);
+			ERRCHECK(result);
+		}
 
 		result = system->getSoftwareFormat(&ticks_per_second, nullptr, nullptr);
 		ERRCHECK(result);
@@ -304,6 +1547,12 @@ int Bridge::play_channel(ChannelParams params) {
 	result = channel->setLoopCount(params.looped ? -1 : 0); // -1 for infinite repeat
 	ERRCHECK(result);
 
+	if (params.looped && params.loop_end > params.loop_start) {
+		// end point is inclusive
+		result = channel->setLoopPoints(params.loop_start, FMOD_TIMEUNIT_MS, params.loop_end - 1, FMOD_TIMEUNIT_MS);
+		ERRCHECK(result);
+	}
+
 	result = channel->setVolume(params.volume);
 	ERRCHECK(result);
 
@@ -313,16 +1562,68 @@ int Bridge::play_channel(ChannelParams params) {
 	result = channel->setPriority(params.priority);
 	ERRCHECK(result);
 
+	if (params.reverse) {
+		// negative frequency plays backwards, starting from the end
+		float frequency = 0.;
+		result = channel->getFrequency(&frequency);
+		ERRCHECK(result);
+
+		result = channel->setFrequency(-std::abs(frequency));
+		ERRCHECK(result);
+
+		if (params.start_offset == 0) {
+			unsigned int length = 0;
+			result = source->getLength(&length, FMOD_TIMEUNIT_PCM);
+			ERRCHECK(result);
+
+			if (length != 0) {
+				result = channel->setPosition(length - 1, FMOD_TIMEUNIT_PCM);
+				ERRCHECK(result);
+			}
+		}
+		else {
+			unsigned int length = 0;
+			result = source->getLength(&length, FMOD_TIMEUNIT_MS);
+			ERRCHECK(result);
+
+			result = channel->setPosition(length - std::min(length, params.start_offset), FMOD_TIMEUNIT_MS);
+			ERRCHECK(result);
+		}
+	}
+	else if (params.start_offset != 0) {
+		result = channel->setPosition(params.start_offset, FMOD_TIMEUNIT_MS);
+		ERRCHECK(result);
+	}
+
 	// all parameters are set, start playback
 
 	result = channel->setPaused(false);
 	ERRCHECK(result);
 
-	return sparse_array_insert(channels, channel);
+	int id = sparse_array_insert(channels, channel);
+	if (channel_ages.size() < channels.size())
+		channel_ages.resize(channels.size());
+	channel_ages[id] = ++channel_counter;
+	return id;
+}
+
+uint64_t Bridge::get_sync_clock(int group_id) {
+	unsigned long long clock = 0;
+	result = get_group(group_id)->getDSPClock(&clock, nullptr);
+	ERRCHECK(result);
+
+	// mixer may be already processing current block, so skip it and the next one
+	unsigned int buffer_length = 0;
+	result = system->getDSPBufferSize(&buffer_length, nullptr);
+	ERRCHECK(result);
+
+	return clock + 2 * buffer_length;
 }
 
 bool Bridge::update_channel(int i, ChannelUpdateParams params) {
 	auto& channel = channels.at(i);
+	if (!channel)
+		return false; // already freed
 
 	bool is_playing = false;
 	result = channel->isPlaying(&is_playing);
@@ -340,6 +1641,11 @@ bool Bridge::update_channel(int i, ChannelUpdateParams params) {
 		ERRCHECK(result);
 	}
 
+	if (params.set_distance) {
+		result = channel->set3DMinMaxDistance(params.min_distance, params.max_distance);
+		ERRCHECK(result);
+	}
+
 	if (params.set_volume_etc) {
 		result = channel->setVolume(params.volume);
 		ERRCHECK(result);
@@ -349,11 +1655,89 @@ bool Bridge::update_channel(int i, ChannelUpdateParams params) {
 
 		result = channel->setPriority(params.priority);
 		ERRCHECK(result);
+
+		FMOD_MODE mode = 0;
+		result = channel->getMode(&mode);
+		ERRCHECK(result);
+
+		if (mode & FMOD_3D) {
+			result = channel->set3DSpread(params.spread);
+			ERRCHECK(result);
+		}
 	}
 
 	return is_playing;
 }
 
+void Bridge::update_channels(rust::Slice<const ChannelUpdate> updates) {
+	for (auto& update : updates)
+		update_channel(update.id, update.params);
+}
+
+void Bridge::set_channel_group(int i, int group_id) {
+	auto& channel = channels.at(i);
+
+	result = channel->setChannelGroup(get_group(group_id));
+
+	if (result != FMOD_ERR_INVALID_HANDLE && result != FMOD_ERR_CHANNEL_STOLEN)
+		ERRCHECK(result); // sound stopped or stolen
+}
+
+void Bridge::set_channel_lowpass(int i, float cutoff) {
+	if (cutoff <= 0.) {
+		remove_channel_dsp(i, FMOD_DSP_TYPE_LOWPASS_SIMPLE);
+		return;
+	}
+
+	auto dsp = get_channel_dsp(i, FMOD_DSP_TYPE_LOWPASS_SIMPLE);
+	if (!dsp)
+		return;
+
+	result = dsp->setParameterFloat(FMOD_DSP_LOWPASS_SIMPLE_CUTOFF, cutoff);
+	ERRCHECK(result);
+}
+
+void Bridge::set_channel_mute(int i, bool mute) {
+	auto& channel = channels.at(i);
+
+	result = channel->setMute(mute);
+
+	if (result != FMOD_ERR_INVALID_HANDLE && result != FMOD_ERR_CHANNEL_STOLEN)
+		ERRCHECK(result); // sound stopped or stolen
+}
+
+void Bridge::set_channel_distortion(int i, float level) {
+	if (level <= 0.) {
+		remove_channel_dsp(i, FMOD_DSP_TYPE_DISTORTION);
+		return;
+	}
+
+	auto dsp = get_channel_dsp(i, FMOD_DSP_TYPE_DISTORTION);
+	if (!dsp)
+		return;
+
+	result = dsp->setParameterFloat(FMOD_DSP_DISTORTION_LEVEL, std::min(level, 1.f));
+	ERRCHECK(result);
+}
+
+void Bridge::set_channel_chorus(int i, float rate, float depth, float mix) {
+	if (mix <= 0.) {
+		remove_channel_dsp(i, FMOD_DSP_TYPE_CHORUS);
+		return;
+	}
+
+	auto dsp = get_channel_dsp(i, FMOD_DSP_TYPE_CHORUS);
+	if (!dsp)
+		return;
+
+	result = dsp->setParameterFloat(FMOD_DSP_CHORUS_RATE, std::clamp(rate, 0.f, 20.f));
+	ERRCHECK(result);
+	result = dsp->setParameterFloat(FMOD_DSP_CHORUS_DEPTH, std::clamp(depth, 0.f, 1.f) * 100.f);
+	ERRCHECK(result);
+	result = dsp->setParameterFloat(FMOD_DSP_CHORUS_MIX, std::min(mix, 1.f) * 100.f);
+	ERRCHECK(result);
+}
+
 bool Bridge::is_playing_channel(int i) {
 	auto& channel = channels.at(i);
 
@@ -368,15 +1752,144 @@ bool Bridge::is_playing_channel(int i) {
 	return is_playing;
 }
 
+double Bridge::get_channel_position(int i) {
+	auto& channel = channels.at(i);
+
+	auto freed = freed_positions.find(i);
+	if (freed != freed_positions.end())
+		return freed->second;
+
+	unsigned int position = 0;
+	result = channel->getPosition(&position, FMOD_TIMEUNIT_PCM);
+	if (result == FMOD_ERR_INVALID_HANDLE || result == FMOD_ERR_CHANNEL_STOLEN) // sound stopped
+		return -1;
+	if (!ERRCHECK(result))
+		return -1;
+
+	FMOD::Sound* sound = nullptr;
+	result = channel->getCurrentSound(&sound);
+	if (!ERRCHECK(result) || !sound)
+		return -1;
+
+	float frequency = 0; // PCM samples per second
+	result = sound->getDefaults(&frequency, nullptr);
+	if (!ERRCHECK(result) || frequency <= 0)
+		return -1;
+
+	return position / (double) frequency;
+}
+
+ChannelEnd Bridge::get_channel_end(int i) {
+	auto& channel = channels.at(i);
+	ChannelEnd end = {};
+	end.remaining = -1;
+
+	unsigned int position = 0;
+	result = channel->getPosition(&position, FMOD_TIMEUNIT_PCM);
+	if (result == FMOD_ERR_INVALID_HANDLE || result == FMOD_ERR_CHANNEL_STOLEN) // sound stopped
+		return end;
+	if (!ERRCHECK(result))
+		return end;
+
+	int loop_count = 0; // sounds are created with FMOD_LOOP_NORMAL, so only count is checked
+	result = channel->getLoopCount(&loop_count);
+	if (!ERRCHECK(result) || loop_count != 0) // looped channel never ends
+		return end;
+
+	FMOD::Sound* sound = nullptr;
+	result = channel->getCurrentSound(&sound);
+	if (!ERRCHECK(result) || !sound)
+		return end;
+
+	unsigned int length = 0;
+	result = sound->getLength(&length, FMOD_TIMEUNIT_PCM);
+	if (!ERRCHECK(result))
+		return end;
+
+	float frequency = 0, pitch = 0; // negative frequency means reversed playback
+	result = channel->getFrequency(&frequency);
+	if (!ERRCHECK(result))
+		return end;
+	result = channel->getPitch(&pitch);
+	if (!ERRCHECK(result))
+		return end;
+
+	unsigned long long parentclock = 0; // delay uses parent clock, not channel one
+	result = channel->getDSPClock(nullptr, &parentclock);
+	if (!ERRCHECK(result))
+		return end;
+
+	int ticks_per_second = 0; // sample rate = clock ticks per second
+	result = system->getSoftwareFormat(&ticks_per_second, nullptr, nullptr);
+	if (!ERRCHECK(result))
+		return end;
+
+	const double speed = std::abs(frequency * pitch); // samples per second
+	if (speed <= 0)
+		return end;
+	const double samples_left = frequency < 0 ? position : length - std::min(position, length);
+
+	end.remaining = samples_left / speed;
+	end.clock = parentclock + (uint64_t) std::llround(end.remaining * ticks_per_second);
+	return end;
+}
+
+Occlusion Bridge::get_channel_occlusion(int i) {
+	auto& channel = channels.at(i);
+	Occlusion occlusion = {};
+
+	FMOD_MODE mode = 0;
+	result = channel->getMode(&mode);
+	if (result == FMOD_ERR_INVALID_HANDLE || result == FMOD_ERR_CHANNEL_STOLEN) // sound stopped
+		return occlusion;
+	if (!ERRCHECK(result) || !(mode & FMOD_3D) || (mode & FMOD_3D_IGNOREGEOMETRY))
+		return occlusion;
+
+	FMOD_VECTOR position = {};
+	result = channel->get3DAttributes(&position, nullptr);
+	if (!ERRCHECK(result))
+		return occlusion;
+
+	FMOD_VECTOR listener = {};
+	result = system->get3DListenerAttributes(0, &listener, nullptr, nullptr, nullptr);
+	if (!ERRCHECK(result))
+		return occlusion;
+
+	result = system->getGeometryOcclusion(&listener, &position, &occlusion.direct, &occlusion.reverb);
+	ERRCHECK(result);
+	return occlusion;
+}
+
+void Bridge::stop_channel(int i) {
+	auto& channel = channels.at(i);
+
+	result = channel->stop();
+	
+	if (result != FMOD_ERR_INVALID_HANDLE && result != FMOD_ERR_CHANNEL_STOLEN)
+		ERRCHECK(result); // sound stopped or stolen
+}
+
 void Bridge::free_channel(int i) {
 	auto& channel = channels.at(i);
 
+	auto dsps = channel_dsps.find(i);
+	if (dsps != channel_dsps.end()) {
+		for (auto& dsp : dsps->second) {
+			channel->removeDSP(dsp.second); // fails if channel stopped or stolen
+
+			result = dsp.second->release();
+			ERRCHECK(result);
+		}
+		channel_dsps.erase(dsps);
+	}
+
 	result = channel->stop();
 	
 	if (result != FMOD_ERR_INVALID_HANDLE && result != FMOD_ERR_CHANNEL_STOLEN)
 		ERRCHECK(result); // sound stopped or stolen
 
 	channel = nullptr;
+	freed_positions.erase(i);
 }
 
 int Bridge::add_geometry(Geometry params) {
@@ -402,7 +1915,11 @@ int Bridge::add_geometry(Geometry params) {
 		ERRCHECK(result);
 	}
 
-	return sparse_array_insert(geometries, geometry);
+	int id = sparse_array_insert(geometries, geometry);
+	set_geometry_transform(id, params.transform);
+	if (!params.active)
+		set_geometry_active(id, false);
+	return id;
 }
 
 void Bridge::free_geometry(int i) {
@@ -414,12 +1931,116 @@ void Bridge::free_geometry(int i) {
 	geometry = nullptr;
 }
 
-int Bridge::add_reverb(Reverb params) {
-	FMOD::Reverb3D* reverb = nullptr;
-	result = system->createReverb3D(&reverb);
+void Bridge::set_geometry_transform(int i, GeometryTransform transform) {
+	auto& geometry = geometries.at(i);
+
+	auto position = vector(transform.position);
+	auto forward = vector(transform.forward);
+	auto up = vector(transform.up);
+	auto scale = vector(transform.scale);
+
+	result = geometry->setPosition(&position);
+	ERRCHECK(result);
+
+	result = geometry->setRotation(&forward, &up);
+	ERRCHECK(result);
+
+	result = geometry->setScale(&scale);
+	ERRCHECK(result);
+}
+
+void Bridge::set_geometry_active(int i, bool active) {
+	auto& geometry = geometries.at(i);
+
+	result = geometry->setActive(active);
+	ERRCHECK(result);
+}
+
+void Bridge::set_geometry_occlusion(int i, float direct_occlusion, float reverb_occlusion) {
+	auto& geometry = geometries.at(i);
+
+	int polygon_count = 0;
+	result = geometry->getNumPolygons(&polygon_count);
 	if (!ERRCHECK(result))
-		return -1;
-	
+		return;
+
+	for (int polygon = 0; polygon < polygon_count; ++polygon) {
+		result = geometry->setPolygonAttributes(polygon, direct_occlusion, reverb_occlusion, true);
+		ERRCHECK(result);
+	}
+}
+
+rust::Vec<uint8_t> Bridge::save_geometry(int i) {
+	auto& geometry = geometries.at(i);
+	rust::Vec<uint8_t> data;
+
+	int size = 0;
+	result = geometry->save(nullptr, &size);
+	if (!ERRCHECK(result))
+		return data;
+
+	std::vector<uint8_t> buffer(size);
+	result = geometry->save(buffer.data(), &size);
+	if (!ERRCHECK(result))
+		return data;
+
+	data.reserve(buffer.size());
+	for (auto byte : buffer)
+		data.push_back(byte);
+	return data;
+}
+
+Geometry Bridge::load_geometry(rust::Slice<const uint8_t> data) {
+	Geometry params = {};
+
+	FMOD::Geometry* geometry = nullptr;
+	result = system->loadGeometry(data.data(), data.size(), &geometry);
+	if (!ERRCHECK(result))
+		return params;
+
+	int polygon_count = 0;
+	result = geometry->getNumPolygons(&polygon_count);
+	ERRCHECK(result);
+
+	for (int polygon = 0; polygon < polygon_count; ++polygon) {
+		if (polygon == 0) {
+			bool double_sided = true; // unused value
+			result = geometry->getPolygonAttributes(polygon, &params.direct_occlusion, &params.reverb_occlusion, &double_sided);
+			ERRCHECK(result);
+		}
+
+		int vertex_count = 0;
+		result = geometry->getPolygonNumVertices(polygon, &vertex_count);
+		if (!ERRCHECK(result))
+			continue;
+
+		Polygon out = {};
+		for (int vertex = 0; vertex < vertex_count; ++vertex) {
+			FMOD_VECTOR v = {};
+			result = geometry->getPolygonVertex(polygon, vertex, &v);
+			ERRCHECK(result);
+			out.vertices.push_back({v.x, v.y, v.z});
+		}
+		params.polygons.push_back(std::move(out));
+	}
+
+	result = geometry->release();
+	ERRCHECK(result);
+
+	return params;
+}
+
+Occlusion Bridge::get_geometry_occlusion(Vector listener, Vector source) {
+	FMOD_VECTOR listener_position = vector(listener);
+	FMOD_VECTOR source_position = vector(source);
+
+	Occlusion occlusion = {};
+	result = system->getGeometryOcclusion(&listener_position, &source_position, &occlusion.direct, &occlusion.reverb);
+	ERRCHECK(result);
+	return occlusion;
+}
+
+static FMOD_REVERB_PROPERTIES reverb_properties(const ReverbProps& params) {
 	FMOD_REVERB_PROPERTIES prop = FMOD_PRESET_GENERIC;
 	prop.DecayTime = params.decay_time;
 	prop.EarlyDelay = params.early_delay;
@@ -433,7 +2054,16 @@ int Bridge::add_reverb(Reverb params) {
 	prop.HighCut = params.high_cut;
 	prop.EarlyLateMix = params.early_late_mix;
 	prop.WetLevel = params.wet_level;
+	return prop;
+}
 
+int Bridge::add_reverb(Reverb params) {
+	FMOD::Reverb3D* reverb = nullptr;
+	result = system->createReverb3D(&reverb);
+	if (!ERRCHECK(result))
+		return -1;
+	
+	FMOD_REVERB_PROPERTIES prop = reverb_properties(params.props);
 	result = reverb->setProperties(&prop);
 	ERRCHECK(result);
 
@@ -453,9 +2083,22 @@ void Bridge::free_reverb(int i) {
 	reverb = nullptr;
 }
 
-std::unique_ptr<Bridge> create(InitParams params) {
+void Bridge::set_global_reverb(int instance, bool enabled, ReverbProps props) {
+	FMOD_REVERB_PROPERTIES prop = FMOD_PRESET_OFF;
+	if (enabled)
+		prop = reverb_properties(props);
+
+	result = system->setReverbProperties(instance, &prop);
+	ERRCHECK(result);
+}
+
+std::unique_ptr<Bridge> create(InitParams params, InitError& error) {
 	auto p = std::make_unique<Bridge>();
-	if (!p->init(std::move(params)))
+	if (!p->init(std::move(params))) {
+		// init fails right after an FMOD call fails
+		error.code = p->result;
+		error.message = FMOD_ErrorString(p->result);
 		return {};
+	}
 	return p;
 }
@@ -1,23 +1,49 @@
 #ifndef BRIDGE_H
 #define BRIDGE_H
 
+#include <cstdint>
 #include <memory>
+#include <mutex>
+#include <string>
 #include <unordered_map>
+#include <unordered_set>
 #include <vector>
 
 #include "../fmod/include/fmod.hpp"
+#include "rust/cxx.h"
 
 // Forward declarations for structs generated by cxx-bridge.
 // See bridge.rs for description
+struct Vector;
 struct InitParams;
+struct EngineInfo;
+struct EngineStats;
+struct MemoryStats;
 struct EngineParams;
+struct LimiterParams;
 struct GroupParams;
+struct GroupStats;
+struct GroupEffect;
+struct EqBand;
 struct AudioFileParams;
 struct ChannelParams;
 struct ChannelUpdateParams;
+struct ChannelUpdate;
 struct ListenerParams;
+enum class FileOpenState : uint8_t;
 struct Geometry;
+struct GeometryTransform;
+struct Occlusion;
+struct ChannelEnd;
 struct Reverb;
+struct ReverbProps;
+struct CapturedOutput;
+struct SoundInfo;
+struct SoundTag;
+struct GeneratorParams;
+struct AudioGeneratorBox;
+struct InitError;
+struct BridgeError;
 
 // Interface - FMOD wrapper.
 // Visible by Rust.
@@ -25,7 +51,40 @@ struct Bridge {
 	FMOD::System* system = {};
 	FMOD_RESULT result;
 
+	/// Error reported by 'report_error'
+	struct Error {
+		std::string message;
+		/// FMOD_RESULT, FMOD_OK if it's not an FMOD error
+		int code = FMOD_OK;
+	};
+	/// Reported errors which weren't taken yet, oldest first.
+	/// New errors are dropped if there are already MAX_ERRORS of them.
+	std::vector<Error> errors;
+	static constexpr size_t MAX_ERRORS = 256;
+
 	std::unordered_map<int, FMOD::ChannelGroup*> groups;
+	/// Volume of groups which are created when sound is played in them
+	float default_group_volume = 1;
+	/// Groups which were created that way and weren't updated since
+	std::unordered_set<int> default_groups;
+
+	/// Limiter on the master group, nullptr if disabled
+	FMOD::DSP* master_limiter = nullptr;
+	/// Low-pass filter on the master group, nullptr if disabled
+	FMOD::DSP* master_lowpass = nullptr;
+	/// See set_mixer_suspended
+	bool mixer_suspended = false;
+
+	/// Samples copied from the master group by capture DSP (on the mixer thread)
+	struct OutputCapture {
+		std::mutex mutex;
+		/// Interleaved
+		std::vector<float> samples;
+		int channels = 0;
+	};
+	/// DSP on the master group which copies output into 'capture', nullptr if disabled
+	FMOD::DSP* capture_dsp = nullptr;
+	std::unique_ptr<OutputCapture> capture = std::make_unique<OutputCapture>();
 
 	// These are sparsed arrays - new values will fill vacant (nullptr) places if available,
 	// instead of increasing vector size.
@@ -36,12 +95,100 @@ struct Bridge {
 	std::vector<FMOD::Geometry*> geometries;
 	std::vector<FMOD::Reverb3D*> reverbs;
 
+	/// When channel was started, greater value means later. Same indices as 'channels'
+	std::vector<uint64_t> channel_ages;
+	uint64_t channel_counter = 0;
+
+	/// Positions (in seconds) of channels which were playing a sound when it was unloaded.
+	/// Key is channel ID
+	std::unordered_map<int, double> freed_positions;
+
+	/// Effects added to channels. Key is channel ID, then DSP type
+	std::unordered_map<int, std::unordered_map<int, FMOD::DSP*>> channel_dsps;
+
+	/// Handles of loaded DSP plugins, index is ID
+	std::vector<unsigned int> plugins;
+	/// Plugin effects on groups in order of processing, as (plugin ID, DSP). Key is group user ID.
+	/// DSP is nullptr if it failed to be created.
+	std::unordered_map<int, std::vector<std::pair<int, FMOD::DSP*>>> group_effects;
+	/// Built-in effects added to groups. Key is group user ID, then DSP type
+	std::unordered_map<int, std::unordered_map<int, FMOD::DSP*>> group_dsps;
+
+	/// Streamed sound can be played only once at a time, so to play it several times
+	/// at once the file is opened again.
+	struct Stream {
+		/// If empty, 'contents' is used
+		std::string filename;
+		/// File in memory, instances point to it
+		std::vector<uint8_t> contents;
+		/// First one is the same as in 'sounds' array
+		std::vector<FMOD::Sound*> instances;
+		/// Channel last started for the instance with the same index
+		std::vector<FMOD::Channel*> channels;
+	};
+
+	/// Key is sound ID
+	std::unordered_map<int, Stream> streams;
+	/// Number of all opened stream instances
+	int open_streams = 0;
+	int max_streams = 0;
+
+	/// Sound bank (FSB file), entries of which are its subsounds
+	struct Bank {
+		FMOD::Sound* sound = nullptr;
+		/// Number of entries in 'sounds' array which belong to this bank
+		int users = 0;
+		/// 'free_bank' was called, bank is released together with its last used entry
+		bool freed = false;
+	};
+	/// Key is bank ID
+	std::unordered_map<int, Bank> banks;
+	/// ID for the next bank
+	int bank_counter = 0;
+	/// Key is sound ID, value is bank ID
+	std::unordered_map<int, int> bank_entries;
+
+	/// Sources of procedurally-generated sounds. Key is sound ID
+	std::unordered_map<int, rust::Box<AudioGeneratorBox>> generators;
+
+	/// Files which are loaded in background, freed when they are ready.
+	/// Key is sound ID
+	std::unordered_map<int, std::vector<uint8_t>> loading_files;
+
 	/// Returns false on error. Must be called only once per bridge lifetime.
 	bool init(InitParams params);
 	~Bridge();
 
+	/// Logs printf-formatted message as error and records it, see take_errors
+	void report_error(FMOD_RESULT code, const char* fmt, ...)
+#ifdef __GNUC__
+		__attribute__ ((format (printf, 3, 4)))
+#endif
+	;
+	/// Reports result if it is an error (see ERRCHECK). Returns false on error.
+	bool check_result(FMOD_RESULT result, int line);
+
 	/// Creates group with default parameters if it doesn't exist
 	FMOD::ChannelGroup* get_group(int user_id);
+	/// Returns sound which can be played right now, or nullptr on error.
+	/// For streams 'stream_index' is set to index of used instance, otherwise to -1.
+	FMOD::Sound* get_playable_sound(int id, int& stream_index);
+	/// Opens new instance of the stream, returns nullptr on error
+	FMOD::Sound* open_stream(const Stream& stream);
+	/// Returns effect of specified type on a channel, creates it if it doesn't exist.
+	/// Returns nullptr on error.
+	FMOD::DSP* get_channel_dsp(int id, FMOD_DSP_TYPE type);
+	/// Removes effect of specified type from a channel, if it exists
+	void remove_channel_dsp(int id, FMOD_DSP_TYPE type);
+	/// Returns effect of specified type on a group, creates it if it doesn't exist.
+	/// Effects are processed before plugin effects. Returns nullptr on error.
+	FMOD::DSP* get_group_dsp(int group_id, FMOD_DSP_TYPE type);
+	/// Removes effect of specified type from a group, if it exists
+	void remove_group_dsp(int group_id, FMOD_DSP_TYPE type);
+	/// Stops oldest (or quietest) non-virtual channel, returns false if there are none
+	bool steal_channel(bool oldest);
+	/// Group to which all other groups are connected. Never returns nullptr
+	FMOD::ChannelGroup* get_master_group();
 
 	//
 	// Methods visible in Rust
@@ -49,25 +196,127 @@ struct Bridge {
 
 	/// Should be called frequently to update various internal states
 	void update();
+	/// Number of errors which weren't taken yet
+	size_t error_count() const;
+	/// Returns and removes errors starting from specified index (which is less than or equal to error_count)
+	rust::Vec<BridgeError> take_errors(size_t first);
+	/// Current output format and other information
+	EngineInfo get_engine_info();
+	/// Channel counts and CPU usage
+	EngineStats get_stats();
+	/// Memory used by FMOD
+	MemoryStats get_memory_stats();
 	void update_engine(EngineParams params);
+	/// Adds, updates or removes limiter on the master group.
+	/// It is placed after master group fader (i.e. it's the last DSP before output).
+	void set_master_limiter(LimiterParams params);
+	/// Pitch (playback speed) multiplier for all groups
+	void set_master_pitch(float pitch);
+	/// Adds low-pass filter with specified cutoff frequency (Hz) to the master group.
+	/// Cutoff of zero or less removes the filter.
+	/// It is placed after master group fader, but before the limiter.
+	void set_master_lowpass(float cutoff);
+	/// Pauses or resumes all groups.
+	/// Channels stay "playing" while paused, see is_playing_channel.
+	void set_master_paused(bool paused);
+	/// Suspends or resumes the mixer thread (i.e. when mobile application is in background).
+	/// Does nothing if already in requested state.
+	void set_mixer_suspended(bool suspended);
+	/// Calls update enough times to mix specified duration of audio.
+	/// Has effect only with non-realtime output types.
+	void advance_mixer(uint32_t milliseconds);
+	/// Starts or stops copying master group output (it's the last DSP before output at the moment of enabling).
+	/// Captured samples are kept until taken, even if capture is stopped.
+	void set_output_capture(bool enabled);
+	/// Returns and clears captured samples
+	CapturedOutput take_captured_output();
 
+	/// Sets number of 3D listeners, in [1; 8] range (FMOD_MAX_LISTENERS).
+	/// Sound attenuation is calculated for the nearest one.
+	void set_listener_count(int count);
 	/// Sets new 3D listener state (where user's "ears" are in the world).
-    void update_listener(ListenerParams params);
+	/// Index must be less than listener count.
+	void update_listener(int index, ListenerParams params);
 	/// Creates group if it doesn't exist
 	void update_group(GroupParams params);
+	/// Sets volume of groups which are (or will be) created by playing
+	/// sounds in them, until they are updated
+	void set_default_group_volume(float volume);
+	/// Returns channel counts for all existing groups
+	rust::Vec<GroupStats> get_group_stats();
+	/// Loads DSP plugin from file, returns -1 on error. Plugins can't be unloaded
+	int load_plugin(rust::Str filename);
+	/// Replaces plugin effects on the group, creating group if it doesn't exist.
+	/// If plugins are the same, only parameters are changed (so effect state isn't reset).
+	void set_group_effects(int group_id, rust::Slice<const GroupEffect> effects);
+	/// Sets up to 5 peaking EQ bands on the group, removes EQ if there are none
+	void set_group_eq(int group_id, rust::Slice<const EqBand> bands);
+	/// Level is in [0; 1] range, 0 removes the effect
+	void set_group_distortion(int group_id, float level);
 
 	/// Load sound into engine. Returns ID or -1 on error
 	int load_audio_file(AudioFileParams params);
 	/// Unload sound. ID will be reused
 	void free_audio_file(int id);
+	/// Load FSB file. Returns ID or -1 on error
+	int load_bank(rust::Slice<const uint8_t> file_contents);
+	/// Create stream which gets samples from the generator. Returns ID or -1 on error.
+	/// Generator is dropped when the sound is freed.
+	int load_generator(GeneratorParams params, rust::Box<AudioGeneratorBox> generator);
+	/// Bank is kept in memory while any of its entries are loaded
+	void free_bank(int id);
+	/// Names of subsounds, index is entry index
+	rust::Vec<rust::String> get_bank_entry_names(int id);
+	/// Load subsound of the bank as a sound, without copying it. Returns sound ID or -1 on error.
+	/// Must be unloaded with 'free_audio_file' as usual.
+	int load_bank_entry(int bank_id, int index);
+	/// Length and format of the sound
+	SoundInfo get_sound_info(int id);
+	/// Metadata of the file (i.e. ID3 or Vorbis comments)
+	rust::Vec<SoundTag> get_sound_tags(int id);
+	/// Approximate memory used by the sound data, in bytes.
+	/// Entries of banks share memory with the bank and return 0.
+	uint32_t get_sound_memory(int id);
+	/// Decoded samples mixed to mono, 'offset' and 'count' are in PCM samples.
+	/// Truncated at the end of the sound. Only for sounds loaded as uncompressed PCM.
+	rust::Vec<float> read_sound_pcm(int id, uint32_t offset, uint32_t count);
+	/// Sound can be used only when it's ready
+	FileOpenState get_file_open_state(int id);
 
 	/// Play sound. Returns ID or -1 on error or -2 if sound is not tracked.
 	/// ID won't be reused until 'free_channel' is called.
 	int play_channel(ChannelParams params);
+	/// Returns DSP clock of the group a bit in the future, so that all channels started
+	/// with it as 'start_clock' during this update begin at exactly the same sample.
+	uint64_t get_sync_clock(int group_id);
 	/// Change parameters of playing sound. Returns false if sound stopped
 	bool update_channel(int id, ChannelUpdateParams params);
-	/// Returns true if sound is currently playing, or false otherwise
+	/// Same as calling update_channel for each element.
+	void update_channels(rust::Slice<const ChannelUpdate> updates);
+	/// Move sound to another group (user ID) without interrupting playback
+	void set_channel_group(int id, int group_id);
+	/// Adds low-pass filter with specified cutoff frequency (Hz) to the sound.
+	/// Cutoff of zero or less removes the filter.
+	void set_channel_lowpass(int id, float cutoff);
+	/// Muted channel still advances, unlike paused one
+	void set_channel_mute(int id, bool mute);
+	/// Level is in [0; 1] range, 0 removes the effect
+	void set_channel_distortion(int id, float level);
+	/// Rate is in Hz, depth and mix are in [0; 1] range; mix 0 removes the effect
+	void set_channel_chorus(int id, float rate, float depth, float mix);
+	/// Returns true if sound is currently playing, or false otherwise.
+	/// Paused sounds (including ones paused via parent group) are considered playing.
 	bool is_playing_channel(int id);
+	/// Occlusion by geometry between the sound and the listener.
+	/// Zero for non-spatial sounds and sounds ignoring geometry.
+	Occlusion get_channel_occlusion(int id);
+	/// Playback position in seconds (sample-accurate), or -1 if sound stopped.
+	/// If sound was unloaded while playing, returns position at that moment.
+	double get_channel_position(int id);
+	/// Estimated from current position and playback speed of the channel
+	ChannelEnd get_channel_end(int id);
+	/// Stops playback. ID won't be reused until 'free_channel' is called.
+	void stop_channel(int id);
 	/// Stops playback. ID will be reused.
 	void free_channel(int id);
 
@@ -77,6 +326,20 @@ struct Bridge {
     int add_geometry(Geometry params);
 	/// ID will be reused
     void free_geometry(int id);
+	/// Move already added geometry; polygons aren't rebuilt, so this is cheap.
+	void set_geometry_transform(int id, GeometryTransform transform);
+	/// Inactive geometry is kept, but doesn't occlude anything
+	void set_geometry_active(int id, bool active);
+	/// Change occlusion of all polygons, same as in add_geometry
+	void set_geometry_occlusion(int id, float direct_occlusion, float reverb_occlusion);
+	/// Serialize geometry in FMOD format. Returns empty array on error.
+	rust::Vec<uint8_t> save_geometry(int id);
+	/// Read polygons from data returned by save_geometry, without adding geometry to the world.
+	/// Occlusion is taken from the first polygon, transform isn't set.
+	Geometry load_geometry(rust::Slice<const uint8_t> data);
+
+	/// Occlusion by geometry between two points.
+	Occlusion get_geometry_occlusion(Vector listener, Vector source);
 
 	/// 3D-world reverb sphere. Returns ID or -1 on error.
 	/// Will apply reverb effect to sounds within the sphere.
@@ -84,11 +347,14 @@ struct Bridge {
     int add_reverb(Reverb params);
 	/// ID will be reused
     void free_reverb(int id);
+	/// Non-positional reverb applied to all sounds, instance must be in [0; 3] range.
+	/// Reverb instance stays allocated after being disabled.
+	void set_global_reverb(int instance, bool enabled, ReverbProps props);
 };
 
 /// Create new bridge; initializes it.
 /// Only one bridge can exist at the same time!
-/// Returns nullptr on error.
-std::unique_ptr<Bridge> create(InitParams params);
+/// Returns nullptr on error, setting 'error'.
+std::unique_ptr<Bridge> create(InitParams params, InitError& error);
 
 #endif // BRIDGE_H
@@ -1,4 +1,5 @@
 /// Declarations for Rust <-> C++ bridge
+#[allow(clippy::module_inception)]
 #[cxx::bridge]
 pub mod bridge {
     // Only bridge-specific stuff is documented here, for details see how bridge is
@@ -15,21 +16,239 @@ pub mod bridge {
         z: f32,
     }
 
+    /// Occlusion factors, 0 - not occluded, 1 - fully occluded
+    struct Occlusion {
+        direct: f32,
+        reverb: f32,
+    }
+
+    /// When playback of non-looped channel will reach the end
+    struct ChannelEnd {
+        /// Seconds, -1 if channel stopped or is looped
+        remaining: f64,
+        /// DSP clock of the group (see `ChannelParams::start_clock`)
+        clock: u64,
+    }
+
     struct InitParams {
         max_virtual_channels: i32,
         max_active_channels: i32,
+        /// Total number of streams which can be played at once
+        max_streams: i32,
+        /// Hz, 0 to use default
+        sample_rate: i32,
+        speaker_mode: SpeakerMode,
+        /// Used only with SpeakerMode::Raw
+        raw_speakers: i32,
+        /// Samples, 0 to use default
+        dsp_buffer_length: i32,
+        /// 0 to use default
+        dsp_buffer_count: i32,
+        output_type: OutputType,
+        /// Used only with OutputType::WavWriter and WavWriterNrt, FMOD default if empty
+        wav_output_path: String,
+        /// Allow FMOD profiler to connect
+        profiler: bool,
+        /// 0 to use default
+        profiler_port: i32,
+        /// Max coordinate value for geometry
+        geometry_world_size: f32,
+        /// Which FMOD debug messages are logged
+        log_level: LogLevel,
+    }
+
+    enum LogLevel {
+        Error,
+        Warning,
+        /// All FMOD messages
+        Verbose,
+    }
+
+    #[derive(Default)]
+    struct InitError {
+        /// FMOD_RESULT
+        code: i32,
+        message: String,
+    }
+
+    /// Error reported by the bridge, it's also logged
+    struct BridgeError {
+        message: String,
+        /// FMOD_RESULT, 0 if it's not an FMOD error
+        code: i32,
+    }
+
+    enum OutputType {
+        /// Platform default
+        Auto,
+        NoSound,
+        NoSoundNrt,
+        Wasapi,
+        Alsa,
+        PulseAudio,
+        CoreAudio,
+        WindowsSonic,
+        /// Write output to a file instead of playing it
+        WavWriter,
+        WavWriterNrt,
+    }
+
+    enum SpeakerMode {
+        /// When passed to init, device default is used.
+        /// When returned, speaker mode is not one of listed here.
+        Default,
+        Mono,
+        Stereo,
+        Quad,
+        Surround51,
+        Surround71,
+        Raw,
+    }
+
+    struct EngineStats {
+        /// Number of playing channels, including virtual ones
+        channels_playing: i32,
+        /// Number of non-virtual playing channels
+        real_channels_playing: i32,
+        /// CPU usage of mixer thread, percent
+        cpu_dsp: f32,
+        /// CPU usage of streaming thread, percent
+        cpu_stream: f32,
+        /// CPU usage of geometry thread, percent
+        cpu_geometry: f32,
+        /// CPU usage of update calls, percent
+        cpu_update: f32,
+    }
+
+    /// Bytes allocated by FMOD
+    struct MemoryStats {
+        current: i32,
+        /// Maximum since engine was initialized
+        max: i32,
+    }
+
+    struct EngineInfo {
+        /// Hz
+        sample_rate: i32,
+        speaker_mode: SpeakerMode,
+        speaker_count: i32,
+        dsp_buffer_length: i32,
+        dsp_buffer_count: i32,
+        /// Version of loaded FMOD library, 0xaaaabbcc -> aaaa.bb.cc
+        version: u32,
+        /// Version of FMOD headers bridge was compiled with, same format
+        header_version: u32,
+        /// Auto if not one of listed in the enum
+        output_type: OutputType,
+        /// Empty on error
+        driver_name: String,
+        geometry_world_size: f32,
+    }
+
+    /// Format of samples produced by `AudioGeneratorBox`
+    struct GeneratorParams {
+        channels: i32,
+        /// Hz
+        sample_rate: i32,
+    }
+
+    /// Metadata of the file. Only tags with string values are returned
+    struct SoundTag {
+        name: String,
+        value: String,
+    }
+
+    struct SoundInfo {
+        /// 0xffffffff if unknown
+        length_ms: u32,
+        channels: i32,
+        /// Hz
+        sample_rate: f32,
+        /// Bits per sample
+        bits: i32,
+        file_type: SoundType,
+        sample_format: SampleFormat,
+        /// Streamed or kept compressed in memory
+        decoded_while_playing: bool,
+    }
+
+    enum SoundType {
+        Unknown,
+        Aiff,
+        Flac,
+        Fsb,
+        Mpeg,
+        OggVorbis,
+        Opus,
+        Wav,
+        Other,
+    }
+
+    enum SampleFormat {
+        Unknown,
+        Pcm8,
+        Pcm16,
+        Pcm24,
+        Pcm32,
+        PcmFloat,
+        Compressed,
+    }
+
+    struct CapturedOutput {
+        /// Hz
+        sample_rate: i32,
+        /// 0 if nothing was captured
+        channels: i32,
+        /// Interleaved
+        samples: Vec<f32>,
     }
 
     struct EngineParams {
         doppler_scale: f32,
         distance_scale: f32,
         rolloff_scale: f32,
-        max_world_size: f32,
+    }
+
+    struct LimiterParams {
+        /// If false, limiter is removed and other parameters are ignored
+        enabled: bool,
+        /// Decibels
+        ceiling: f32,
+        /// Milliseconds
+        release_time: f32,
     }
 
     struct GroupParams {
         user_id: i32,
         volume: f32,
+        pitch: f32,
+    }
+
+    struct GroupEffect {
+        /// Returned by load_plugin
+        plugin_id: i32,
+        /// Float parameters in order of their indices
+        params: Vec<f32>,
+    }
+
+    /// Peaking filter
+    struct EqBand {
+        /// Hz
+        frequency: f32,
+        /// Decibels
+        gain: f32,
+        /// Quality factor, higher value means narrower band
+        q: f32,
+    }
+
+    struct GroupStats {
+        user_id: i32,
+        /// Number of non-virtual channels
+        playing: i32,
+        /// Number of virtual channels
+        virtualized: i32,
+        /// Effective volume, includes volume of parent groups
+        audibility: f32,
     }
 
     #[derive(Default)]
@@ -44,6 +263,37 @@ pub mod bridge {
         ///
         /// If defaulted, `custom` is used.
         file_contents: &'a [u8],
+
+        /// Stream `file_contents` (it's copied and kept in memory)
+        /// instead of decoding it all at once
+        stream_contents: bool,
+
+        /// Keep `file_contents` compressed in memory, decoding it while playing.
+        /// Not supported for all formats.
+        compressed: bool,
+
+        /// Return immediately and load `file_contents` in the background
+        /// (see `get_file_open_state`). Ignored for streams.
+        nonblocking: bool,
+    }
+
+    enum FileOpenState {
+        Loading,
+        Ready,
+        /// Sound can't be played and should be freed
+        Error,
+    }
+
+    /// What to do if there are no free non-virtual channels
+    enum StealBehavior {
+        /// Let FMOD virtualize channels based on priority and audibility
+        Priority,
+        /// Stop the oldest non-virtual channel
+        Oldest,
+        /// Stop the quietest non-virtual channel
+        Quietest,
+        /// Don't play the sound (without logging an error)
+        FailNew,
     }
 
     struct ChannelParams {
@@ -53,16 +303,21 @@ pub mod bridge {
         group_id: i32,
         /// Range `[0; 256]`. Lower number means higher priority
         priority: i32,
+        steal: StealBehavior,
 
         // spatial parameters
         /// Sound is spatial - position and velocity are used.
         /// This can't be changed later.
         is_positional: bool,
+        /// Spatial sound isn't occluded by geometry. This can't be changed later.
+        ignore_geometry: bool,
         position: Vector,
         /// per second (used for doppler)
         velocity: Vector,
         min_distance: f32,
         max_distance: f32,
+        /// Angle in degrees over which channels of multichannel sound are spread
+        spread: f32,
 
         // common parameters
         /// Loop playback infinitely
@@ -71,9 +326,21 @@ pub mod bridge {
         volume: f32,
         /// Speed at which to play (this IS playback speed, not pitch!)
         pitch: f32,
+        /// Play backwards from the end. Fails for streamed sounds.
+        reverse: bool,
+        /// Position from which to start playback, milliseconds.
+        /// If reversed, it's measured from the end.
+        start_offset: u32,
+        /// Part of the sound which is repeated if looped, milliseconds.
+        /// Whole sound is repeated if end isn't greater than start.
+        loop_start: u32,
+        loop_end: u32,
 
         /// Pause before actually starting playback, microseconds
         startup_delay: i32,
+        /// If not 0, playback starts at this DSP clock of the group (see
+        /// `get_sync_clock`); `startup_delay` is added to it.
+        start_clock: u64,
     }
 
     #[derive(Default)]
@@ -83,6 +350,10 @@ pub mod bridge {
         set_position: bool,
         position: Vector,
         velocity: Vector,
+        /// If true, set new distance range (spatial-only)
+        set_distance: bool,
+        min_distance: f32,
+        max_distance: f32,
 
         // common parameters
         /// If true, set new volume and other parameters
@@ -90,6 +361,13 @@ pub mod bridge {
         volume: f32,
         pitch: f32,
         priority: i32,
+        /// Spatial-only
+        spread: f32,
+    }
+
+    struct ChannelUpdate {
+        id: i32,
+        params: ChannelUpdateParams,
     }
 
     #[derive(Clone, Default)]
@@ -110,7 +388,22 @@ pub mod bridge {
     struct Geometry {
         direct_occlusion: f32,
         reverb_occlusion: f32,
+        /// In local space, see `transform`
         polygons: Vec<Polygon>,
+        transform: GeometryTransform,
+        /// Inactive geometry doesn't occlude anything
+        active: bool,
+    }
+
+    struct GeometryTransform {
+        /// World position of the local origin
+        position: Vector,
+        /// World direction of local +Z axis, unit
+        forward: Vector,
+        /// World direction of local +Y axis, unit
+        up: Vector,
+        /// Applied in local space
+        scale: Vector,
     }
 
     #[derive(Clone)]
@@ -119,7 +412,11 @@ pub mod bridge {
         max_dist: f32,
         /// World center of the sphere where effect is applied
         position: Vector,
+        props: ReverbProps,
+    }
 
+    #[derive(Clone)]
+    struct ReverbProps {
         decay_time: f32,
         early_delay: f32,
         late_delay: f32,
@@ -137,7 +434,14 @@ pub mod bridge {
     // Rust methods visible in C++
     extern "Rust" {
         fn bridge_log_info(s: &[u8]);
+        /// Error is also recorded by the bridge, see `take_errors`
         fn bridge_log_error(s: &[u8]);
+        /// Message from FMOD itself, not recorded
+        fn bridge_log_fmod(level: LogLevel, s: &[u8]);
+
+        type AudioGeneratorBox;
+        /// Called from FMOD thread. Returns number of samples written.
+        fn generator_fill(generator: &mut AudioGeneratorBox, out: &mut [f32]) -> usize;
     }
 
     // Interface class.
@@ -154,26 +458,92 @@ pub mod bridge {
         // Some methods will crash the application if used incorrectly (i.e. using
         // invalid ID), but should never do it in any other situtation.
 
-        fn create(params: InitParams) -> UniquePtr<Bridge>;
+        fn create(params: InitParams, error: &mut InitError) -> UniquePtr<Bridge>;
         fn update(self: Pin<&mut Bridge>); // must be called periodically
+        fn error_count(self: &Bridge) -> usize;
+        /// Errors starting from `first` index, oldest first
+        fn take_errors(self: Pin<&mut Bridge>, first: usize) -> Vec<BridgeError>;
+        fn get_engine_info(self: Pin<&mut Bridge>) -> EngineInfo;
+        fn get_stats(self: Pin<&mut Bridge>) -> EngineStats;
+        fn get_memory_stats(self: Pin<&mut Bridge>) -> MemoryStats;
         fn update_engine(self: Pin<&mut Bridge>, params: EngineParams);
+        fn set_master_limiter(self: Pin<&mut Bridge>, params: LimiterParams);
+        fn set_master_pitch(self: Pin<&mut Bridge>, pitch: f32);
+        fn set_master_lowpass(self: Pin<&mut Bridge>, cutoff: f32); // 0 to disable
+        fn set_master_paused(self: Pin<&mut Bridge>, paused: bool);
+        fn set_mixer_suspended(self: Pin<&mut Bridge>, suspended: bool);
+        fn advance_mixer(self: Pin<&mut Bridge>, milliseconds: u32);
+        fn set_output_capture(self: Pin<&mut Bridge>, enabled: bool);
+        fn take_captured_output(self: Pin<&mut Bridge>) -> CapturedOutput;
 
-        fn update_listener(self: Pin<&mut Bridge>, params: ListenerParams);
+        fn set_listener_count(self: Pin<&mut Bridge>, count: i32);
+        fn update_listener(self: Pin<&mut Bridge>, index: i32, params: ListenerParams);
         fn update_group(self: Pin<&mut Bridge>, params: GroupParams);
+        fn set_default_group_volume(self: Pin<&mut Bridge>, volume: f32);
+        fn get_group_stats(self: Pin<&mut Bridge>) -> Vec<GroupStats>;
+        fn load_plugin(self: Pin<&mut Bridge>, filename: &str) -> i32; // returns -1 on error
+        fn set_group_effects(self: Pin<&mut Bridge>, group_id: i32, effects: &[GroupEffect]);
+        fn set_group_eq(self: Pin<&mut Bridge>, group_id: i32, bands: &[EqBand]); // empty to remove
+        fn set_group_distortion(self: Pin<&mut Bridge>, group_id: i32, level: f32); // 0 to remove
 
         fn load_audio_file(self: Pin<&mut Bridge>, params: AudioFileParams) -> i32; // returns -1 on error
         fn free_audio_file(self: Pin<&mut Bridge>, id: i32);
+        fn load_generator(
+            self: Pin<&mut Bridge>,
+            params: GeneratorParams,
+            generator: Box<AudioGeneratorBox>,
+        ) -> i32; // returns -1 on error
+        fn load_bank(self: Pin<&mut Bridge>, file_contents: &[u8]) -> i32; // returns -1 on error
+        fn free_bank(self: Pin<&mut Bridge>, id: i32);
+        fn get_bank_entry_names(self: Pin<&mut Bridge>, id: i32) -> Vec<String>;
+        fn load_bank_entry(self: Pin<&mut Bridge>, bank_id: i32, index: i32) -> i32; // returns -1 on error
+        fn get_sound_info(self: Pin<&mut Bridge>, id: i32) -> SoundInfo;
+        fn get_sound_tags(self: Pin<&mut Bridge>, id: i32) -> Vec<SoundTag>;
+        fn get_sound_memory(self: Pin<&mut Bridge>, id: i32) -> u32; // approximate bytes
+        fn read_sound_pcm(self: Pin<&mut Bridge>, id: i32, offset: u32, count: u32) -> Vec<f32>; // empty on error
+        fn get_file_open_state(self: Pin<&mut Bridge>, id: i32) -> FileOpenState;
 
         fn play_channel(self: Pin<&mut Bridge>, params: ChannelParams) -> i32; // returns -1 on error
-        fn update_channel(self: Pin<&mut Bridge>, id: i32, params: ChannelUpdateParams) -> bool;
-        fn is_playing_channel(self: Pin<&mut Bridge>, id: i32) -> bool; // sound haven't stopped yet
+        fn get_sync_clock(self: Pin<&mut Bridge>, group_id: i32) -> u64;
+        fn update_channels(self: Pin<&mut Bridge>, updates: &[ChannelUpdate]);
+        fn set_channel_group(self: Pin<&mut Bridge>, id: i32, group_id: i32);
+        fn set_channel_lowpass(self: Pin<&mut Bridge>, id: i32, cutoff: f32); // 0 to disable
+        fn set_channel_mute(self: Pin<&mut Bridge>, id: i32, mute: bool);
+        fn set_channel_distortion(self: Pin<&mut Bridge>, id: i32, level: f32); // 0 to disable
+        fn set_channel_chorus(self: Pin<&mut Bridge>, id: i32, rate: f32, depth: f32, mix: f32); // mix 0 to disable
+        fn is_playing_channel(self: Pin<&mut Bridge>, id: i32) -> bool; // sound haven't stopped yet (paused sounds are playing)
+        fn get_channel_occlusion(self: Pin<&mut Bridge>, id: i32) -> Occlusion;
+        fn get_channel_position(self: Pin<&mut Bridge>, id: i32) -> f64; // returns -1 if stopped, or position at which sound was freed
+        fn get_channel_end(self: Pin<&mut Bridge>, id: i32) -> ChannelEnd;
+        fn stop_channel(self: Pin<&mut Bridge>, id: i32); // ID stays valid until freed
         fn free_channel(self: Pin<&mut Bridge>, id: i32);
 
         fn add_geometry(self: Pin<&mut Bridge>, params: Geometry) -> i32; // returns -1 on error
         fn free_geometry(self: Pin<&mut Bridge>, id: i32);
+        fn set_geometry_transform(self: Pin<&mut Bridge>, id: i32, transform: GeometryTransform);
+        fn set_geometry_active(self: Pin<&mut Bridge>, id: i32, active: bool);
+        fn set_geometry_occlusion(
+            self: Pin<&mut Bridge>,
+            id: i32,
+            direct_occlusion: f32,
+            reverb_occlusion: f32,
+        );
+        fn save_geometry(self: Pin<&mut Bridge>, id: i32) -> Vec<u8>; // empty on error
+        fn load_geometry(self: Pin<&mut Bridge>, data: &[u8]) -> Geometry; // no polygons on error
 
         fn add_reverb(self: Pin<&mut Bridge>, params: Reverb) -> i32; // returns -1 on error
         fn free_reverb(self: Pin<&mut Bridge>, id: i32);
+        fn get_geometry_occlusion(
+            self: Pin<&mut Bridge>,
+            listener: Vector,
+            source: Vector,
+        ) -> Occlusion;
+        fn set_global_reverb(
+            self: Pin<&mut Bridge>,
+            instance: i32,
+            enabled: bool,
+            props: ReverbProps,
+        );
     }
 }
 
@@ -182,13 +552,38 @@ unsafe impl Send for bridge::Bridge {}
 unsafe impl Sync for bridge::Bridge {}
 
 fn bridge_log_info(s: &[u8]) {
-    bevy::log::info!("{}", String::from_utf8_lossy(s));
+    if LOG_INFO.load(std::sync::atomic::Ordering::Relaxed) {
+        bevy::log::info!("{}", String::from_utf8_lossy(s));
+    }
+}
+
+fn bridge_log_fmod(level: bridge::LogLevel, s: &[u8]) {
+    let message = String::from_utf8_lossy(s);
+    let message = message.trim_end();
+    match level {
+        bridge::LogLevel::Error => bevy::log::error!("FMOD: {message}"),
+        bridge::LogLevel::Warning => bevy::log::warn!("FMOD: {message}"),
+        _ => bevy::log::debug!("FMOD: {message}"),
+    }
 }
 
+/// If false, `bridge_log_info` does nothing
+pub static LOG_INFO: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(true);
+
 fn bridge_log_error(s: &[u8]) {
     bevy::log::error!("{}", String::from_utf8_lossy(s));
 }
 
+/// Writes samples into the buffer, returns how many were written
+pub type GeneratorFn = dyn FnMut(&mut [f32]) -> usize + Send;
+
+/// Source of samples for a sound, owned by the bridge
+pub struct AudioGeneratorBox(pub Box<GeneratorFn>);
+
+fn generator_fill(generator: &mut AudioGeneratorBox, out: &mut [f32]) -> usize {
+    (generator.0)(out).min(out.len())
+}
+
 impl From<bevy::prelude::Vec3> for bridge::Vector {
     fn from(v: bevy::prelude::Vec3) -> Self {
         Self {
@@ -198,3 +593,9 @@ impl From<bevy::prelude::Vec3> for bridge::Vector {
         }
     }
 }
+
+impl From<bridge::Vector> for bevy::prelude::Vec3 {
+    fn from(v: bridge::Vector) -> Self {
+        Self::new(v.x, v.y, v.z)
+    }
+}
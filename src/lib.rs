@@ -8,12 +8,15 @@
 //!     - occlusion by geometry;
 //!     - reverb effect;
 //! - support for most common audio file formats;
-//! - sound groups and global settings.
+//! - sound groups and global settings;
+//! - per-group effects: equalizer, distortion and DSP plugins.
 //!
-//! Missing features:
-//! - per-group DSP;
-//! - support for procedurally-generated sounds;
-//! - loop start and end points for looped sounds.
+//! Optional cargo features:
+//! - `diagnostics` - record channel count and CPU usage as bevy diagnostics;
+//! - `gizmos` - draw debug visualization of spatial audio;
+//! - `serialize` - save and load settings as RON files;
+//! - `physics-integration` - generate geometry and reverb spheres from
+//!   `bevy_rapier3d` colliders.
 
 mod bridge;
 mod plugin;
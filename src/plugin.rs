@@ -1,5 +1,6 @@
 use super::bridge::bridge;
 use bevy::{
+    ecs::query::WorldQuery,
     prelude::*,
     reflect::{TypePath, TypeUuid},
     transform::TransformSystem,
@@ -7,7 +8,14 @@ use bevy::{
 };
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::{sync::Mutex, time::Duration};
+use std::{
+    collections::VecDeque,
+    fmt,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 /// Add [`Handle<AudioSource>`] component to play sound.
 ///
@@ -17,13 +25,27 @@ use std::{sync::Mutex, time::Duration};
 /// Spatial sounds have distance falloff, panning and are affected by other
 /// spatial entities such as reverb zones and geometry.
 ///
-/// When playback stops, the entity will be despawned. Vice-versa, removing
+/// When playback stops, the entity will be despawned (see
+/// [`AudioSettings::auto_despawn`]). Vice-versa, removing
 /// [`Handle<AudioSource>`] stops playback.
+///
+/// If the asset isn't loaded yet, playback starts once it is loaded (see
+/// [`AudioSettings::load_timeout`]).
+///
+/// If the asset is hot-reloaded, sounds which are playing it are restarted
+/// from the same position. If the new sound is shorter than that, looped
+/// sounds wrap around and other sounds are stopped.
 #[derive(TypeUuid, TypePath)]
 #[uuid = "eff1daad-71f0-4f2a-8d08-7a6cbbd6af02"]
 pub struct AudioSource {
     id: EngineId,
 
+    /// Kept to load the source again on [`ReinitAudioEngine`]
+    data: AudioSourceData,
+
+    /// Loaded in background, see [`AudioSource::state`]
+    nonblocking: bool,
+
     /// Default parameters, used only if that component is not present
     /// when handle is added to an entity. Component won't be added to the
     /// entity.
@@ -31,22 +53,95 @@ pub struct AudioSource {
 
     /// Randomize default parameters on each use
     pub randomize_params: bool,
+
+    /// Start each instance from a random position (i.e. so multiple copies
+    /// of the same looped sound don't play in phase).
+    ///
+    /// Ignored if length of the source is unknown.
+    pub randomize_start_offset: bool,
+
+    /// Position from which each instance starts, unless overridden by
+    /// [`AudioStartOffset`] or [`AudioSource::randomize_start_offset`].
+    /// Set by [`AudioSource::trim_silence`].
+    pub start_offset: Duration,
+
+    /// Play on repeat as if [`AudioLoop`] was added to the entity, unless
+    /// entity has [`AudioForceOneShot`]
+    pub looped: bool,
+
+    /// Part of the sound, as `(start, end)`, which is repeated if sound is
+    /// looped (i.e. to skip intro). Playback still starts from the beginning.
+    ///
+    /// If `None` or end isn't greater than start, whole sound is repeated.
+    pub loop_region: Option<(Duration, Duration)>,
+
+    /// Metadata of the file (i.e. ID3 tags or Vorbis comments), name to
+    /// value. Common tags are named `title`, `artist`, `album` and `comment`
+    /// regardless of the format; others keep their original names.
+    ///
+    /// For streamed and non-blocking sources tags are read after the source
+    /// is opened, so they may be empty right after loading.
+    pub tags: HashMap<String, String>,
+
+    /// Tags should be read again, see [`AudioSource::tags`]
+    tags_pending: bool,
+
+    /// See [`AudioSource::loudness`]
+    loudness: Option<f32>,
 }
 
 impl AudioSource {
     /// Load source from file loaded into memory.
     ///
-    /// Returns [`None`] on error.
+    /// Copy of the file is kept while the source exists, so it can be loaded
+    /// again on [`ReinitAudioEngine`]. Sources loaded via [`AssetServer`]
+    /// don't keep it (they are reloaded by [`AssetServer`] instead), so only
+    /// the decoded sound stays in memory.
+    pub fn from_memory(file_contents: &[u8]) -> Result<Self, AudioLoadError> {
+        Self::from_memory_with_mode(file_contents, default())
+    }
+
+    /// Load source from file loaded into memory, storing it as specified.
+    pub fn from_memory_with_mode(
+        file_contents: &[u8],
+        mode: AudioLoadMode,
+    ) -> Result<Self, AudioLoadError> {
+        Self::load(AudioSourceData::Memory(file_contents.to_vec(), mode), false)
+    }
+
+    /// Source loaded by [`AudioFileLoader`], doesn't keep the file
+    fn from_asset_file(
+        file_contents: &[u8],
+        mode: AudioLoadMode,
+        nonblocking: bool,
+    ) -> Result<Self, AudioLoadError> {
+        Self::load_with(
+            AudioSourceData::AssetFile(mode),
+            nonblocking,
+            |bridge, _| load_memory(bridge, file_contents, mode, nonblocking),
+        )
+    }
+
+    /// Same as [`AudioSource::from_memory_with_mode`] with
+    /// [`AudioLoadMode::CompressedInMemory`]
+    pub fn from_memory_compressed(file_contents: &[u8]) -> Result<Self, AudioLoadError> {
+        Self::from_memory_with_mode(file_contents, AudioLoadMode::CompressedInMemory)
+    }
+
+    /// Same as [`AudioSource::from_memory_with_mode`], but returns
+    /// immediately and decodes the file in background, so loading large
+    /// files doesn't cause a hitch. Decoding errors are reported only via
+    /// [`AudioSource::state`].
+    ///
+    /// Sounds using the source are started once it's ready (see
+    /// [`AudioSettings::load_timeout`]).
     ///
-    /// This is how sounds are loaded via [`AssetServer`].
-    pub fn from_memory(file_contents: &[u8]) -> Option<Self> {
-        let mut bridge = BRIDGE.lock().unwrap();
-        let bridge = bridge.as_mut().unwrap().pin_mut();
-        let instance = bridge.load_audio_file(bridge::AudioFileParams {
-            file_contents,
-            ..default()
-        });
-        (instance != -1).then_some(Self::new(instance))
+    /// Has no effect for [`AudioLoadMode::Stream`].
+    pub fn from_memory_nonblocking(
+        file_contents: &[u8],
+        mode: AudioLoadMode,
+    ) -> Result<Self, AudioLoadError> {
+        Self::load(AudioSourceData::Memory(file_contents.to_vec(), mode), true)
     }
 
     /// Stream file from disk as it is being played instead of loading it whole
@@ -56,27 +151,413 @@ impl AudioSource {
     /// and uncompressed file can take a lot of memory._
     ///
     /// **Filename must be relative to current directory, not assets
-    /// directory!**
+    /// directory!** See [`AudioSource::stream_asset`] for that.
+    ///
+    /// Each instance being played opens the file again, and total number of
+    /// opened streams is limited by [`AudioEngineInitSettings::max_streams`].
+    ///
+    /// Source must be added to [`Assets`] manually. Sources loaded via
+    /// [`AssetServer`] can be streamed too, but their file is kept in memory:
+    /// see [`FmodAudioPlugin::load_mode`], or
+    /// [`AudioAssetSettings::with_load_mode`] with [`AudioLoadMode::Stream`]
+    /// for specific files.
+    pub fn stream_file(filename: String) -> Result<Self, AudioLoadError> {
+        Self::load(AudioSourceData::Stream(filename), false)
+    }
+
+    /// Procedurally-generated sound, samples are requested from the generator
+    /// as the sound is played.
+    ///
+    /// Source is streamed, so only one instance of it can be played at a
+    /// time. It's looped by default, so playback continues until stopped.
+    pub fn from_generator(generator: Box<dyn AudioGenerator>) -> Result<Self, AudioLoadError> {
+        let mut source = Self::load(
+            AudioSourceData::Generator(Arc::new(Mutex::new(generator))),
+            false,
+        )?;
+        source.looped = true;
+        Ok(source)
+    }
+
+    /// Sine wave of full volume, i.e. for testing and calibration. Behaves
+    /// exactly like a source loaded from a file.
+    pub fn sine(frequency: f32, duration: Duration) -> Result<Self, AudioLoadError> {
+        let mut generator = AudioSineGenerator::new(frequency);
+        let mut samples = vec![0.; test_tone_length(duration)];
+        generator.fill(&mut samples);
+        Self::from_memory(&wav_file(&samples, TEST_TONE_SAMPLE_RATE))
+    }
+
+    /// White noise of full volume, i.e. for testing and calibration. Behaves
+    /// exactly like a source loaded from a file.
+    pub fn white_noise(duration: Duration) -> Result<Self, AudioLoadError> {
+        let mut rng = thread_rng();
+        let samples: Vec<f32> = (0..test_tone_length(duration))
+            .map(|_| rng.gen_range(-1. ..=1.))
+            .collect();
+        Self::from_memory(&wav_file(&samples, TEST_TONE_SAMPLE_RATE))
+    }
+
+    /// Same as [`AudioSource::stream_file`], but path is relative to the
+    /// assets directory (as for [`AssetServer::load`]).
+    ///
+    /// Works only if assets are loaded from the filesystem (default on
+    /// desktop platforms), otherwise returns [`AudioLoadError::NotOnFilesystem`].
+    pub fn stream_asset(
+        asset_server: &AssetServer,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, AudioLoadError> {
+        let Some(filename) = asset_file_path(asset_server, path.as_ref()) else {
+            return Err(AudioLoadError::NotOnFilesystem(path.as_ref().to_path_buf()));
+        };
+        Self::stream_file(filename.to_string_lossy().into_owned())
+    }
+
+    /// Length and format of the source.
     ///
-    /// **Only one such source can be played back at once!**
+    /// Returns [`None`] if audio is disabled (see [`AudioState`]) or the
+    /// source isn't ready.
     ///
-    /// Returns [`None`] on error.
-    pub fn stream_file(filename: String) -> Option<Self> {
+    /// _This locks the audio engine, so avoid calling it often (i.e. cache
+    /// the result instead of calling it every frame)._
+    pub fn info(&self) -> Option<AudioSourceInfo> {
         let mut bridge = BRIDGE.lock().unwrap();
-        let bridge = bridge.as_mut().unwrap().pin_mut();
-        let instance = bridge.load_audio_file(bridge::AudioFileParams {
-            filename,
-            ..default()
-        });
-        (instance != -1).then_some(Self::new(instance))
+        let bridge = bridge.as_mut()?;
+        if !self.is_ready(bridge.pin_mut()) {
+            return None;
+        }
+        Some(self.info_with(bridge.pin_mut()))
     }
 
-    fn new(id: EngineId) -> Self {
-        Self {
+    fn info_with(&self, bridge: std::pin::Pin<&mut bridge::Bridge>) -> AudioSourceInfo {
+        let info = bridge.get_sound_info(self.id);
+        AudioSourceInfo {
+            duration: (info.length_ms != u32::MAX)
+                .then(|| Duration::from_millis(info.length_ms.into())),
+            channels: info.channels.max(0) as u32,
+            sample_rate: info.sample_rate.max(0.) as u32,
+            bits: info.bits.max(0) as u32,
+            file_type: match info.file_type {
+                bridge::SoundType::Aiff => AudioFileType::Aiff,
+                bridge::SoundType::Flac => AudioFileType::Flac,
+                bridge::SoundType::Fsb => AudioFileType::Fsb,
+                bridge::SoundType::Mpeg => AudioFileType::Mpeg,
+                bridge::SoundType::OggVorbis => AudioFileType::OggVorbis,
+                bridge::SoundType::Opus => AudioFileType::Opus,
+                bridge::SoundType::Wav => AudioFileType::Wav,
+                bridge::SoundType::Other => AudioFileType::Other,
+                _ => AudioFileType::Unknown,
+            },
+            sample_format: match info.sample_format {
+                bridge::SampleFormat::Pcm8 => AudioSampleFormat::Pcm8,
+                bridge::SampleFormat::Pcm16 => AudioSampleFormat::Pcm16,
+                bridge::SampleFormat::Pcm24 => AudioSampleFormat::Pcm24,
+                bridge::SampleFormat::Pcm32 => AudioSampleFormat::Pcm32,
+                bridge::SampleFormat::PcmFloat => AudioSampleFormat::Float,
+                bridge::SampleFormat::Compressed => AudioSampleFormat::Compressed,
+                _ => AudioSampleFormat::Unknown,
+            },
+            decoded_while_playing: info.decoded_while_playing,
+        }
+    }
+
+    /// Decoded samples in the range, mixed to mono. Range past the end of the
+    /// source is truncated.
+    ///
+    /// Works only for sources loaded into memory as PCM ([`AudioLoadMode::Memory`]);
+    /// returns [`None`] for streamed and compressed sources, or if the source
+    /// isn't ready.
+    ///
+    /// _This locks the audio engine for the duration of the read._
+    pub fn read_pcm(&self, range: Range<Duration>) -> Option<Vec<f32>> {
+        /// Samples copied per bridge call
+        const CHUNK: u32 = 65536;
+
+        let mut bridge = BRIDGE.lock().unwrap();
+        let bridge = bridge.as_mut()?;
+        if !self.is_ready(bridge.pin_mut()) {
+            return None;
+        }
+
+        let info = self.info_with(bridge.pin_mut());
+        let is_pcm = !matches!(
+            info.sample_format,
+            AudioSampleFormat::Compressed | AudioSampleFormat::Unknown
+        );
+        if info.decoded_while_playing || !is_pcm {
+            return None;
+        }
+
+        let rate = info.sample_rate as f64;
+        let start = (range.start.as_secs_f64() * rate) as u64;
+        let end = (range.end.as_secs_f64() * rate) as u64;
+        let (Ok(mut offset), Ok(end)) = (u32::try_from(start), u32::try_from(end)) else {
+            return Some(vec![]);
+        };
+
+        let mut samples = Vec::with_capacity(end.saturating_sub(offset) as usize);
+        while offset < end {
+            let count = (end - offset).min(CHUNK);
+            let chunk = bridge.pin_mut().read_sound_pcm(self.id, offset, count);
+            samples.extend_from_slice(&chunk);
+            if chunk.len() < count as usize {
+                break;
+            }
+            offset += count;
+        }
+        Some(samples)
+    }
+
+    /// Skip silence at the start of the sound, so it's heard immediately
+    /// after being played. Sets [`AudioSource::start_offset`], and
+    /// [`AudioSource::loop_region`] if it's not set yet, so silence at the end
+    /// is skipped too when the sound is looped. One-shot sounds still play
+    /// silence at the end (i.e. they finish later than they are heard).
+    /// Samples with absolute value not greater than threshold are considered
+    /// silent.
+    ///
+    /// Works only for the same sources as [`AudioSource::read_pcm`] (i.e. not
+    /// for streamed ones). Returns false if nothing was trimmed.
+    ///
+    /// _This decodes the whole sound, so it's better to do it once after loading._
+    pub fn trim_silence(&mut self, threshold: f32) -> bool {
+        let Some(info) = self.info() else {
+            return false;
+        };
+        let Some(samples) = info
+            .duration
+            .and_then(|length| self.read_pcm(Duration::ZERO..length))
+        else {
+            return false;
+        };
+
+        let is_loud = |sample: &f32| sample.abs() > threshold;
+        let (Some(first), Some(last)) = (
+            samples.iter().position(is_loud),
+            samples.iter().rposition(is_loud),
+        ) else {
+            return false; // completely silent
+        };
+        if first == 0 && last + 1 == samples.len() {
+            return false;
+        }
+
+        let rate = info.sample_rate.max(1) as f64;
+        let start = Duration::from_secs_f64(first as f64 / rate);
+        let end = Duration::from_secs_f64((last + 1) as f64 / rate);
+
+        self.start_offset = start;
+        self.loop_region.get_or_insert((start, end));
+        true
+    }
+
+    /// Measure integrated loudness of the sound (in LUFS, as defined by
+    /// ITU-R BS.1770) and remember it, see [`AudioSource::loudness`].
+    ///
+    /// Channels are mixed to mono before measuring, so loudness of sounds
+    /// with different content in each channel is slightly underestimated.
+    ///
+    /// Works only for the same sources as [`AudioSource::read_pcm`] (i.e. not
+    /// for streamed ones). Returns [`None`] if sound can't be read or is
+    /// completely silent.
+    ///
+    /// _This decodes the whole sound, so it's better to do it once after loading._
+    pub fn measure_loudness(&mut self) -> Option<f32> {
+        let info = self.info()?;
+        let samples = self.read_pcm(Duration::ZERO..info.duration?)?;
+        self.loudness = integrated_loudness(&samples, info.sample_rate as f64);
+        self.loudness
+    }
+
+    /// Loudness in LUFS, if it was measured by [`AudioSource::measure_loudness`]
+    /// or [`AudioSource::normalize_loudness`]. Doesn't account for
+    /// [`AudioSource::params`].
+    pub fn loudness(&self) -> Option<f32> {
+        self.loudness
+    }
+
+    /// Measure loudness (see [`AudioSource::measure_loudness`]) and multiply
+    /// volume in [`AudioSource::params`] so the sound is played at the target
+    /// loudness (in LUFS; -23 is broadcast standard, games often use -18..-16).
+    ///
+    /// Returns false if loudness can't be measured.
+    pub fn normalize_loudness(&mut self, target_lufs: f32) -> bool {
+        let Some(loudness) = self.measure_loudness() else {
+            return false;
+        };
+        self.params.volume *= 10_f32.powf((target_lufs - loudness) / 20.);
+        true
+    }
+
+    /// Length of the source, [`None`] if it's unknown (may happen for
+    /// streamed sources). See [`AudioSource::info`].
+    pub fn duration(&self) -> Option<Duration> {
+        self.info().and_then(|info| info.duration)
+    }
+
+    /// Whether source can be played. Sources loaded with
+    /// [`AudioSource::from_memory_nonblocking`] may not be ready right away.
+    ///
+    /// _This locks the audio engine._
+    pub fn state(&self) -> AudioSourceState {
+        let mut bridge = BRIDGE.lock().unwrap();
+        match bridge.as_mut() {
+            Some(bridge) => self.state_with(bridge.pin_mut()),
+            None => AudioSourceState::Failed,
+        }
+    }
+
+    fn state_with(&self, bridge: std::pin::Pin<&mut bridge::Bridge>) -> AudioSourceState {
+        if self.id == -1 {
+            return match self.data {
+                // being reloaded after re-initialization, see reinit_engine
+                AudioSourceData::AssetFile(_) => AudioSourceState::Loading,
+                _ => AudioSourceState::Failed,
+            };
+        }
+        if !self.nonblocking {
+            return AudioSourceState::Ready;
+        }
+        match bridge.get_file_open_state(self.id) {
+            bridge::FileOpenState::Loading => AudioSourceState::Loading,
+            bridge::FileOpenState::Ready => AudioSourceState::Ready,
+            _ => AudioSourceState::Failed,
+        }
+    }
+
+    fn is_ready(&self, bridge: std::pin::Pin<&mut bridge::Bridge>) -> bool {
+        self.state_with(bridge) == AudioSourceState::Ready
+    }
+
+    fn load(data: AudioSourceData, nonblocking: bool) -> Result<Self, AudioLoadError> {
+        Self::load_with(data, nonblocking, |bridge, data| {
+            data.load(bridge, nonblocking)
+        })
+    }
+
+    /// `load` returns engine ID or -1 on error
+    fn load_with(
+        data: AudioSourceData,
+        nonblocking: bool,
+        load: impl FnOnce(std::pin::Pin<&mut bridge::Bridge>, &AudioSourceData) -> EngineId,
+    ) -> Result<Self, AudioLoadError> {
+        let mut bridge = BRIDGE.lock().unwrap();
+        let bridge = bridge.as_mut().ok_or(AudioLoadError::Disabled)?;
+        let first_error = bridge.error_count();
+        let id = load(bridge.pin_mut(), &data);
+        if id == -1 {
+            let file = data.describe();
+            return Err(AudioLoadError::engine(file, bridge.pin_mut(), first_error));
+        }
+
+        let streamed = matches!(
+            data,
+            AudioSourceData::Stream(_)
+                | AudioSourceData::Memory(_, AudioLoadMode::Stream)
+                | AudioSourceData::AssetFile(AudioLoadMode::Stream)
+        );
+        let mut source = Self {
             id,
+            data,
+            nonblocking,
             params: default(),
             randomize_params: false,
+            randomize_start_offset: false,
+            start_offset: Duration::ZERO,
+            looped: false,
+            loop_region: None,
+            tags: default(),
+            tags_pending: streamed || nonblocking,
+            loudness: None,
+        };
+        if !source.tags_pending {
+            source.read_tags(bridge.pin_mut());
         }
+        Ok(source)
+    }
+
+    /// Value of `title` tag, see [`AudioSource::tags`]
+    pub fn title(&self) -> Option<&str> {
+        self.tags.get("title").map(|v| v.as_str())
+    }
+
+    /// Value of `artist` tag, see [`AudioSource::tags`]
+    pub fn artist(&self) -> Option<&str> {
+        self.tags.get("artist").map(|v| v.as_str())
+    }
+
+    /// Value of `album` tag, see [`AudioSource::tags`]
+    pub fn album(&self) -> Option<&str> {
+        self.tags.get("album").map(|v| v.as_str())
+    }
+
+    /// Set [`AudioSource::params`]
+    pub fn with_params(mut self, params: AudioParameters) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set [`AudioParameters::volume`] of [`AudioSource::params`]
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.params.volume = volume;
+        self
+    }
+
+    /// Set [`AudioParameters::speed`] of [`AudioSource::params`]
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.params.speed = speed;
+        self
+    }
+
+    /// Set [`AudioParameters::priority`] of [`AudioSource::params`].
+    /// Accepts either [`AudioPriority`] or raw value.
+    pub fn with_priority(mut self, priority: impl Into<u8>) -> Self {
+        self.params.priority = priority.into();
+        self
+    }
+
+    /// Set [`AudioSource::randomize_params`]
+    pub fn randomized(mut self) -> Self {
+        self.randomize_params = true;
+        self
+    }
+
+    /// Set [`AudioSource::randomize_start_offset`]
+    pub fn with_random_start(mut self) -> Self {
+        self.randomize_start_offset = true;
+        self
+    }
+
+    /// Call [`AudioSource::normalize_loudness`]
+    pub fn with_normalized_loudness(mut self, target_lufs: f32) -> Self {
+        self.normalize_loudness(target_lufs);
+        self
+    }
+
+    /// Call [`AudioSource::trim_silence`]
+    pub fn with_trimmed_silence(mut self, threshold: f32) -> Self {
+        self.trim_silence(threshold);
+        self
+    }
+
+    /// Set [`AudioSource::looped`]
+    pub fn with_loop(mut self) -> Self {
+        self.looped = true;
+        self
+    }
+
+    /// Set [`AudioSource::loop_region`]. This doesn't make the source looped.
+    pub fn with_loop_region(mut self, start: Duration, end: Duration) -> Self {
+        self.loop_region = Some((start, end));
+        self
+    }
+
+    fn read_tags(&mut self, bridge: std::pin::Pin<&mut bridge::Bridge>) {
+        self.tags = bridge
+            .get_sound_tags(self.id)
+            .into_iter()
+            .map(|tag| (common_tag_name(&tag.name), tag.value))
+            .collect();
     }
 
     fn params(&self) -> AudioParameters {
@@ -86,851 +567,5848 @@ impl AudioSource {
         }
         params
     }
-
-    // TODO(later): implement custom audio source via trait object
 }
 
 impl Drop for AudioSource {
     fn drop(&mut self) {
-        let mut bridge = BRIDGE.lock().unwrap();
-        let bridge = bridge.as_mut().unwrap().pin_mut();
-        bridge.free_audio_file(self.id);
+        if self.id == -1 {
+            return; // failed to load after re-initialization
+        }
+        if let Some(bridge) = BRIDGE.lock().unwrap().as_mut() {
+            bridge.pin_mut().free_audio_file(self.id);
+        }
     }
 }
 
-/// Add together with [`Handle<AudioSource>`] to play sound on repeat forever.
-///
-/// Otherwise this component is ignored.
-// TODO(later): don't ignore changes.
-#[derive(Component, Clone, Copy, Default)]
-pub struct AudioLoop;
-
-/// Add/change at any time to control playback.
-#[derive(Component, Clone, Copy, Serialize, Deserialize)]
-#[serde(default)]
-pub struct AudioParameters {
-    /// Linear volume multiplier; will be multiplied by group and master
-    /// volumes.
+/// Produces samples of a procedurally-generated sound, see
+/// [`AudioSource::from_generator`].
+pub trait AudioGenerator: Send + 'static {
+    /// Write next samples into the buffer, interleaved if there are multiple
+    /// channels. Returns number of samples written, the rest of the buffer is
+    /// filled with silence.
     ///
-    /// Should be in `[0; 1]` range. Value is not clamped.
-    pub volume: f32,
+    /// _This is called from the audio engine thread, so it should be fast.
+    /// Panics abort the application._
+    fn fill(&mut self, out: &mut [f32]) -> usize;
 
-    /// Playback speed multiplier, also changes pitch. Value is not clamped.
-    pub speed: f32,
+    /// Number of channels, read once when the source is loaded
+    fn channels(&self) -> u32 {
+        1
+    }
 
-    /// If there is not enough free channels, sounds with higher priority will
-    /// be played instead of low priority sounds.
-    ///
-    /// Lower value means higher priority.
-    pub priority: u8,
+    /// Samples per second, read once when the source is loaded
+    fn sample_rate(&self) -> u32 {
+        48000
+    }
+}
 
-    /// For spatial sound only: if distance from listener to sound is less,
-    /// volume is max. Value is not clamped.
-    ///
-    /// **Used only when component is added together with
-    /// [`Handle<AudioSource>`], later changes are ignored!**
-    pub min_distance: f32,
+/// Generates sine wave of constant frequency
+#[derive(Clone, Copy, Debug)]
+pub struct AudioSineGenerator {
+    /// Hz
+    pub frequency: f32,
 
-    /// For spatial sound only: if distance from listener to sound is more,
-    /// volume is zero. Value is not clamped.
-    ///
-    /// **Used only when component is added together with
-    /// [`Handle<AudioSource>`], later changes are ignored!**
-    pub max_distance: f32,
+    /// Amplitude, in `[0; 1]` range
+    pub volume: f32,
+
+    /// Current phase, in `[0; 1)` range
+    pub phase: f32,
 }
 
-impl Default for AudioParameters {
-    fn default() -> Self {
+impl AudioSineGenerator {
+    pub fn new(frequency: f32) -> Self {
         Self {
+            frequency,
             volume: 1.,
-            speed: 1.,
-            priority: 128,
-            min_distance: 0.8,
-            max_distance: 20.,
+            phase: 0.,
         }
     }
 }
 
-impl AudioParameters {
-    /// Randomly change values a bit
-    pub fn randomize(&mut self) {
-        self.volume *= thread_rng().gen_range(0.95..1.05);
-        self.speed *= thread_rng().gen_range(0.95..1.05);
+impl AudioGenerator for AudioSineGenerator {
+    fn fill(&mut self, out: &mut [f32]) -> usize {
+        let step = self.frequency / self.sample_rate() as f32;
+        for sample in out.iter_mut() {
+            *sample = (self.phase * std::f32::consts::TAU).sin() * self.volume;
+            self.phase = (self.phase + step).fract();
+        }
+        out.len()
     }
+}
 
-    /// Randomly change values a bit
-    pub fn get_randomized(mut self) -> Self {
-        self.randomize();
-        self
+/// Integrated loudness in LUFS, as defined by ITU-R BS.1770-4.
+/// Returns [`None`] if sound is silent.
+fn integrated_loudness(samples: &[f32], sample_rate: f64) -> Option<f32> {
+    if samples.is_empty() {
+        return None;
     }
+
+    /// Biquad filter as `(b0, b1, b2, a1, a2)`
+    type Biquad = (f64, f64, f64, f64, f64);
+
+    // K-weighting: high shelf, then high pass
+    let shelf: Biquad = {
+        let (f0, gain, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+        let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+        let vh = 10_f64.powf(gain / 20.);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1. + k / q + k * k;
+        (
+            (vh + vb * k / q + k * k) / a0,
+            2. * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+            2. * (k * k - 1.) / a0,
+            (1. - k / q + k * k) / a0,
+        )
+    };
+    let high_pass: Biquad = {
+        let (f0, q) = (38.13547087602444, 0.5003270373238773);
+        let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+        let a0 = 1. + k / q + k * k;
+        (
+            1.,
+            -2.,
+            1.,
+            2. * (k * k - 1.) / a0,
+            (1. - k / q + k * k) / a0,
+        )
+    };
+
+    // squared K-weighted samples
+    let mut state = [(0., 0., 0., 0.); 2];
+    let power: Vec<f64> = samples
+        .iter()
+        .map(|sample| {
+            let mut x = *sample as f64;
+            for ((b0, b1, b2, a1, a2), (x1, x2, y1, y2)) in
+                [shelf, high_pass].into_iter().zip(state.iter_mut())
+            {
+                let y = b0 * x + b1 * *x1 + b2 * *x2 - a1 * *y1 - a2 * *y2;
+                (*x2, *x1, *y2, *y1) = (*x1, x, *y1, y);
+                x = y;
+            }
+            x * x
+        })
+        .collect();
+
+    // mean power of 400 ms blocks overlapping by 75%
+    let block = ((sample_rate * 0.4) as usize).clamp(1, power.len());
+    let step = (block / 4).max(1);
+    let blocks: Vec<f64> = (0..=power.len().saturating_sub(block))
+        .step_by(step)
+        .map(|start| power[start..start + block].iter().sum::<f64>() / block as f64)
+        .filter(|z| z.is_finite())
+        .collect();
+
+    let loudness = |z: f64| -0.691 + 10. * z.log10();
+    let gated_mean = |threshold: f64| {
+        let gated: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|z| loudness(*z) > threshold)
+            .collect();
+        (!gated.is_empty()).then(|| gated.iter().sum::<f64>() / gated.len() as f64)
+    };
+
+    let absolute = gated_mean(-70.)?;
+    let relative = gated_mean(loudness(absolute) - 10.)?;
+    Some(loudness(relative) as f32)
 }
 
-/// Add together with [`Handle<AudioSource>`] to start playback after specified
-/// delay.
-#[derive(Component, Clone, Default)]
-pub struct AudioStartupDelay(pub Duration);
+/// See [`AudioSource::sine`]
+const TEST_TONE_SAMPLE_RATE: u32 = 48000;
 
-impl AudioStartupDelay {
-    /// Set to small randomized delay (<= 10 ms)
-    pub fn random() -> Self {
-        let max = 0.010; // 10 ms
-        Self(Duration::from_secs_f32(thread_rng().gen_range(0. ..max)))
+/// Number of samples
+fn test_tone_length(duration: Duration) -> usize {
+    (duration.as_secs_f64() * TEST_TONE_SAMPLE_RATE as f64) as usize
+}
+
+/// Mono 16-bit WAV file
+fn wav_file(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let data_size = samples.len() as u32 * 2;
+
+    let mut file = Vec::with_capacity(44 + data_size as usize);
+    file.extend_from_slice(b"RIFF");
+    file.extend_from_slice(&(36 + data_size).to_le_bytes());
+    file.extend_from_slice(b"WAVEfmt ");
+    file.extend_from_slice(&16u32.to_le_bytes()); // chunk size
+    file.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    file.extend_from_slice(&1u16.to_le_bytes()); // channels
+    file.extend_from_slice(&sample_rate.to_le_bytes());
+    file.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // bytes per second
+    file.extend_from_slice(&2u16.to_le_bytes()); // bytes per sample
+    file.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    file.extend_from_slice(b"data");
+    file.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1., 1.) * i16::MAX as f32) as i16;
+        file.extend_from_slice(&sample.to_le_bytes());
     }
+    file
+}
 
-    /// Randomly change value a bit
-    pub fn randomize(mut self) -> Self {
-        let k = thread_rng().gen_range(0.95..1.05);
-        self.0 = Duration::from_secs_f32(self.0.as_secs_f32() * k);
-        self
+/// Same name for common tags in different formats
+fn common_tag_name(name: &str) -> String {
+    match name.to_ascii_uppercase().as_str() {
+        "TITLE" | "TIT2" | "TT2" => "title",
+        "ARTIST" | "TPE1" | "TP1" => "artist",
+        "ALBUM" | "TALB" | "TAL" => "album",
+        "COMMENT" | "COMM" | "COM" => "comment",
+        _ => name,
     }
+    .to_string()
 }
 
-/// Add together with [`Handle<AudioSource>`] to assign sound to a non-default
-/// group.
-///
-/// Otherwise this component is ignored.
-///
-/// Each sound is assigned to a group, for easier manipulation.
-/// Groups are defined by user (except for default group `AudioGroup(0)`)
-///
-/// Groups are not required to be registered in any way.
-/// ATM they are used only for per-group settings, but there are plans for
-/// per-group effect plugins and combining several groups.
-// TODO(later): dont' ignore changes
-#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
-pub struct AudioGroup(pub i32);
+/// See [`AudioSource::state`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioSourceState {
+    /// Still being decoded in background
+    Loading,
 
-/// Add audio geometry to the engine to occlude spatial sounds.
-/// Removal of this component removes geometry from the engine.
-///
-/// Otherwise this component is ignored.
-///
-/// Requires [`GlobalTransform`]. Changes to it will be ignored.
-// TODO(later): dont' ignore changes
-#[derive(Component, Clone, Default, Serialize, Deserialize)]
-#[serde(default)]
-pub struct AudioGeometry {
-    pub polygon_vertices: AudioGeometryData,
-    pub params: AudioGeometryParams,
+    /// Can be played
+    Ready,
+
+    /// Failed to load, or audio engine was re-initialized and source failed
+    /// to load again. Sounds using it won't be played.
+    Failed,
 }
 
-/// Vec of planar polygons - each polygon can have any number of points,
-/// but they must lie on the same plane.
-///
-/// Polygon must be convex.
-pub type AudioGeometryData = Vec<Vec<Vec3>>;
+/// See [`AudioSource::info`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AudioSourceInfo {
+    /// Length of the source, [`None`] if it's unknown
+    pub duration: Option<Duration>,
 
-/// Parameters for audio geometry
-#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
-#[serde(default)]
-pub struct AudioGeometryParams {
-    /// Volume of non-reverberated part of sound behind the geometry, in `[0;
-    /// 1]` range.
-    pub direct_occlusion: f32,
+    pub channels: u32,
 
-    /// Volume of reverberated part of sound (when geometry is between the sound
-    /// and the center of the reverb sphere), in `[0; 1]` range.
-    pub reverb_occlusion: f32,
-}
+    /// Hz
+    pub sample_rate: u32,
 
-impl Default for AudioGeometryParams {
-    fn default() -> Self {
-        Self {
-            direct_occlusion: 0.3,
-            reverb_occlusion: 0.3,
-        }
-    }
-}
+    /// Bits per sample
+    pub bits: u32,
 
-/// Add reverb sphere to the engine to affect spatial sounds.
-/// Removal of this component removes reverb from the engine.
-///
-/// Otherwise this component is ignored.
-///
-/// Requires [`GlobalTransform`]. Changes to it will be ignored.
-// TODO(later): dont' ignore changes
-#[derive(Component, Serialize, Deserialize, Debug)]
-#[serde(default)]
-pub struct AudioReverbSphere {
-    /// Effect is applied in full to sounds closer than that
-    pub min_distance: f32,
+    pub file_type: AudioFileType,
 
-    /// Effect is not applied to sounds farther than that
-    pub max_distance: f32,
+    /// Format in which samples are stored in memory
+    pub sample_format: AudioSampleFormat,
 
-    pub props: AudioReverbProps,
+    /// Source is streamed or kept compressed in memory (see
+    /// [`AudioLoadMode`]), so it's decoded while being played. This costs
+    /// CPU time for each playing instance.
+    pub decoded_while_playing: bool,
 }
 
-impl Default for AudioReverbSphere {
-    fn default() -> Self {
-        Self {
-            min_distance: 5.,
-            max_distance: 20.,
-            ..default()
+impl fmt::Display for AudioSourceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}, {:?}, {} channels, {} Hz, {} bits",
+            self.file_type, self.sample_format, self.channels, self.sample_rate, self.bits
+        )?;
+        if let Some(duration) = self.duration {
+            write!(f, ", {:.3} s", duration.as_secs_f32())?;
+        }
+        if self.decoded_while_playing {
+            write!(f, ", decoded while playing")?;
         }
+        Ok(())
     }
 }
 
-/// Reverb properties
-#[derive(Clone, Serialize, Deserialize, Debug)]
-#[serde(default)]
-pub struct AudioReverbProps {
-    /// Reverberation decay time.
-    ///
-    /// Milliseconds, range `[0; 20_000]`.
-    pub decay_time: f32,
-
-    /// Initial reflection delay time.
-    ///
-    /// Milliseconds, range `[0; 300]`.
-    pub early_delay: f32,
-
-    /// Late reverberation delay time relative to initial reflection.
-    ///
-    /// Milliseconds, range `[0; 100]`.
-    pub late_delay: f32,
-
-    /// Reference high frequency.
-    ///
-    /// Hertz, range `[20; 20_000]`.
-    pub hf_reference: f32,
-
-    /// High-frequency to mid-frequency decay time ratio.
-    ///
-    /// Percent, range `[10; 100]`.
-    pub hf_decay_ratio: f32,
+/// See [`AudioSourceInfo::file_type`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioFileType {
+    Unknown,
+    Aiff,
+    Flac,
+    /// FMOD sound bank, see [`AudioBank`]
+    Fsb,
+    /// MP2 or MP3
+    Mpeg,
+    OggVorbis,
+    Opus,
+    Wav,
+    /// Supported by FMOD, but not listed here
+    Other,
+}
 
-    /// Value that controls the echo density in the late reverberation decay.
-    ///
-    /// Percent, range `[10; 100]`.
-    pub diffusion: f32,
+/// See [`AudioSourceInfo::sample_format`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioSampleFormat {
+    Unknown,
+    Pcm8,
+    Pcm16,
+    Pcm24,
+    Pcm32,
+    Float,
+    /// Kept compressed (streamed or [`AudioLoadMode::CompressedInMemory`])
+    Compressed,
+}
 
-    /// Value that controls the modal density in the late reverberation decay.
-    ///
-    /// Percent, range `[10; 100]`.
-    pub density: f32,
+/// See [`AudioSettings::emitter_interpolation`]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum AudioInterpolation {
+    /// Transform of the current frame is used
+    #[default]
+    None,
 
-    /// Reference low frequency.
+    /// Position at the time when the engine update was due, interpolated
+    /// between transforms of the previous and the current frame. Updates are
+    /// evenly spaced in time even if frames aren't, at the cost of up to one
+    /// frame of latency.
     ///
-    /// Hertz, range `[20; 1000]`.
-    pub low_shelf_frequency: f32,
+    /// Same as `None` if [`FmodAudioPlugin::update_hz`] isn't set.
+    Interpolate,
 
-    /// Relative room effect level at low frequencies.
-    ///
-    /// Decibels, range `[-36, 12]`.
-    pub low_shelf_gain: f32,
+    /// Position at the time of the next engine update, extrapolated from
+    /// transforms of the previous and the current frame. Doesn't add latency,
+    /// but overshoots when sounds suddenly stop or turn.
+    Extrapolate,
+}
 
-    /// Relative room effect level at high frequencies.
-    ///
-    /// Hertz, range `[0; 200_000]`.
-    pub high_cut: f32,
+/// How [`AudioSource`] loaded from memory is stored, see
+/// [`AudioSource::from_memory_with_mode`]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum AudioLoadMode {
+    /// Decoded into memory once. Uses the most memory, but is the cheapest
+    /// to play.
+    #[default]
+    Memory,
 
-    /// Early reflections level relative to room effect.
+    /// Decoded while being played, like [`AudioSource::stream_file`] but
+    /// file is kept in memory instead of being read from disk.
     ///
-    /// Percent, range `[0; 100]`.
-    pub early_late_mix: f32,
+    /// Each instance played at the same time counts as a separate stream
+    /// (see [`AudioEngineInitSettings::max_streams`]).
+    Stream,
 
-    /// Room effect level at mid frequencies.
+    /// Kept compressed in memory and decoded while being played. Works for
+    /// compressed formats (i.e. MP3, Vorbis, FSB and IMA ADPCM WAV), others
+    /// (i.e. PCM WAV) are loaded as with [`AudioLoadMode::Memory`].
     ///
-    /// Decibels, range `[-80; 20]`.
-    pub wet_level: f32,
+    /// Uses several times less memory than [`AudioLoadMode::Memory`] at the
+    /// cost of decoding on each playback, which takes more CPU time per
+    /// playing instance. Unlike streams, any number of instances can be
+    /// played at once, and looping, seeking and speed changes work as usual.
+    /// Good choice for large libraries of sounds which aren't played often.
+    CompressedInMemory,
 }
 
-impl Default for AudioReverbProps {
-    // `FMOD_PRESET_GENERIC`
-    fn default() -> Self {
-        Self {
-            decay_time: 1500.,
-            early_delay: 7.,
-            late_delay: 11.,
-            hf_reference: 5000.,
-            hf_decay_ratio: 50.,
-            diffusion: 50.,
-            density: 100.,
-            low_shelf_frequency: 250.,
-            low_shelf_gain: 0.,
-            high_cut: 200_000.,
-            early_late_mix: 50.,
-            wet_level: -6.,
-        }
+/// Absolute path to the asset, if it's loaded from the filesystem
+fn asset_file_path(asset_server: &AssetServer, path: &Path) -> Option<PathBuf> {
+    #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+    {
+        let io = asset_server
+            .asset_io()
+            .downcast_ref::<bevy::asset::FileAssetIo>()?;
+        Some(io.root_path().join(path))
+    }
+    #[cfg(any(target_arch = "wasm32", target_os = "android"))]
+    {
+        let _ = (asset_server, path);
+        None
     }
 }
 
-impl AudioReverbProps {
-    /// `FMOD_PRESET_HALLWAY`, sounds like somewhat wide corridor
-    pub fn hallway() -> Self {
-        Self {
-            decay_time: 1500.,
-            early_delay: 7.,
-            late_delay: 11.,
-            hf_reference: 5000.,
-            hf_decay_ratio: 59.,
-            diffusion: 100.,
-            density: 100.,
-            low_shelf_frequency: 250.,
-            low_shelf_gain: 0.,
-            high_cut: 7800.,
-            early_late_mix: 87.,
-            wet_level: -5.5,
-        }
-    }
+enum AudioSourceData {
+    Memory(Vec<u8>, AudioLoadMode),
+    /// Loaded by [`AudioFileLoader`]. File isn't kept, asset is reloaded by
+    /// [`AssetServer`] on [`ReinitAudioEngine`] instead.
+    AssetFile(AudioLoadMode),
+    Stream(String),
+    /// Bank and index of the entry
+    BankEntry(Handle<AudioBank>, usize),
+    /// Shared with the engine, which locks it while reading samples
+    Generator(Arc<Mutex<Box<dyn AudioGenerator>>>),
+}
 
-    /// `FMOD_PRESET_HANGAR`, sounds like giant empty room
-    pub fn hangar() -> Self {
-        Self {
-            decay_time: 10000.,
-            early_delay: 20.,
-            late_delay: 30.,
-            hf_reference: 5000.,
-            hf_decay_ratio: 23.,
-            diffusion: 100.,
-            density: 100.,
-            low_shelf_frequency: 250.,
-            low_shelf_gain: 0.,
-            high_cut: 3400.,
-            early_late_mix: 72.,
-            wet_level: -7.4,
+impl AudioSourceData {
+    /// For [`AudioLoadError`]
+    fn describe(&self) -> String {
+        match self {
+            Self::Memory(file_contents, _) => format!("{} bytes in memory", file_contents.len()),
+            Self::AssetFile(_) => "file".to_string(),
+            Self::Stream(filename) => format!("\"{filename}\""),
+            Self::BankEntry(bank, index) => format!("entry {index} of {bank:?}"),
+            Self::Generator(_) => "generator".to_string(),
         }
     }
 
-    /// Exaggerated reverb for giant empty room
-    pub fn huge_room() -> Self {
-        Self {
-            decay_time: 6000.,
-            wet_level: 3.,
-            ..Self::hangar()
+    /// Returns -1 on error
+    fn load(&self, bridge: std::pin::Pin<&mut bridge::Bridge>, nonblocking: bool) -> EngineId {
+        match self {
+            Self::Memory(file_contents, mode) => {
+                load_memory(bridge, file_contents, *mode, nonblocking)
+            }
+            Self::AssetFile(_) => -1, // requires the file, see reinit_engine
+            Self::Stream(filename) => bridge.load_audio_file(bridge::AudioFileParams {
+                filename: filename.clone(),
+                ..default()
+            }),
+            Self::BankEntry(..) => -1, // requires the bank, see AudioBank::load_entry
+            Self::Generator(generator) => {
+                let params = {
+                    let generator = generator.lock().unwrap();
+                    bridge::GeneratorParams {
+                        channels: generator.channels().clamp(1, 32) as i32,
+                        sample_rate: generator.sample_rate().clamp(1000, 192000) as i32,
+                    }
+                };
+                let generator = generator.clone();
+                bridge.load_generator(
+                    params,
+                    Box::new(super::bridge::AudioGeneratorBox(Box::new(move |out| {
+                        // if generator panicked earlier, output silence
+                        generator
+                            .lock()
+                            .map_or(0, |mut generator| generator.fill(out))
+                    }))),
+                )
+            }
         }
     }
 }
 
-/// Marker for entity whose position is used for spatial
-/// audio.
-///
-/// Requires [`GlobalTransform`].
+/// Returns -1 on error
+fn load_memory(
+    bridge: std::pin::Pin<&mut bridge::Bridge>,
+    file_contents: &[u8],
+    mode: AudioLoadMode,
+    nonblocking: bool,
+) -> EngineId {
+    bridge.load_audio_file(bridge::AudioFileParams {
+        file_contents,
+        stream_contents: mode == AudioLoadMode::Stream,
+        compressed: mode == AudioLoadMode::CompressedInMemory,
+        nonblocking,
+        ..default()
+    })
+}
+
+/// Sound bank (`.fsb` file) with many sounds, which are kept compressed in
+/// memory. Use [`AudioSource::from_bank`] to play them.
 ///
-/// There can't be multiple listeners.
+/// Banks are loaded via [`AssetServer`] from files with `.fsb` extension.
 ///
-/// If listener doesn't exist, spatial sounds will play at the last remembered
-/// position (which is `Vec3::ZERO` on startup).
-#[derive(Component, Clone, Default)]
-pub struct AudioListener;
+/// Bank stays in memory while any source created from it exists, even if
+/// the asset itself is removed.
+#[derive(TypeUuid, TypePath)]
+#[uuid = "3f7ade4f-f23e-420d-9587-86cd85ba310d"]
+pub struct AudioBank {
+    id: EngineId,
 
-/// Global engine settings
-#[derive(Resource, Clone, Serialize, Deserialize, Debug)]
-#[serde(default)]
-pub struct AudioSettings {
-    /// Per-group settings.
-    ///
-    /// If group isn't present here, defaults will be used for sounds belonging
-    /// to that group.
-    pub groups: HashMap<AudioGroup, AudioGroupParameters>,
+    /// Kept to load the bank again on [`ReinitAudioEngine`]
+    file_contents: Vec<u8>,
 
-    /// Linear volume multiplier applied to all sounds.
-    ///
-    /// Should be in `[0; 1]` range.
-    pub master_volume: f32,
+    names: Vec<String>,
+}
 
-    /// If false, consider master volume to be zero.
-    ///
-    /// _Hearing same sounds and music over-and-over-and-over-again in long
-    /// debugging sessions gets really, really annoying, doesn't it?_
-    pub enabled: bool,
+impl AudioBank {
+    /// Load bank from file loaded into memory
+    pub fn from_memory(file_contents: &[u8]) -> Result<Self, AudioLoadError> {
+        let mut bridge = BRIDGE.lock().unwrap();
+        let bridge = bridge.as_mut().ok_or(AudioLoadError::Disabled)?;
+        let mut bank = Self {
+            id: -1,
+            file_contents: file_contents.to_vec(),
+            names: vec![],
+        };
+        let first_error = bridge.error_count();
+        bank.load(bridge.pin_mut());
+        match bank.id {
+            -1 => Err(AudioLoadError::engine(
+                format!("bank of {} bytes in memory", file_contents.len()),
+                bridge.pin_mut(),
+                first_error,
+            )),
+            _ => Ok(bank),
+        }
+    }
 
-    pub engine: AudioEngineSettings,
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Names of entries, index in the slice is entry index
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Index of the first entry with specified name
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|entry| entry == name)
+    }
+
+    fn load(&mut self, mut bridge: std::pin::Pin<&mut bridge::Bridge>) {
+        self.id = bridge.as_mut().load_bank(&self.file_contents);
+        if self.id != -1 {
+            self.names = bridge.get_bank_entry_names(self.id);
+        }
+    }
+
+    /// Returns -1 on error
+    fn load_entry(&self, bridge: std::pin::Pin<&mut bridge::Bridge>, index: usize) -> EngineId {
+        if self.id == -1 {
+            return -1; // failed to load after re-initialization
+        }
+        bridge.load_bank_entry(self.id, index as i32)
+    }
 }
 
-impl Default for AudioSettings {
-    fn default() -> Self {
-        Self {
-            groups: default(),
-            master_volume: 0.5,
-            enabled: true,
-            engine: default(),
+impl Drop for AudioBank {
+    fn drop(&mut self) {
+        if self.id == -1 {
+            return; // failed to load after re-initialization
+        }
+        if let Some(bridge) = BRIDGE.lock().unwrap().as_mut() {
+            bridge.pin_mut().free_bank(self.id);
         }
     }
 }
 
-/// Per-group engine settings
-#[derive(Clone, Serialize, Deserialize, Debug)]
-#[serde(default)]
-pub struct AudioGroupParameters {
-    /// Linear volume multiplier for all sounds in the group.
-    ///
-    /// Should be in `[0; 1]` range.
-    pub volume: f32,
+/// Entry of [`AudioBank`], see [`AudioSource::from_bank`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AudioBankEntry {
+    Index(usize),
+    Name(String),
 }
 
-impl Default for AudioGroupParameters {
-    fn default() -> Self {
-        Self { volume: 1. }
+impl From<usize> for AudioBankEntry {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
     }
 }
 
-/// Global engine configuration
-#[derive(Resource, Clone, Serialize, Deserialize, Debug)]
-#[serde(default)]
-pub struct AudioEngineSettings {
-    /// How much pitch varies with relative speed (Doppler effect).
-    ///
-    /// With this at 1 effective sound speed is 340 m/s.
-    pub doppler_scale: f32,
+impl From<&str> for AudioBankEntry {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
 
-    /// Used only for doppler. Set to 1 if you use meters, set to 3.28 if you
-    /// use feet.
-    pub distance_scale: f32,
+impl From<String> for AudioBankEntry {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
 
-    /// Global factor applied to all distance calculations:
+impl AudioSource {
+    /// Source playing an entry of the bank. It shares memory with the bank
+    /// instead of copying it.
     ///
-    /// `distance = (distance - minDistance) * rolloffscale + minDistance`
-    pub rolloff_scale: f32,
+    /// Fails if bank isn't loaded yet or doesn't have such entry.
+    pub fn from_bank(
+        banks: &Assets<AudioBank>,
+        bank: &Handle<AudioBank>,
+        entry: impl Into<AudioBankEntry>,
+    ) -> Result<Self, AudioLoadError> {
+        let handle = bank;
+        let bank = banks.get(handle).ok_or(AudioLoadError::BankNotLoaded)?;
+        let entry = entry.into();
+        let index = match &entry {
+            AudioBankEntry::Index(index) => Some(*index).filter(|index| *index < bank.len()),
+            AudioBankEntry::Name(name) => bank.find(name),
+        };
+        let Some(index) = index else {
+            return Err(AudioLoadError::NoBankEntry(entry));
+        };
+
+        let mut bridge = BRIDGE.lock().unwrap();
+        let bridge = bridge.as_mut().ok_or(AudioLoadError::Disabled)?;
+        let first_error = bridge.error_count();
+        let id = bank.load_entry(bridge.pin_mut(), index);
+        if id == -1 {
+            let file = AudioSourceData::BankEntry(handle.clone(), index).describe();
+            return Err(AudioLoadError::engine(file, bridge.pin_mut(), first_error));
+        }
+        Ok(Self {
+            id,
+            data: AudioSourceData::BankEntry(handle.clone(), index),
+            nonblocking: false,
+            params: default(),
+            randomize_params: false,
+            randomize_start_offset: false,
+            start_offset: Duration::ZERO,
+            looped: false,
+            loop_region: None,
+            tags: default(),
+            tags_pending: true,
+            loudness: None,
+        })
+    }
+}
+
+/// Why [`AudioSource`] or [`AudioBank`] failed to load
+#[derive(Clone, PartialEq, Debug)]
+pub enum AudioLoadError {
+    /// Audio engine isn't initialized, see [`AudioState`]
+    Disabled,
+
+    /// Engine failed to open the file (i.e. it's corrupted or has unsupported
+    /// format). `file` is either a filename or size of the data in memory.
+    Fmod {
+        file: String,
+        /// `FMOD_RESULT` code, 0 if it's not an FMOD error
+        code: i32,
+        message: String,
+    },
+
+    /// See [`AudioSource::stream_asset`]
+    NotOnFilesystem(PathBuf),
+
+    /// See [`AudioSource::from_bank`]
+    BankNotLoaded,
+
+    /// See [`AudioSource::from_bank`]
+    NoBankEntry(AudioBankEntry),
+}
+
+impl AudioLoadError {
+    /// From the last error reported by the engine starting from `first_error`
+    /// (see `Bridge::error_count`). These errors are taken, so they aren't
+    /// sent as [`AudioError`].
+    fn engine(
+        file: String,
+        bridge: std::pin::Pin<&mut bridge::Bridge>,
+        first_error: usize,
+    ) -> Self {
+        let error = bridge.take_errors(first_error).pop();
+        Self::Fmod {
+            file,
+            code: error.as_ref().map_or(0, |error| error.code),
+            message: error.map_or_else(|| "unknown error".to_string(), |error| error.message),
+        }
+    }
+
+    /// Add asset path to the description of the file
+    fn with_path(self, path: &Path) -> Self {
+        match self {
+            Self::Fmod {
+                file,
+                code,
+                message,
+            } => Self::Fmod {
+                file: format!("\"{}\" ({file})", path.display()),
+                code,
+                message,
+            },
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for AudioLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "audio is disabled"),
+            Self::Fmod {
+                file,
+                code,
+                message,
+            } => write!(f, "failed to load {file} (FMOD error {code}): {message}"),
+            Self::NotOnFilesystem(path) => write!(
+                f,
+                "can't stream {path:?}: assets aren't loaded from the filesystem"
+            ),
+            Self::BankNotLoaded => write!(f, "bank isn't loaded yet"),
+            Self::NoBankEntry(entry) => write!(f, "bank has no entry {entry:?}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioLoadError {}
+
+/// Add together with [`Handle<AudioSource>`] to play sound on repeat forever.
+/// Overridden by [`AudioForceOneShot`].
+///
+/// Otherwise this component is ignored.
+// TODO(later): don't ignore changes.
+#[derive(Component, Clone, Copy, Default)]
+pub struct AudioLoop;
+
+/// Add together with [`Handle<AudioSource>`] to play sound only once, even if
+/// [`AudioSource::looped`] is set or [`AudioLoop`] is present (this takes
+/// precedence over both).
+///
+/// Looping isn't part of [`AudioParameters`], so
+/// [`AudioSource::randomize_params`] never affects it.
+///
+/// Otherwise this component is ignored.
+#[derive(Component, Clone, Copy, Default)]
+pub struct AudioForceOneShot;
+
+/// Insert on an entity with playing sound to restart it from the beginning
+/// (i.e. to re-trigger a looped alarm instead of playing another instance).
+/// Component is removed once the sound is restarted.
+///
+/// Sound is started again the same way it was started initially, so
+/// [`AudioStartOffset`], [`AudioParameters::reverse`] and other components
+/// are respected. Entity and its components are kept.
+#[derive(Component, Clone, Copy, Default)]
+pub struct AudioRestart;
+
+/// Add together with [`Handle<AudioSource>`] to play several sounds
+/// back-to-back on the same entity.
+///
+/// When current sound stops, next one is removed from the queue and played
+/// with the same components, replacing [`Handle<AudioSource>`]. Entity is
+/// despawned only when the queue is empty. Sounds which aren't loaded at
+/// that moment are skipped.
+///
+/// Since looped sounds never stop, queue is ignored for them.
+///
+/// _Next sound starts when the previous one is detected as stopped, so there
+/// can be a gap of up to one frame._
+#[derive(Component, Clone, Default)]
+pub struct AudioQueue {
+    pub next: VecDeque<Handle<AudioSource>>,
+}
+
+/// List of tracks, see [`AudioPlaylistPlayer`]
+#[derive(TypeUuid, TypePath, Clone, Default)]
+#[uuid = "8ee481cb-98b5-4664-a70f-6eadebc9ed13"]
+pub struct AudioPlaylist {
+    pub tracks: Vec<Handle<AudioSource>>,
+}
+
+/// Add to an entity to play [`AudioPlaylist`] (i.e. background music).
+///
+/// Unlike [`AudioQueue`], next track is scheduled to start exactly when the
+/// previous one ends, so there is no gap between them. For that tracks
+/// must be already loaded.
+///
+/// Each track is played by a child entity, which gets [`AudioGroup`] and
+/// [`AudioParameters`] of this entity. Tracks are never spatial, and ones
+/// with [`AudioSource::looped`] never end.
+///
+/// Entity is despawned after the last track ends, unless `repeat` is set
+/// (see [`AudioSettings::auto_despawn`]).
+/// Despawning it stops playback.
+///
+/// Changes after playback has started are ignored.
+#[derive(Component, Clone, Default)]
+pub struct AudioPlaylistPlayer {
+    pub playlist: Handle<AudioPlaylist>,
+
+    /// Play tracks in random order
+    pub shuffle: bool,
+
+    /// Start again after the last track (in new order if shuffled)
+    pub repeat: bool,
+}
+
+/// Sent when [`AudioPlaylistPlayer`] starts playing a track
+#[derive(Event, Clone, Debug)]
+pub struct AudioPlaylistTrack {
+    /// Entity with [`AudioPlaylistPlayer`]
+    pub entity: Entity,
+
+    /// Index of the track in [`AudioPlaylist::tracks`]
+    pub index: usize,
+
+    pub track: Handle<AudioSource>,
+}
+
+/// Add instead of [`Handle<AudioSource>`] to play sound from a file loaded
+/// into memory (i.e. downloaded or generated at runtime).
+///
+/// File is loaded with [`AudioSource::from_memory`], added to [`Assets`] and
+/// its handle is added to the entity before playback starts, so it's played
+/// as usual. Sources are reused while they exist, so playing the same bytes
+/// again doesn't load them again. Later changes to this component are ignored.
+///
+/// If file fails to load, entity is despawned (see
+/// [`AudioSettings::auto_despawn`]) and [`AudioError`] is sent.
+#[derive(Component, Clone)]
+pub struct AudioBytes(pub Arc<Vec<u8>>);
+
+/// Add instead of [`Handle<AudioSource>`] to play one of several sounds,
+/// picked randomly according to their weights.
+///
+/// Chosen sound is added to the entity as [`Handle<AudioSource>`] before
+/// playback starts, so it's played as usual. Later changes to this component
+/// are ignored.
+#[derive(Component, Clone, Default)]
+pub struct AudioRandomPool {
+    /// Sounds and their weights. Weights must be non-negative.
+    pub variants: Vec<(Handle<AudioSource>, f32)>,
+
+    /// Don't pick the same sound which was picked last time from a pool with
+    /// the same variants (unless it's the only one with non-zero weight).
+    pub no_immediate_repeat: bool,
+}
+
+impl AudioRandomPool {
+    /// Randomly choose one of the sounds.
+    ///
+    /// Returns [`None`] if there are no sounds or all weights are zero.
+    pub fn pick(&self) -> Option<Handle<AudioSource>> {
+        self.pick_index(None)
+            .map(|index| self.variants[index].0.clone())
+    }
+
+    /// Randomly choose index of one of the sounds, avoiding `exclude` if
+    /// possible.
+    ///
+    /// Returns [`None`] if there are no sounds or all weights are zero.
+    pub fn pick_index(&self, exclude: Option<usize>) -> Option<usize> {
+        let weights = |exclude: Option<usize>| {
+            self.variants
+                .iter()
+                .enumerate()
+                .map(move |(index, v)| if Some(index) == exclude { 0. } else { v.1 })
+        };
+        let distribution = rand::distributions::WeightedIndex::new(weights(exclude))
+            .or_else(|_| rand::distributions::WeightedIndex::new(weights(None)))
+            .ok()?;
+        Some(distribution.sample(&mut thread_rng()))
+    }
+}
+
+/// Add/change at any time to control playback.
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioParameters {
+    /// Linear volume multiplier; will be multiplied by group and master
+    /// volumes.
+    ///
+    /// Should be in `[0; 1]` range. Value is not clamped.
+    pub volume: f32,
+
+    /// Playback speed multiplier, also changes pitch. Value is not clamped.
+    pub speed: f32,
+
+    /// If there is not enough free channels, sounds with higher priority will
+    /// be played instead of low priority sounds.
+    ///
+    /// Lower value means higher priority. See [`AudioPriority`] for named values.
+    pub priority: u8,
+
+    /// For spatial sound only: recompute priority every frame from the
+    /// distance to the nearest [`AudioListener`], so distant sounds are
+    /// stolen first. Priority goes from [`AudioParameters::priority`] at
+    /// [`AudioParameters::min_distance`] to [`AudioPriority::Ambient`] at
+    /// [`AudioParameters::max_distance`].
+    pub auto_priority: bool,
+
+    /// What to do when the sound is started, but all active channels are
+    /// already in use (see [`AudioEngineInitSettings::max_active_channels`]).
+    ///
+    /// **Used only when component is added together with
+    /// [`Handle<AudioSource>`], later changes are ignored!**
+    pub steal_behavior: AudioSteal,
+
+    /// For spatial sound only: if distance from listener to sound is less,
+    /// volume is max. Value is not clamped.
+    ///
+    /// **Used only when component is added together with
+    /// [`Handle<AudioSource>`], later changes are ignored!**
+    pub min_distance: f32,
+
+    /// For spatial sound only: if distance from listener to sound is more,
+    /// volume is zero. Value is not clamped.
+    ///
+    /// **Used only when component is added together with
+    /// [`Handle<AudioSource>`], later changes are ignored!**
+    pub max_distance: f32,
+
+    /// For spatial sound only: angle (in degrees, `[0; 360]`) over which
+    /// channels of stereo or multichannel sound are spread. At 0 sound is
+    /// a point source, at 360 it surrounds the listener (i.e. for nearby
+    /// ambience like rivers and fires).
+    pub spread: f32,
+
+    /// Play sound backwards, starting from the end. Looped sound repeats in
+    /// reverse too.
+    ///
+    /// Streamed sources (see [`AudioSource::stream_file`]) can't be played
+    /// in reverse, they fail to start.
+    ///
+    /// **Used only when component is added together with
+    /// [`Handle<AudioSource>`], later changes are ignored!**
+    pub reverse: bool,
+}
+
+impl Default for AudioParameters {
+    fn default() -> Self {
+        Self {
+            volume: 1.,
+            speed: 1.,
+            priority: AudioPriority::Normal.into(),
+            auto_priority: false,
+            steal_behavior: default(),
+            min_distance: 0.8,
+            max_distance: 20.,
+            spread: 0.,
+            reverse: false,
+        }
+    }
+}
+
+impl AudioParameters {
+    /// Randomly change values a bit
+    pub fn randomize(&mut self) {
+        self.volume *= thread_rng().gen_range(0.95..1.05);
+        self.speed *= thread_rng().gen_range(0.95..1.05);
+    }
+
+    /// Randomly change values a bit
+    pub fn get_randomized(mut self) -> Self {
+        self.randomize();
+        self
+    }
+
+    /// Set [`AudioParameters::priority`]. Accepts either [`AudioPriority`] or raw value.
+    pub fn with_priority(mut self, priority: impl Into<u8>) -> Self {
+        self.priority = priority.into();
+        self
+    }
+}
+
+/// Named values for [`AudioParameters::priority`], from highest to lowest
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum AudioPriority {
+    /// Never stolen by other sounds (unless they are critical too)
+    Critical,
+
+    High,
+
+    /// Default priority
+    #[default]
+    Normal,
+
+    Low,
+
+    /// Background sounds which can be dropped first
+    Ambient,
+
+    /// Raw value, lower means higher priority
+    Custom(u8),
+}
+
+impl From<AudioPriority> for u8 {
+    fn from(priority: AudioPriority) -> Self {
+        match priority {
+            AudioPriority::Critical => 0,
+            AudioPriority::High => 64,
+            AudioPriority::Normal => 128,
+            AudioPriority::Low => 192,
+            AudioPriority::Ambient => 255,
+            AudioPriority::Custom(value) => value,
+        }
+    }
+}
+
+/// See [`AudioParameters::steal_behavior`]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum AudioSteal {
+    /// Sounds with lower priority (or the quietest ones if priority is the
+    /// same) become virtual - they continue to play, but silently.
+    #[default]
+    Priority,
+
+    /// Stop the oldest playing sound
+    Oldest,
+
+    /// Stop the quietest playing sound
+    Quietest,
+
+    /// Don't play the new sound. It's treated as if it has finished playing.
+    FailNew,
+}
+
+/// Add together with [`Handle<AudioSource>`] to start playback after specified
+/// delay.
+#[derive(Component, Clone, Default)]
+pub struct AudioStartupDelay(pub Duration);
+
+impl AudioStartupDelay {
+    /// Set to small randomized delay (<= 10 ms)
+    pub fn random() -> Self {
+        let max = 0.010; // 10 ms
+        Self(Duration::from_secs_f32(thread_rng().gen_range(0. ..max)))
+    }
+
+    /// Randomly change value a bit
+    pub fn randomize(mut self) -> Self {
+        let k = thread_rng().gen_range(0.95..1.05);
+        self.0 = Duration::from_secs_f32(self.0.as_secs_f32() * k);
+        self
+    }
+}
+
+/// Add together with [`Handle<AudioSource>`] to start playback from specified
+/// position instead of the beginning (i.e. to resume ambience from saved
+/// position). Overrides [`AudioSource::randomize_start_offset`].
+///
+/// If offset is beyond the end, it wraps around for looped sounds; for other
+/// sounds it's clamped and a warning is logged.
+///
+/// Changes after playback has started are ignored.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct AudioStartOffset(pub Duration);
+
+/// Add together with [`Handle<AudioSource>`] to start playback at exactly the
+/// same sample as other sounds with the same key started in the same frame
+/// (i.e. for layers of adaptive music). Playback starts slightly later than
+/// it would otherwise; [`AudioStartupDelay`] is applied on top of it.
+///
+/// Sounds must be in the same [`AudioGroup`] and their assets must be already
+/// loaded, otherwise they won't be in sync. Ignored for [`AudioQueue`].
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct AudioSyncStart(pub u32);
+
+/// Add to an entity with [`Handle<AudioSource>`] to silence the sound while
+/// it keeps playing, so it stays in sync with other sounds when the
+/// component is removed (i.e. for layered music stems).
+///
+/// Unlike [`AudioSettings::paused`], muted sound advances. Unlike setting
+/// [`AudioParameters::volume`] to zero, original volume doesn't need to be
+/// remembered.
+#[derive(Component, Clone, Copy, Default)]
+pub struct AudioMute;
+
+/// Distortion effect applied to the sound (i.e. for damage feedback).
+///
+/// Can be added, changed or removed while sound is playing. See also
+/// [`AudioGroupParameters::distortion`].
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct AudioDistortion {
+    /// Amount of distortion, in `[0; 1]` range. 0 is the same as no effect.
+    pub level: f32,
+}
+
+/// Chorus (or flanger, with low `mix` and high `rate`) modulation effect
+/// applied to the sound.
+///
+/// Can be added, changed or removed while sound is playing.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct AudioChorus {
+    /// Modulation rate in Hz, in `[0; 20]` range
+    pub rate: f32,
+
+    /// Modulation depth, in `[0; 1]` range
+    pub depth: f32,
+
+    /// Volume of the modulated sound, in `[0; 1]` range. 0 is the same as no
+    /// effect.
+    pub mix: f32,
+}
+
+impl Default for AudioChorus {
+    fn default() -> Self {
+        Self {
+            rate: 0.8,
+            depth: 0.03,
+            mix: 0.5,
+        }
+    }
+}
+
+/// Add together with [`Handle<AudioSource>`] to assign sound to a non-default
+/// group.
+///
+/// Changing or removing it moves playing sound to another group without
+/// interrupting playback.
+///
+/// Each sound is assigned to a group, for easier manipulation.
+/// Groups are defined by user (except for default group `AudioGroup(0)`)
+///
+/// Groups are not required to be registered in any way.
+/// ATM they are used only for per-group settings, but there are plans for
+/// per-group effect plugins and combining several groups.
+///
+/// Instead of picking an integer, group can be created from a name with
+/// [`AudioGroupRegistry::register`] (or [`AudioGroupName`] component).
+/// Registered groups are serialized as their names by
+/// [`AudioSettings::save_to`]; otherwise groups are serialized as their IDs.
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct AudioGroup(pub i32);
+
+impl AudioGroup {
+    /// Group with ID derived from the name. Same name always results in the
+    /// same group.
+    ///
+    /// IDs of named groups are always negative, so they never collide with
+    /// non-negative IDs picked by user. Use [`AudioGroupRegistry::register`]
+    /// to detect collisions between names.
+    pub fn named(name: &str) -> Self {
+        // FNV-1a, so IDs are same between runs and platforms
+        let mut hash: u32 = 0x811c9dc5;
+        for byte in name.bytes() {
+            hash ^= byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+        Self(-((hash & 0x7fff_ffff) as i32) - 1)
+    }
+}
+
+/// Add instead of [`AudioGroup`] to refer to the group by name. It's resolved
+/// via [`AudioGroupRegistry`] and [`AudioGroup`] is added to the entity
+/// before playback starts.
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct AudioGroupName(pub std::borrow::Cow<'static, str>);
+
+impl AudioGroupName {
+    pub fn new(name: impl Into<std::borrow::Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+}
+
+/// Groups registered by name. Registered groups are the same as ones created
+/// with [`AudioGroup::named`], so they are stable between runs.
+#[derive(Resource, Default, Debug)]
+pub struct AudioGroupRegistry {
+    groups: HashMap<String, AudioGroup>,
+    names: HashMap<AudioGroup, String>,
+}
+
+impl AudioGroupRegistry {
+    /// Returns group with this name, registering it if it's not registered
+    /// yet. If two names result in the same group, error is logged.
+    pub fn register(&mut self, name: &str) -> AudioGroup {
+        if let Some(group) = self.groups.get(name) {
+            return *group;
+        }
+        let group = AudioGroup::named(name);
+        match self.names.get(&group) {
+            Some(existing) => error!(
+                "audio group name \"{name}\" collides with \"{existing}\", they will be the same group"
+            ),
+            None => {
+                self.names.insert(group, name.to_string());
+            }
+        }
+        self.groups.insert(name.to_string(), group);
+        group
+    }
+
+    /// Group with this name, if it was registered
+    pub fn get(&self, name: &str) -> Option<AudioGroup> {
+        self.groups.get(name).copied()
+    }
+
+    /// Name of the group, if it was registered
+    pub fn name(&self, group: AudioGroup) -> Option<&str> {
+        self.names.get(&group).map(|name| name.as_str())
+    }
+
+    /// All registered groups and their names
+    pub fn iter(&self) -> impl Iterator<Item = (&str, AudioGroup)> {
+        self.groups
+            .iter()
+            .map(|(name, group)| (name.as_str(), *group))
+    }
+}
+
+thread_local! {
+    /// Names of registered groups while settings are saved or loaded (see
+    /// `with_group_names`), so [`NamedGroup`] is serialized as a name. Names
+    /// found while loading are added to it.
+    static SERDE_GROUP_NAMES: std::cell::RefCell<Option<HashMap<AudioGroup, String>>> = default();
+}
+
+/// Call `f` with names of the registered groups available to [`NamedGroup`]
+/// (de)serialization. Returns them together with names which were loaded.
+#[cfg(feature = "serialize")]
+fn with_group_names<T>(
+    registry: &AudioGroupRegistry,
+    f: impl FnOnce() -> T,
+) -> (T, HashMap<AudioGroup, String>) {
+    SERDE_GROUP_NAMES.with(|names| *names.borrow_mut() = Some(registry.names.clone()));
+    let result = f();
+    let names = SERDE_GROUP_NAMES.with(|names| names.borrow_mut().take());
+    (result, names.unwrap_or_default())
+}
+
+/// [`AudioGroup`] which is written as its name if it's registered, but only
+/// inside `with_group_names` (i.e. in RON files written by
+/// [`AudioSettings::save_to`]). Otherwise it uses the usual format.
+#[derive(PartialEq, Eq, Hash)]
+struct NamedGroup(AudioGroup);
+
+impl Serialize for NamedGroup {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = SERDE_GROUP_NAMES.with(|names| {
+            let names = names.borrow();
+            names.as_ref().map(|names| names.get(&self.0).cloned())
+        });
+        match name {
+            Some(Some(name)) => serializer.serialize_str(&name),
+            Some(None) => serializer.serialize_i32(self.0 .0),
+            None => self.0.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NamedGroup {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = AudioGroup;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("group ID or name")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                i32::try_from(v)
+                    .map(AudioGroup)
+                    .map_err(|_| E::custom("group ID out of range"))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                i32::try_from(v)
+                    .map(AudioGroup)
+                    .map_err(|_| E::custom("group ID out of range"))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let group = AudioGroup::named(v);
+                SERDE_GROUP_NAMES.with(|names| {
+                    if let Some(names) = names.borrow_mut().as_mut() {
+                        names.insert(group, v.to_string());
+                    }
+                });
+                Ok(group)
+            }
+
+            /// Usual format, i.e. `(3)`
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                seq.next_element()?
+                    .map(AudioGroup)
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))
+            }
+        }
+
+        let with_names = SERDE_GROUP_NAMES.with(|names| names.borrow().is_some());
+        match with_names {
+            // only RON is used there, which is self-describing
+            true => deserializer.deserialize_any(Visitor).map(NamedGroup),
+            false => AudioGroup::deserialize(deserializer).map(NamedGroup),
+        }
+    }
+}
+
+/// Adapters for `#[serde(with = ...)]` which (de)serialize groups as
+/// [`NamedGroup`]
+mod named_groups {
+    use super::*;
+
+    pub fn serialize<S: serde::Serializer>(group: &AudioGroup, s: S) -> Result<S::Ok, S::Error> {
+        NamedGroup(*group).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<AudioGroup, D::Error> {
+        NamedGroup::deserialize(d).map(|group| group.0)
+    }
+
+    pub mod vec {
+        use super::*;
+
+        pub fn serialize<S: serde::Serializer>(
+            groups: &[AudioGroup],
+            s: S,
+        ) -> Result<S::Ok, S::Error> {
+            s.collect_seq(groups.iter().map(|group| NamedGroup(*group)))
+        }
+
+        pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+            d: D,
+        ) -> Result<Vec<AudioGroup>, D::Error> {
+            let groups = Vec::<NamedGroup>::deserialize(d)?;
+            Ok(groups.into_iter().map(|group| group.0).collect())
+        }
+    }
+
+    pub mod map {
+        use super::*;
+
+        pub fn serialize<S: serde::Serializer, V: Serialize>(
+            groups: &HashMap<AudioGroup, V>,
+            s: S,
+        ) -> Result<S::Ok, S::Error> {
+            s.collect_map(groups.iter().map(|(group, v)| (NamedGroup(*group), v)))
+        }
+
+        pub fn deserialize<'de, D: serde::Deserializer<'de>, V: Deserialize<'de>>(
+            d: D,
+        ) -> Result<HashMap<AudioGroup, V>, D::Error> {
+            let groups = HashMap::<NamedGroup, V>::deserialize(d)?;
+            Ok(groups.into_iter().map(|(group, v)| (group.0, v)).collect())
+        }
+    }
+}
+
+/// Load RON file, registering names of the groups in it
+#[cfg(feature = "serialize")]
+fn load_ron_with_groups<T: serde::de::DeserializeOwned + Default>(
+    path: &Path,
+    registry: &mut AudioGroupRegistry,
+) -> T {
+    let (value, names) = with_group_names(registry, || load_ron(path));
+    for name in names.values() {
+        registry.register(name);
+    }
+    value
+}
+
+/// Add audio geometry to the engine to occlude spatial sounds.
+/// Removal of this component removes geometry from the engine.
+///
+/// Changes to [`AudioGeometry::params`] and [`AudioGeometry::active`] are
+/// applied, changes to [`AudioGeometry::polygon_vertices`] are ignored.
+///
+/// Requires [`GlobalTransform`]. Changes to it move the geometry without
+/// rebuilding it, so moving geometry (i.e. doors) is cheap. Shear from
+/// non-uniformly scaled parents is ignored.
+///
+/// Can also be loaded via [`AssetServer`] from files with `.fmodgeo`
+/// extension (see [`AudioGeometry::to_bytes`]); add [`Handle<AudioGeometry>`]
+/// to an entity and this component will be added once the asset is loaded.
+/// It's replaced if the asset is modified (i.e. hot-reloaded), keeping
+/// [`AudioGeometry::active`].
+#[derive(Component, Clone, Serialize, Deserialize, TypeUuid, TypePath)]
+#[uuid = "5b0b1e0c-7d4e-4f37-9a3c-2f64c8e1a9d2"]
+#[serde(default)]
+pub struct AudioGeometry {
+    pub polygon_vertices: AudioGeometryData,
+    pub params: AudioGeometryParams,
+
+    /// Inactive geometry doesn't occlude anything, but is kept in the engine,
+    /// so it can be cheaply enabled again (i.e. for destroyed walls).
+    /// True by default, including when it's omitted in serialized data.
+    pub active: bool,
+
+    /// Split polygons which aren't planar or convex into triangles, so they
+    /// still occlude correctly. Otherwise such polygons are only reported
+    /// with a warning.
+    pub auto_triangulate: bool,
+}
+
+impl Default for AudioGeometry {
+    fn default() -> Self {
+        Self {
+            polygon_vertices: default(),
+            params: default(),
+            active: true,
+            auto_triangulate: false,
+        }
+    }
+}
+
+impl AudioGeometry {
+    /// Box centered at the origin.
+    ///
+    /// All polygons are wound counter-clockwise when viewed from outside.
+    pub fn cuboid(half_extents: Vec3, params: AudioGeometryParams) -> Self {
+        let (x, y, z) = (
+            Vec3::X * half_extents.x,
+            Vec3::Y * half_extents.y,
+            Vec3::Z * half_extents.z,
+        );
+        Self {
+            polygon_vertices: vec![
+                quad_polygon(x, y, z),
+                quad_polygon(-x, z, y),
+                quad_polygon(y, z, x),
+                quad_polygon(-y, x, z),
+                quad_polygon(z, x, y),
+                quad_polygon(-z, y, x),
+            ],
+            params,
+            ..default()
+        }
+    }
+
+    /// Single rectangle centered at the origin and facing `normal`.
+    ///
+    /// `size` is along two axes perpendicular to `normal`; rotation around
+    /// the normal is arbitrary. Polygon is wound counter-clockwise when viewed
+    /// from the side `normal` points to.
+    pub fn quad(size: Vec2, normal: Vec3, params: AudioGeometryParams) -> Self {
+        let normal = normal.try_normalize().unwrap_or(Vec3::Y);
+        let u = normal.any_orthonormal_vector();
+        let v = normal.cross(u);
+        Self {
+            polygon_vertices: vec![quad_polygon(Vec3::ZERO, u * size.x / 2., v * size.y / 2.)],
+            params,
+            ..default()
+        }
+    }
+
+    /// Cylinder centered at the origin with the Y axis, approximated with
+    /// `segments` side rectangles (at least 3) and two caps.
+    ///
+    /// All polygons are wound counter-clockwise when viewed from outside.
+    pub fn cylinder(
+        radius: f32,
+        height: f32,
+        segments: usize,
+        params: AudioGeometryParams,
+    ) -> Self {
+        let segments = segments.max(3);
+        let half_height = height / 2.;
+        let point = |index: usize, y: f32| {
+            let angle = index as f32 / segments as f32 * std::f32::consts::TAU;
+            Vec3::new(angle.sin() * radius, y, angle.cos() * radius)
+        };
+
+        let mut polygons: AudioGeometryData = (0..segments)
+            .map(|index| {
+                vec![
+                    point(index, -half_height),
+                    point(index + 1, -half_height),
+                    point(index + 1, half_height),
+                    point(index, half_height),
+                ]
+            })
+            .collect();
+        polygons.push(
+            (0..segments)
+                .map(|index| point(index, half_height))
+                .collect(),
+        );
+        polygons.push(
+            (0..segments)
+                .rev()
+                .map(|index| point(index, -half_height))
+                .collect(),
+        );
+
+        Self {
+            polygon_vertices: polygons,
+            params,
+            ..default()
+        }
+    }
+
+    /// Serialize in FMOD geometry format, to be loaded with
+    /// [`AudioGeometry::from_bytes`] or as a `.fmodgeo` asset.
+    ///
+    /// Polygons are stored as the engine would use them (i.e. after
+    /// [`AudioGeometry::auto_triangulate`]); [`AudioGeometry::active`] isn't
+    /// stored.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AudioLoadError> {
+        let mut bridge = BRIDGE.lock().unwrap();
+        let bridge = bridge.as_mut().ok_or(AudioLoadError::Disabled)?;
+        let first_error = bridge.error_count();
+
+        let id = bridge
+            .pin_mut()
+            .add_geometry(geometry_params(self, &GlobalTransform::IDENTITY));
+        if id == -1 {
+            let file = "geometry".to_string();
+            return Err(AudioLoadError::engine(file, bridge.pin_mut(), first_error));
+        }
+        let bytes = bridge.pin_mut().save_geometry(id);
+        bridge.pin_mut().free_geometry(id);
+
+        match bytes.is_empty() {
+            true => Err(AudioLoadError::engine(
+                "geometry".to_string(),
+                bridge.pin_mut(),
+                first_error,
+            )),
+            false => Ok(bytes),
+        }
+    }
+
+    /// Load geometry saved with [`AudioGeometry::to_bytes`]. Occlusion is
+    /// taken from the first polygon.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AudioLoadError> {
+        let mut bridge = BRIDGE.lock().unwrap();
+        let bridge = bridge.as_mut().ok_or(AudioLoadError::Disabled)?;
+        let first_error = bridge.error_count();
+
+        let params = bridge.pin_mut().load_geometry(bytes);
+        if bridge.error_count() != first_error {
+            return Err(AudioLoadError::engine(
+                format!("geometry of {} bytes in memory", bytes.len()),
+                bridge.pin_mut(),
+                first_error,
+            ));
+        }
+
+        Ok(Self {
+            polygon_vertices: params
+                .polygons
+                .into_iter()
+                .map(|polygon| polygon.vertices.into_iter().map(Vec3::from).collect())
+                .collect(),
+            params: AudioGeometryParams {
+                direct_occlusion: params.direct_occlusion,
+                reverb_occlusion: params.reverb_occlusion,
+            },
+            ..default()
+        })
+    }
+}
+
+/// Rectangle with given center and half-sizes, facing `u.cross(v)`
+fn quad_polygon(center: Vec3, u: Vec3, v: Vec3) -> Vec<Vec3> {
+    vec![
+        center - u - v,
+        center + u - v,
+        center + u + v,
+        center - u + v,
+    ]
+}
+
+/// Vec of planar polygons - each polygon can have any number of points,
+/// but they must lie on the same plane.
+///
+/// Polygon must be convex. Invalid polygons are reported when
+/// [`AudioGeometry`] is added, see [`AudioGeometry::auto_triangulate`].
+pub type AudioGeometryData = Vec<Vec<Vec3>>;
+
+/// Add to an entity with physics [`Collider`](bevy_rapier3d::prelude::Collider)
+/// to generate [`AudioGeometry`] from its shape.
+///
+/// Geometry is generated once, when either component is added; later changes
+/// to the collider shape are ignored. Curved shapes are approximated with
+/// polygons. Half-spaces, heightfields and polylines aren't supported.
+#[cfg(feature = "physics-integration")]
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct AudioColliderGeometry(pub AudioGeometryParams);
+
+/// Add to an entity with ball [`Collider`](bevy_rapier3d::prelude::Collider)
+/// to generate [`AudioReverbSphere`] with the same radius.
+///
+/// Reverb is generated once, when either component is added.
+#[cfg(feature = "physics-integration")]
+#[derive(Component, Clone, Debug)]
+pub struct AudioColliderReverb {
+    /// Effect fades out over this distance towards the collider surface, see
+    /// [`AudioReverbSphere::min_distance`]
+    pub falloff: f32,
+
+    pub props: AudioReverbProps,
+}
+
+#[cfg(feature = "physics-integration")]
+impl Default for AudioColliderReverb {
+    fn default() -> Self {
+        Self {
+            falloff: 5.,
+            props: default(),
+        }
+    }
+}
+
+/// Add to an entity with [`Handle<AudioSource>`] to receive [`AudioBeat`]
+/// event on each beat (i.e. to sync gameplay with music).
+///
+/// Beats are computed from the actual playback position, so they don't drift
+/// even if frame rate or playback speed changes. Events are sent once per
+/// frame, so several beats can be sent at once if frame rate is low.
+///
+/// Not supported for sounds played in reverse.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct AudioBeatSync {
+    /// Beats per minute
+    pub bpm: f32,
+
+    /// Position of the first beat in the sound
+    pub offset: Duration,
+}
+
+/// Sent once for each failure of the audio engine (they are also logged),
+/// i.e. when asset fails to load or sound fails to play.
+///
+/// Not sent for errors returned as [`AudioLoadError`] by constructors like
+/// [`AudioSource::from_memory`].
+#[derive(Event, Clone, PartialEq, Eq, Debug)]
+pub struct AudioError {
+    /// What failed
+    pub context: AudioErrorContext,
+
+    /// Error message
+    pub message: String,
+
+    /// `FMOD_RESULT` code, 0 if it's not an FMOD error
+    pub code: i32,
+}
+
+impl AudioError {
+    fn new(context: AudioErrorContext, error: bridge::BridgeError) -> Self {
+        Self {
+            context,
+            message: error.message,
+            code: error.code,
+        }
+    }
+
+    /// From the last error reported by the engine starting from `first_error`
+    /// (see `Bridge::error_count`), which are taken
+    fn engine(
+        context: AudioErrorContext,
+        bridge: std::pin::Pin<&mut bridge::Bridge>,
+        first_error: usize,
+    ) -> Self {
+        match bridge.take_errors(first_error).pop() {
+            Some(error) => Self::new(context, error),
+            None => Self {
+                context,
+                message: "unknown error".to_string(),
+                code: 0,
+            },
+        }
+    }
+
+    fn from_load_error(context: AudioErrorContext, error: AudioLoadError) -> Self {
+        match error {
+            AudioLoadError::Fmod { code, message, .. } => Self {
+                context,
+                message,
+                code,
+            },
+            error => Self {
+                context,
+                message: error.to_string(),
+                code: 0,
+            },
+        }
+    }
+}
+
+/// See [`AudioError::context`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AudioErrorContext {
+    /// Asset loaded via [`AssetServer`] (or [`ReinitAudioEngine`])
+    Asset(PathBuf),
+
+    /// Sound, geometry or reverb of the entity. For [`AudioBytes`] it's a
+    /// failure to load the sound.
+    Entity(Entity),
+
+    /// Not related to specific asset or entity
+    Engine,
+}
+
+/// Sent for each beat of a sound with [`AudioBeatSync`]
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AudioBeat {
+    /// Entity with the sound
+    pub entity: Entity,
+
+    /// Index of the beat, counting from the start of the sound. Starts from
+    /// zero again when looped sound wraps around.
+    pub index: u32,
+}
+
+/// Add to an entity with [`Handle<AudioSource>`] to get how much the sound is
+/// currently occluded by [`AudioGeometry`]. Updated every frame while the
+/// sound is playing.
+///
+/// Zero for non-spatial sounds and sounds in groups with
+/// [`AudioGroupParameters::ignore_geometry`].
+#[derive(Component, Clone, Copy, Default, PartialEq, Debug)]
+pub struct AudioOcclusionReadout {
+    /// Occlusion of non-reverberated part of sound, in `[0; 1]` range
+    /// (0 - not occluded, 1 - fully occluded).
+    pub direct: f32,
+
+    /// Occlusion of reverberated part of sound, in `[0; 1]` range.
+    pub reverb: f32,
+}
+
+/// Parameters for audio geometry.
+///
+/// See also [`AudioSettings::lowpass_cutoff_when_occluded`].
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioGeometryParams {
+    /// Volume of non-reverberated part of sound behind the geometry, in `[0;
+    /// 1]` range.
+    pub direct_occlusion: f32,
+
+    /// Volume of reverberated part of sound (when geometry is between the sound
+    /// and the center of the reverb sphere), in `[0; 1]` range.
+    pub reverb_occlusion: f32,
+}
+
+impl Default for AudioGeometryParams {
+    fn default() -> Self {
+        Self {
+            direct_occlusion: 0.3,
+            reverb_occlusion: 0.3,
+        }
+    }
+}
+
+/// Add reverb sphere to the engine to affect spatial sounds.
+/// Removal of this component removes reverb from the engine.
+///
+/// Otherwise this component is ignored.
+///
+/// Requires [`GlobalTransform`]. Changes to it will be ignored, unless
+/// [`AudioSettings::reverb_blending`] is enabled.
+// TODO(later): dont' ignore changes
+#[derive(Component, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioReverbSphere {
+    /// Effect is applied in full to sounds closer than that
+    pub min_distance: f32,
+
+    /// Effect is not applied to sounds farther than that
+    pub max_distance: f32,
+
+    pub props: AudioReverbProps,
+}
+
+impl Default for AudioReverbSphere {
+    fn default() -> Self {
+        Self {
+            min_distance: 5.,
+            max_distance: 20.,
+            props: default(),
+        }
+    }
+}
+
+/// Reverb properties
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioReverbProps {
+    /// Reverberation decay time.
+    ///
+    /// Milliseconds, range `[0; 20_000]`.
+    pub decay_time: f32,
+
+    /// Initial reflection delay time.
+    ///
+    /// Milliseconds, range `[0; 300]`.
+    pub early_delay: f32,
+
+    /// Late reverberation delay time relative to initial reflection.
+    ///
+    /// Milliseconds, range `[0; 100]`.
+    pub late_delay: f32,
+
+    /// Reference high frequency.
+    ///
+    /// Hertz, range `[20; 20_000]`.
+    pub hf_reference: f32,
+
+    /// High-frequency to mid-frequency decay time ratio.
+    ///
+    /// Percent, range `[10; 100]`.
+    pub hf_decay_ratio: f32,
+
+    /// Value that controls the echo density in the late reverberation decay.
+    ///
+    /// Percent, range `[10; 100]`.
+    pub diffusion: f32,
+
+    /// Value that controls the modal density in the late reverberation decay.
+    ///
+    /// Percent, range `[10; 100]`.
+    pub density: f32,
+
+    /// Reference low frequency.
+    ///
+    /// Hertz, range `[20; 1000]`.
+    pub low_shelf_frequency: f32,
+
+    /// Relative room effect level at low frequencies.
+    ///
+    /// Decibels, range `[-36, 12]`.
+    pub low_shelf_gain: f32,
+
+    /// Relative room effect level at high frequencies.
+    ///
+    /// Hertz, range `[0; 200_000]`.
+    pub high_cut: f32,
+
+    /// Early reflections level relative to room effect.
+    ///
+    /// Percent, range `[0; 100]`.
+    pub early_late_mix: f32,
+
+    /// Room effect level at mid frequencies.
+    ///
+    /// Decibels, range `[-80; 20]`.
+    pub wet_level: f32,
+}
+
+impl Default for AudioReverbProps {
+    // `FMOD_PRESET_GENERIC`
+    fn default() -> Self {
+        Self {
+            decay_time: 1500.,
+            early_delay: 7.,
+            late_delay: 11.,
+            hf_reference: 5000.,
+            hf_decay_ratio: 50.,
+            diffusion: 50.,
+            density: 100.,
+            low_shelf_frequency: 250.,
+            low_shelf_gain: 0.,
+            high_cut: 200_000.,
+            early_late_mix: 50.,
+            wet_level: -6.,
+        }
+    }
+}
+
+impl AudioReverbProps {
+    /// `FMOD_PRESET_HALLWAY`, sounds like somewhat wide corridor
+    pub fn hallway() -> Self {
+        Self {
+            decay_time: 1500.,
+            early_delay: 7.,
+            late_delay: 11.,
+            hf_reference: 5000.,
+            hf_decay_ratio: 59.,
+            diffusion: 100.,
+            density: 100.,
+            low_shelf_frequency: 250.,
+            low_shelf_gain: 0.,
+            high_cut: 7800.,
+            early_late_mix: 87.,
+            wet_level: -5.5,
+        }
+    }
+
+    /// `FMOD_PRESET_HANGAR`, sounds like giant empty room
+    pub fn hangar() -> Self {
+        Self {
+            decay_time: 10000.,
+            early_delay: 20.,
+            late_delay: 30.,
+            hf_reference: 5000.,
+            hf_decay_ratio: 23.,
+            diffusion: 100.,
+            density: 100.,
+            low_shelf_frequency: 250.,
+            low_shelf_gain: 0.,
+            high_cut: 3400.,
+            early_late_mix: 72.,
+            wet_level: -7.4,
+        }
+    }
+
+    /// Exaggerated reverb for giant empty room
+    pub fn huge_room() -> Self {
+        Self {
+            decay_time: 6000.,
+            wet_level: 3.,
+            ..Self::hangar()
+        }
+    }
+}
+
+/// Marker for entity whose position is used for spatial
+/// audio.
+///
+/// Requires [`GlobalTransform`].
+///
+/// There can be up to 8 listeners (i.e. for split-screen), extra ones are
+/// ignored. Each sound is heard only by the nearest listener. Reverb blending
+/// (see [`AudioSettings::reverb_blending`]) uses only one of them.
+///
+/// If listener doesn't exist, spatial sounds will play at the last remembered
+/// position (which is `Vec3::ZERO` on startup).
+#[derive(Component, Clone, Default)]
+pub struct AudioListener;
+
+/// Add to an entity (i.e. player character in a third-person game) to measure
+/// distance attenuation of spatial sounds from it instead of from
+/// [`AudioListener`]. Panning, Doppler effect and geometry occlusion are
+/// still relative to the listener.
+///
+/// Requires [`GlobalTransform`]. Ignored if there are multiple listeners or
+/// multiple attenuation centers.
+///
+/// _This is done by scaling [`AudioParameters::min_distance`] and
+/// [`AudioParameters::max_distance`] of each sound by the ratio of its
+/// distances to the listener and to the attenuation center._
+#[derive(Component, Clone, Default)]
+pub struct AudioAttenuationCenter;
+
+/// Global engine settings
+#[derive(Resource, Clone, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Per-group settings.
+    ///
+    /// If group isn't present here, defaults will be used for sounds belonging
+    /// to that group.
+    #[serde(with = "named_groups::map")]
+    pub groups: HashMap<AudioGroup, AudioGroupParameters>,
+
+    /// Linear volume multiplier applied to all sounds.
+    ///
+    /// Should be in `[0; 1]` range.
+    pub master_volume: f32,
+
+    /// If true, entities are despawned when their sound stops or fails to
+    /// play (this includes [`AudioPlaylistPlayer`] when playlist ends).
+    ///
+    /// If false, only audio components are removed ([`Handle<AudioSource>`],
+    /// [`AudioPlaylistPlayer`] and others which start playback), so entities
+    /// can be managed by other systems. There is no per-entity override.
+    pub auto_despawn: bool,
+
+    /// How long it takes for volume of a group to change from 0 to 1 (or
+    /// back) when [`AudioSettings::master_volume`],
+    /// [`AudioSettings::enabled`] or [`AudioGroupParameters::volume`] is
+    /// changed. Prevents clicks on abrupt changes (i.e. when dragging a
+    /// slider). Zero applies changes instantly.
+    pub volume_ramp: Duration,
+
+    /// If false, consider master volume to be zero.
+    ///
+    /// _Hearing same sounds and music over-and-over-and-over-again in long
+    /// debugging sessions gets really, really annoying, doesn't it?_
+    pub enabled: bool,
+
+    /// If true, playback of all sounds is frozen until this is set back to
+    /// false.
+    ///
+    /// Unlike `enabled`, this stops sounds from advancing. Paused sounds are
+    /// not considered stopped, so their entities aren't despawned.
+    pub paused: bool,
+
+    /// Limiter on the final mix, prevents clipping when many sounds are
+    /// playing at once. Disabled if `None`.
+    ///
+    /// It's applied after all volume settings (including `master_volume`),
+    /// right before output.
+    pub master_limiter: Option<AudioLimiter>,
+
+    /// Playback speed multiplier applied to all sounds, also changes pitch.
+    /// Use it to follow speed of the game world (i.e. slow motion), with
+    /// groups like UI or music opting out.
+    ///
+    /// Must be positive. Groups with
+    /// [`AudioGroupParameters::ignore_global_speed`] set aren't affected.
+    pub global_speed: f32,
+
+    /// Pitch (and playback speed) multiplier applied to the whole mix,
+    /// including groups which ignore [`AudioSettings::global_speed`]. Use it
+    /// for effects on everything player hears (i.e. tape stop when the game
+    /// is paused).
+    ///
+    /// Must be positive. Both multipliers are applied to the master group at
+    /// once, so if no group ignores [`AudioSettings::global_speed`] they are
+    /// interchangeable. It multiplies with per-sound speed, so setting it
+    /// back to 1 restores original playback rates.
+    pub master_pitch: f32,
+
+    /// If [`AudioSource`] asset isn't loaded yet when sound is added,
+    /// playback is delayed until it is loaded, but no longer than this.
+    pub load_timeout: Duration,
+
+    /// Muffle distant spatial sounds, as high frequencies are absorbed by air.
+    /// Disabled if `None`.
+    ///
+    /// _This adds a filter to each spatial sound, and updates it every
+    /// frame._
+    pub air_absorption: Option<AirAbsorption>,
+
+    /// Cutoff frequency (Hz) of the low-pass filter applied to spatial sounds
+    /// which are fully occluded by [`AudioGeometry`], so they are muffled in
+    /// addition to being quieter. Partially occluded sounds are filtered
+    /// proportionally. Disabled if `None`.
+    ///
+    /// Sounds in groups with [`AudioGroupParameters::ignore_geometry`] aren't
+    /// affected.
+    ///
+    /// _Occlusion is calculated for each spatial sound every frame._
+    pub lowpass_cutoff_when_occluded: Option<f32>,
+
+    /// How positions of spatial sounds are sampled from their transforms,
+    /// so fast-moving sounds don't jump and Doppler effect doesn't stutter
+    /// when frame rate is low or uneven.
+    ///
+    /// _Velocity of sounds is always measured between engine updates (see
+    /// [`FmodAudioPlugin::update_hz`]), not between frames._
+    pub emitter_interpolation: AudioInterpolation,
+
+    /// Rules for automatically lowering volume of some groups while other
+    /// groups are playing.
+    pub ducking: Vec<AudioDucking>,
+
+    /// Cutoff frequency (Hz) of the low-pass filter applied to all sounds
+    /// (i.e. muffled sound when underwater). Disabled if `None`.
+    pub master_lowpass: Option<f32>,
+
+    /// How long it takes for [`AudioSettings::master_lowpass`] to fully change
+    /// (including enabling and disabling), to avoid abrupt changes.
+    pub master_lowpass_ramp: Duration,
+
+    /// Instead of applying each [`AudioReverbSphere`] separately, blend
+    /// properties of spheres closest to the listener into a single reverb
+    /// which is applied to all sounds. Disabled if `None`.
+    pub reverb_blending: Option<AudioReverbBlending>,
+
+    /// Reverb applied to all sounds regardless of their position (i.e. room
+    /// tone of a whole indoor level). Disabled if `None`.
+    ///
+    /// It's applied in addition to [`AudioReverbSphere`] effects, not instead
+    /// of them.
+    pub ambient_reverb: Option<AudioReverbProps>,
+
+    pub engine: AudioEngineSettings,
+}
+
+impl AudioSettings {
+    /// Volume multiplier for the group, as specified by settings (includes
+    /// master volume and [`AudioSettings::enabled`]).
+    ///
+    /// Automatic changes such as [`AudioDucking`] are not included, for that
+    /// see [`GroupStats::audibility`].
+    pub fn effective_volume(&self, group: AudioGroup) -> f32 {
+        let group_volume = self.groups.get(&group).map(|p| p.volume).unwrap_or(1.);
+        self.effective_master_volume() * group_volume
+    }
+
+    /// Effective volume of groups which aren't in [`AudioSettings::groups`]
+    fn effective_master_volume(&self) -> f32 {
+        if self.enabled {
+            self.master_volume
+        } else {
+            0.
+        }
+    }
+
+    /// Current volume settings, see [`AudioSettingsSnapshot`]
+    pub fn snapshot(&self) -> AudioSettingsSnapshot {
+        AudioSettingsSnapshot {
+            master_volume: self.master_volume,
+            enabled: self.enabled,
+            group_volumes: self
+                .groups
+                .iter()
+                .map(|(group, params)| (*group, params.volume))
+                .collect(),
+        }
+    }
+
+    /// Apply volume settings from the snapshot. Groups not present in the
+    /// snapshot are left as is.
+    pub fn restore(&mut self, snapshot: &AudioSettingsSnapshot) {
+        self.master_volume = snapshot.master_volume;
+        self.enabled = snapshot.enabled;
+        for (group, volume) in snapshot.group_volumes.iter() {
+            self.groups.entry(*group).or_default().volume = *volume;
+        }
+    }
+
+    /// Save settings to RON file. Groups registered in `registry` are saved
+    /// as their names.
+    #[cfg(feature = "serialize")]
+    pub fn save_to(
+        &self,
+        path: impl AsRef<Path>,
+        registry: &AudioGroupRegistry,
+    ) -> std::io::Result<()> {
+        with_group_names(registry, || save_ron(self, path.as_ref())).0
+    }
+
+    /// Load settings from RON file. If file is missing or invalid, defaults
+    /// are returned (for invalid file a warning is logged). Groups saved as
+    /// names are registered in `registry`.
+    #[cfg(feature = "serialize")]
+    pub fn load_from(path: impl AsRef<Path>, registry: &mut AudioGroupRegistry) -> Self {
+        load_ron_with_groups(path.as_ref(), registry)
+    }
+}
+
+/// Volume settings which player usually can change in game options. See
+/// [`AudioSettings::snapshot`].
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioSettingsSnapshot {
+    /// [`AudioSettings::master_volume`]
+    pub master_volume: f32,
+
+    /// [`AudioSettings::enabled`]
+    pub enabled: bool,
+
+    /// [`AudioGroupParameters::volume`]
+    #[serde(with = "named_groups::map")]
+    pub group_volumes: HashMap<AudioGroup, f32>,
+}
+
+impl Default for AudioSettingsSnapshot {
+    /// Snapshot of default settings
+    fn default() -> Self {
+        AudioSettings::default().snapshot()
+    }
+}
+
+impl AudioSettingsSnapshot {
+    /// Save snapshot to RON file, same as [`AudioSettings::save_to`]
+    #[cfg(feature = "serialize")]
+    pub fn save_to(
+        &self,
+        path: impl AsRef<Path>,
+        registry: &AudioGroupRegistry,
+    ) -> std::io::Result<()> {
+        with_group_names(registry, || save_ron(self, path.as_ref())).0
+    }
+
+    /// Load snapshot from RON file, same as [`AudioSettings::load_from`]
+    #[cfg(feature = "serialize")]
+    pub fn load_from(path: impl AsRef<Path>, registry: &mut AudioGroupRegistry) -> Self {
+        load_ron_with_groups(path.as_ref(), registry)
+    }
+}
+
+#[cfg(feature = "serialize")]
+fn save_ron(value: &impl Serialize, path: &Path) -> std::io::Result<()> {
+    let text = ron::ser::to_string_pretty(value, default())
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+    std::fs::write(path, text)
+}
+
+#[cfg(feature = "serialize")]
+fn load_ron<T: serde::de::DeserializeOwned + Default>(path: &Path) -> T {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            if error.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to read {path:?}: {error}");
+            }
+            return default();
+        }
+    };
+    ron::from_str(&text).unwrap_or_else(|error| {
+        warn!("Failed to parse {path:?}: {error}");
+        default()
+    })
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            groups: default(),
+            master_volume: 0.5,
+            auto_despawn: true,
+            volume_ramp: Duration::ZERO,
+            enabled: true,
+            paused: false,
+            master_limiter: None,
+            global_speed: 1.,
+            master_pitch: 1.,
+            load_timeout: Duration::from_secs(5),
+            air_absorption: None,
+            lowpass_cutoff_when_occluded: None,
+            emitter_interpolation: default(),
+            ducking: default(),
+            master_lowpass: None,
+            master_lowpass_ramp: Duration::from_millis(200),
+            reverb_blending: None,
+            ambient_reverb: None,
+            engine: default(),
+        }
+    }
+}
+
+/// Per-group engine settings
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioGroupParameters {
+    /// Linear volume multiplier for all sounds in the group.
+    ///
+    /// Should be in `[0; 1]` range.
+    pub volume: f32,
+
+    /// If true, [`AudioSettings::global_speed`] doesn't affect sounds in the
+    /// group (i.e. music).
+    pub ignore_global_speed: bool,
+
+    /// Max number of sounds in the group which can play at once.
+    ///
+    /// When a new sound is started and limit is reached, sound with the
+    /// lowest priority (and then the furthest from the listener) is stopped,
+    /// as if it finished playing. If that's the new sound, it isn't played.
+    pub max_audible: Option<usize>,
+
+    /// If true, spatial sounds in the group aren't occluded by
+    /// [`AudioGeometry`] (i.e. music played from a spatial emitter).
+    ///
+    /// **Used only when sound is started, changes don't affect sounds which are
+    /// already playing!**
+    pub ignore_geometry: bool,
+
+    /// Effects applied to the whole group, in order of processing.
+    ///
+    /// Effects are re-created only if their list changes (not their
+    /// parameters).
+    ///
+    /// _Not serialized, since plugins must be loaded at runtime._
+    #[serde(skip)]
+    pub effects: Vec<AudioEffect>,
+
+    /// Lower volume of this group while [`AudioDucking::trigger`] group is
+    /// playing. This group is ducked in addition to
+    /// [`AudioDucking::targets`], which can be left empty.
+    ///
+    /// Same as adding it to [`AudioSettings::ducking`].
+    pub duck_when: Option<AudioDucking>,
+
+    /// Three-band parametric equalizer (i.e. for "radio" voice filter),
+    /// applied before [`AudioGroupParameters::effects`].
+    pub eq: Option<[AudioEqBand; 3]>,
+
+    /// Distortion applied to all sounds in the group, in `[0; 1]` range.
+    /// See [`AudioDistortion`].
+    pub distortion: Option<f32>,
+}
+
+impl Default for AudioGroupParameters {
+    fn default() -> Self {
+        Self {
+            volume: 1.,
+            ignore_global_speed: false,
+            max_audible: None,
+            ignore_geometry: false,
+            effects: vec![],
+            duck_when: None,
+            eq: None,
+            distortion: None,
+        }
+    }
+}
+
+/// Band of a parametric equalizer, see [`AudioGroupParameters::eq`]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub struct AudioEqBand {
+    /// Center frequency, Hz. Range is `[20; 22000]`.
+    pub frequency: f32,
+
+    /// Boost or cut at the center frequency, decibels. Range is `[-30; 30]`,
+    /// 0 doesn't change the sound.
+    pub gain_db: f32,
+
+    /// Quality factor, higher values make the band narrower. Range is
+    /// `[0.1; 10]`.
+    pub q: f32,
+}
+
+impl AudioEqBand {
+    pub fn new(frequency: f32, gain_db: f32, q: f32) -> Self {
+        Self {
+            frequency,
+            gain_db,
+            q,
+        }
+    }
+}
+
+impl Default for AudioEqBand {
+    fn default() -> Self {
+        Self::new(1000., 0., 0.707)
+    }
+}
+
+/// See [`AudioGroupParameters::effects`]
+#[derive(Clone, PartialEq, Debug)]
+pub enum AudioEffect {
+    /// Third-party DSP plugin
+    Plugin {
+        plugin: AudioDspPlugin,
+        /// Float parameters in order of their indices. Meaning depends on the
+        /// plugin.
+        params: Vec<f32>,
+    },
+}
+
+/// Third-party DSP plugin (dynamic library), see [`AudioEffect::Plugin`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct AudioDspPlugin(usize);
+
+impl AudioDspPlugin {
+    /// Load plugin from file. Filename must be relative to current directory.
+    ///
+    /// Plugins can't be unloaded. They are loaded again on
+    /// [`ReinitAudioEngine`].
+    ///
+    /// Returns [`None`] on error or if audio is disabled (see
+    /// [`AudioState`]).
+    pub fn load(filename: impl Into<PathBuf>) -> Option<Self> {
+        let filename = filename.into();
+        let mut bridge = BRIDGE.lock().unwrap();
+        let id = bridge
+            .as_mut()?
+            .pin_mut()
+            .load_plugin(&filename.to_string_lossy());
+        if id == -1 {
+            return None;
+        }
+
+        let mut plugins = DSP_PLUGINS.lock().unwrap();
+        plugins.push((filename, id));
+        Some(Self(plugins.len() - 1))
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Loaded [`AudioDspPlugin`], as filename and engine ID (-1 if it failed
+    /// to load after re-initialization)
+    static ref DSP_PLUGINS: Mutex<Vec<(PathBuf, EngineId)>> = default();
+}
+
+/// Number of channels currently playing in each group.
+///
+/// Updated every frame, changes made by user are ignored.
+/// Groups which have never been used are not present.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct AudioGroupStats {
+    pub groups: HashMap<AudioGroup, GroupStats>,
+}
+
+impl AudioGroupStats {
+    /// Returns zeroes for groups which aren't present
+    pub fn get(&self, group: AudioGroup) -> GroupStats {
+        self.groups.get(&group).copied().unwrap_or_default()
+    }
+}
+
+/// Memory used by the audio engine and loaded sources, i.e. to find leaks.
+///
+/// Updated every frame, changes made by user are ignored.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct AudioMemoryStats {
+    /// Bytes currently allocated by the engine (including all sources)
+    pub current: usize,
+
+    /// Maximum of [`AudioMemoryStats::current`] since the engine was initialized
+    pub max: usize,
+
+    /// Approximate bytes used by each [`AudioSource`] asset. Sources created
+    /// with [`AudioSource::from_memory`] also count copy of the file which
+    /// is kept to reload it on [`ReinitAudioEngine`]; sources loaded via
+    /// [`AssetServer`] don't keep it. Use [`AssetServer::get_handle_path`] to
+    /// get asset path.
+    ///
+    /// Sources from [`AudioBank`] share memory with it and aren't counted.
+    pub sources: HashMap<bevy::asset::HandleId, usize>,
+}
+
+impl AudioMemoryStats {
+    /// Sum of [`AudioMemoryStats::sources`]
+    pub fn sources_total(&self) -> usize {
+        self.sources.values().sum()
+    }
+}
+
+/// Number of playing channels, i.e. to tune
+/// [`AudioEngineInitSettings::max_virtual_channels`] and
+/// [`AudioEngineInitSettings::max_active_channels`].
+///
+/// Updated every frame, changes made by user are ignored.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct AudioChannelUsage {
+    /// All playing channels, including virtual ones
+    pub virtual_playing: u32,
+
+    /// Channels which are actually being played (not virtual)
+    pub real_playing: u32,
+}
+
+/// See [`AudioGroupStats`]
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct GroupStats {
+    /// Channels which are actually being played
+    pub playing: usize,
+
+    /// Channels which are muted due to [`AudioEngineInitSettings::max_active_channels`]
+    /// limit or being too quiet
+    pub virtualized: usize,
+
+    /// Volume multiplier actually applied to the group, including master
+    /// volume and automatic changes such as [`AudioDucking`].
+    pub audibility: f32,
+}
+
+/// Air absorption parameters, see [`AudioSettings::air_absorption`].
+///
+/// Sounds farther than `reference_distance` from the listener are low-pass
+/// filtered; cutoff frequency halves every `halving_distance` beyond that, but
+/// doesn't go lower than `min_cutoff`.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AirAbsorption {
+    pub reference_distance: f32,
+    pub halving_distance: f32,
+
+    /// Hertz
+    pub min_cutoff: f32,
+}
+
+impl Default for AirAbsorption {
+    fn default() -> Self {
+        Self {
+            reference_distance: 10.,
+            halving_distance: 20.,
+            min_cutoff: 1000.,
+        }
+    }
+}
+
+impl AirAbsorption {
+    /// Cutoff frequency (Hz) of the low-pass filter for sound at that distance
+    /// from the listener.
+    pub fn cutoff(&self, distance: f32) -> f32 {
+        const MAX_CUTOFF: f32 = 22_000.; // upper limit of human hearing
+
+        let distance = (distance - self.reference_distance).max(0.);
+        let cutoff = MAX_CUTOFF * 0.5_f32.powf(distance / self.halving_distance.max(f32::EPSILON));
+        cutoff.clamp(self.min_cutoff.min(MAX_CUTOFF), MAX_CUTOFF)
+    }
+}
+
+/// While any sound in `trigger` group is playing, volume of `targets` groups
+/// is lowered (i.e. music and effects are lowered during dialogue).
+///
+/// This doesn't change [`AudioGroupParameters::volume`].
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioDucking {
+    #[serde(with = "named_groups")]
+    pub trigger: AudioGroup,
+    #[serde(with = "named_groups::vec")]
+    pub targets: Vec<AudioGroup>,
+
+    /// How much volume of targets is lowered, in decibels. Must be positive.
+    pub amount_db: f32,
+
+    /// How long it takes to lower the volume
+    pub attack: Duration,
+
+    /// How long it takes to restore the volume after trigger group stopped
+    /// playing
+    pub release: Duration,
+}
+
+impl AudioDucking {
+    /// Ducking without targets and with default attack and release
+    pub fn new(trigger: AudioGroup, amount_db: f32) -> Self {
+        Self {
+            trigger,
+            amount_db,
+            ..default()
+        }
+    }
+}
+
+impl Default for AudioDucking {
+    fn default() -> Self {
+        Self {
+            trigger: default(),
+            targets: default(),
+            amount_db: 10.,
+            attack: Duration::from_millis(100),
+            release: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Reverb blending parameters, see [`AudioSettings::reverb_blending`].
+///
+/// Each sphere affects the listener fully within its `min_distance`, and not
+/// at all beyond `max_distance`. Properties of affecting spheres are weighted
+/// by that, and overall reverb level is reduced if the listener is only
+/// partially affected.
+///
+/// _Sphere reverb isn't occluded by [`AudioGeometry`] in this mode._
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioReverbBlending {
+    /// How many closest spheres are blended together
+    pub max_spheres: usize,
+}
+
+impl Default for AudioReverbBlending {
+    fn default() -> Self {
+        Self { max_spheres: 4 }
+    }
+}
+
+/// Limiter parameters, see [`AudioSettings::master_limiter`]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioLimiter {
+    /// Maximum level of the output signal.
+    ///
+    /// Decibels, range `[-12; 0]`.
+    pub ceiling_db: f32,
+
+    /// Time to return the gain reduction to full effect.
+    ///
+    /// Milliseconds, range `[1; 1000]`.
+    pub release_ms: f32,
+}
+
+impl Default for AudioLimiter {
+    fn default() -> Self {
+        Self {
+            ceiling_db: 0.,
+            release_ms: 10.,
+        }
+    }
+}
+
+/// Global engine configuration
+#[derive(Resource, Clone, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioEngineSettings {
+    /// How much pitch varies with relative speed (Doppler effect).
+    ///
+    /// With this at 1 effective sound speed is 340 m/s.
+    pub doppler_scale: f32,
+
+    /// Used only for doppler. Set to 1 if you use meters, set to 3.28 if you
+    /// use feet.
+    pub distance_scale: f32,
+
+    /// Global factor applied to all distance calculations:
+    ///
+    /// `distance = (distance - minDistance) * rolloffscale + minDistance`
+    pub rolloff_scale: f32,
+}
+
+impl Default for AudioEngineSettings {
+    fn default() -> Self {
+        Self {
+            doppler_scale: 0.33,
+            distance_scale: 1.,
+            rolloff_scale: 1.,
+        }
+    }
+}
+
+//
+// plugin
+//
+
+/// All systems are executed in this set in [`PostUpdate`]
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct AudioSystem;
+
+/// File extensions of supported audio files, lowercase without leading dot.
+///
+/// _Actually more types are supported, but why would you use anything else?_
+pub const AUDIO_FILE_EXTENSIONS: &[&str] = &["flac", "mp3", "ogg", "wav"];
+
+/// Engine configuration which cannot be changed after initialization, except
+/// by [`ReinitAudioEngine`]
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AudioEngineInitSettings {
+    /// How many sounds may exist at once.
+    ///
+    /// Only active ones will be played, based on priority and calculated
+    /// volume. Max value is `4095`.
+    pub max_virtual_channels: usize,
+
+    /// How many sounds can be played at once.
+    ///
+    /// If there are more sounds than active channels, sounds with lower
+    /// priority will be muted.
+    ///
+    /// Must not be greater than `max_virtual_channels`.
+    pub max_active_channels: usize,
+
+    /// How many streams (see [`AudioSource::stream_file`]) can be opened at
+    /// once.
+    ///
+    /// Each streamed source takes one stream, plus one for each additional
+    /// instance played at the same time.
+    pub max_streams: usize,
+
+    /// Sample rate of the mixer, Hz. Device default is used if `None`.
+    ///
+    /// Matching device's native rate avoids resampling on output. Higher
+    /// rates cost proportionally more CPU for mixing and effects.
+    ///
+    /// Must be in `[8000; 192_000]` range, otherwise warning is logged and
+    /// default is used.
+    pub sample_rate: Option<u32>,
+
+    /// Speaker configuration of the output (i.e. 5.1 or 7.1 surround). Sounds
+    /// are panned and downmixed for it.
+    ///
+    /// Actually used format is available in [`AudioEngineInfo`].
+    ///
+    /// For spatial audio on headphones see [`AudioOutputType::WindowsSonic`].
+    pub speaker_mode: AudioSpeakerMode,
+
+    /// Length of the mixer buffer in samples and number of buffers. FMOD
+    /// defaults (usually 1024 x 4) are used if `None`.
+    ///
+    /// Latency is roughly `length * count / sample_rate` (about 85 ms with
+    /// defaults at 48 kHz). Smaller buffers reduce latency, but the mixer
+    /// wakes up more often, which costs more CPU and may cause crackling if
+    /// it can't keep up; larger buffers fix crackling on some systems.
+    ///
+    /// Length must be in `[64; 8192]` range and count in `[2; 16]`,
+    /// otherwise warning is logged and defaults are used.
+    ///
+    /// Actually used values are available in [`AudioEngineInfo`].
+    pub dsp_buffer: Option<(u32, u32)>,
+
+    /// Which audio output is used. If it's not supported on current
+    /// platform, engine fails to initialize.
+    pub output_type: AudioOutputType,
+
+    /// Output file for [`AudioOutputType::WavWriter`] and
+    /// [`AudioOutputType::WavWriterNrt`]. If empty,
+    /// `fmodoutput.wav` in current directory is used.
+    pub wav_output_path: PathBuf,
+
+    /// Allow FMOD profiler to connect to the application (possibly from
+    /// another machine). Port is logged on initialization.
+    pub profiler: bool,
+
+    /// Network port for [`AudioEngineInitSettings::profiler`]. FMOD default
+    /// (9264) is used if `None`.
+    pub profiler_port: Option<u16>,
+
+    /// Expected max absolute coordinate value of [`AudioGeometry`] vertices.
+    /// Must be positive.
+    ///
+    /// _This isn't a hard limitation, but exceeding it results in worse
+    /// performance and incorrect occlusion. A warning is logged for geometry
+    /// which exceeds it._
+    pub geometry_world_size: f32,
+
+    /// Which messages from FMOD and this plugin are logged
+    #[serde(default)]
+    pub log_level: AudioLogLevel,
+}
+
+impl Default for AudioEngineInitSettings {
+    fn default() -> Self {
+        Self {
+            max_virtual_channels: 1024,
+            max_active_channels: 32,
+            max_streams: 4,
+            sample_rate: None,
+            speaker_mode: default(),
+            dsp_buffer: None,
+            output_type: default(),
+            wav_output_path: default(),
+            profiler: false,
+            profiler_port: None,
+            geometry_world_size: 500.,
+            log_level: default(),
+        }
+    }
+}
+
+impl AudioEngineInitSettings {
+    /// Check that all values are in valid range. Returns the first invalid
+    /// one.
+    ///
+    /// Invalid values are replaced when engine is initialized (and a warning
+    /// is logged for each).
+    pub fn validate(&self) -> Result<(), AudioInitError> {
+        match self.sanitize().1.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns settings with invalid values replaced, and all errors found
+    fn sanitize(&self) -> (Self, Vec<AudioInitError>) {
+        let mut settings = self.clone();
+        let mut errors = vec![];
+
+        if settings.max_virtual_channels > 4095 {
+            settings.max_virtual_channels = 4095;
+            errors.push(AudioInitError::MaxVirtualChannels {
+                value: self.max_virtual_channels,
+                used: settings.max_virtual_channels,
+            });
+        }
+
+        if settings.max_active_channels > settings.max_virtual_channels {
+            settings.max_active_channels = settings.max_virtual_channels;
+            errors.push(AudioInitError::MaxActiveChannels {
+                value: self.max_active_channels,
+                used: settings.max_active_channels,
+            });
+        }
+
+        if let Some(rate) = settings.sample_rate {
+            if !(8000..=192_000).contains(&rate) {
+                settings.sample_rate = None;
+                errors.push(AudioInitError::SampleRate(rate));
+            }
+        }
+
+        if settings.geometry_world_size.is_nan() || settings.geometry_world_size <= 0. {
+            settings.geometry_world_size = Self::default().geometry_world_size;
+            errors.push(AudioInitError::GeometryWorldSize {
+                value: self.geometry_world_size,
+                used: settings.geometry_world_size,
+            });
+        }
+
+        if let Some((length, count)) = settings.dsp_buffer {
+            if !(64..=8192).contains(&length) || !(2..=16).contains(&count) {
+                settings.dsp_buffer = None;
+                errors.push(AudioInitError::DspBuffer { length, count });
+            }
+        }
+
+        (settings, errors)
+    }
+}
+
+/// Invalid [`AudioEngineInitSettings`] or engine initialization failure
+#[derive(Clone, PartialEq, Debug)]
+pub enum AudioInitError {
+    /// [`AudioEngineInitSettings::max_virtual_channels`] is too big, `used`
+    /// is used instead
+    MaxVirtualChannels { value: usize, used: usize },
+
+    /// [`AudioEngineInitSettings::max_active_channels`] is greater than
+    /// `max_virtual_channels`, `used` is used instead
+    MaxActiveChannels { value: usize, used: usize },
+
+    /// [`AudioEngineInitSettings::sample_rate`] is out of range, default is
+    /// used instead
+    SampleRate(u32),
+
+    /// [`AudioEngineInitSettings::dsp_buffer`] is out of range, defaults are
+    /// used instead
+    DspBuffer { length: u32, count: u32 },
+
+    /// [`AudioEngineInitSettings::geometry_world_size`] isn't positive, `used`
+    /// is used instead
+    GeometryWorldSize { value: f32, used: f32 },
+
+    /// FMOD failed to initialize, with this error code (`FMOD_RESULT`)
+    Fmod { code: i32, message: String },
+}
+
+impl fmt::Display for AudioInitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxVirtualChannels { value, used } => write!(
+                f,
+                "max_virtual_channels is {value}, but max is 4095; using {used}"
+            ),
+            Self::MaxActiveChannels { value, used } => write!(
+                f,
+                "max_active_channels is {value}, but it can't be greater than \
+                 max_virtual_channels; using {used}"
+            ),
+            Self::SampleRate(rate) => write!(
+                f,
+                "sample_rate is {rate} Hz, but it must be in [8000; 192000] range; using default"
+            ),
+            Self::DspBuffer { length, count } => write!(
+                f,
+                "dsp_buffer is {length} samples x {count}, but length must be in \
+                 [64; 8192] range and count in [2; 16]; using defaults"
+            ),
+            Self::GeometryWorldSize { value, used } => write!(
+                f,
+                "geometry_world_size is {value}, but it must be positive; using {used}"
+            ),
+            Self::Fmod { code, message } => write!(f, "FMOD error {code}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioInitError {}
+
+/// See [`AudioEngineInitSettings::output_type`]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum AudioOutputType {
+    /// Platform default
+    #[default]
+    Auto,
+
+    /// All sounds are processed, but there is no output (i.e. for dedicated
+    /// servers and tests)
+    NoSound,
+
+    /// Same as `NoSound`, but mixing happens only when engine is updated
+    /// instead of in real time (one DSP buffer per update, see
+    /// [`AudioOutput::advance`]). Streams and generators are decoded during
+    /// updates too, so they keep up with the mixer.
+    NoSoundNrt,
+
+    /// Windows
+    Wasapi,
+
+    /// Linux
+    Alsa,
+
+    /// Linux
+    PulseAudio,
+
+    /// macOS and iOS
+    CoreAudio,
+
+    /// Windows, object-based spatial audio. Provides headphone
+    /// virtualization (binaural audio) if user has enabled it in the system
+    /// settings.
+    ///
+    /// _FMOD Core has no built-in binaural renderer, so there is no such
+    /// option on other platforms._
+    WindowsSonic,
+
+    /// Write final mix to a WAV file (see
+    /// [`AudioEngineInitSettings::wav_output_path`]) instead of playing it.
+    ///
+    /// Sample rate is the same as mixer's (see [`AudioEngineInfo`]).
+    /// Recording can be started and stopped at runtime with
+    /// [`ReinitAudioEngine`].
+    WavWriter,
+
+    /// Same as `WavWriter`, but non-realtime like `NoSoundNrt` (i.e. for
+    /// rendering audio faster than real time).
+    WavWriterNrt,
+}
+
+/// See [`AudioEngineInitSettings::log_level`]. Each level includes all
+/// previous ones.
+///
+/// Errors of the plugin itself are always logged.
+///
+/// _FMOD messages are available only if logging version of the library
+/// (`fmodL`) is used._
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+pub enum AudioLogLevel {
+    /// FMOD errors
+    Error,
+
+    /// FMOD warnings
+    Warning,
+
+    /// Informational messages of the plugin (i.e. engine version and output
+    /// format on initialization)
+    #[default]
+    Info,
+
+    /// All FMOD messages, logged at debug level
+    Verbose,
+}
+
+/// See [`AudioEngineInitSettings::speaker_mode`]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum AudioSpeakerMode {
+    /// Same as output device
+    #[default]
+    Default,
+
+    Mono,
+
+    Stereo,
+
+    /// Front left, front right, surround left, surround right
+    Quad,
+
+    /// 5.1 surround
+    Surround51,
+
+    /// 7.1 surround
+    Surround71,
+
+    /// Specified number of speakers without any panning (sounds must be
+    /// panned manually)
+    Raw(u32),
+}
+
+/// Read-only information about audio engine, updated after initialization.
+///
+/// Values are zeroed if audio is disabled (see [`AudioState`]), except for
+/// `init_error`.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct AudioEngineInfo {
+    /// Sample rate of the mixer, Hz
+    pub sample_rate: u32,
+
+    /// If device uses speaker mode not listed in [`AudioSpeakerMode`],
+    /// it's reported as `Default`.
+    pub speaker_mode: AudioSpeakerMode,
+
+    /// Number of output channels
+    pub speaker_count: u32,
+
+    /// Length of the mixer buffer, samples
+    pub dsp_buffer_length: u32,
+
+    /// Number of mixer buffers
+    pub dsp_buffer_count: u32,
+
+    /// Version of FMOD library actually loaded at runtime (major, minor,
+    /// patch). Logged on initialization, along with an error if it
+    /// differs from `fmod_header_version`.
+    pub fmod_version: (u32, u32, u32),
+
+    /// Version of FMOD headers this crate was compiled with
+    pub fmod_header_version: (u32, u32, u32),
+
+    /// Output type in use. If it's not listed in [`AudioOutputType`], it's
+    /// reported as `Auto`.
+    pub output_type: AudioOutputType,
+
+    /// Name of the output device
+    pub driver_name: String,
+
+    /// See [`AudioEngineInitSettings::geometry_world_size`]
+    pub geometry_world_size: f32,
+
+    /// Why engine failed to initialize, if it did
+    pub init_error: Option<AudioInitError>,
+}
+
+/// Insert this resource to draw debug visualization of spatial audio with
+/// bevy [`Gizmos`] (i.e. for level design).
+#[cfg(feature = "gizmos")]
+#[derive(Resource, Clone, Default, Debug)]
+pub struct AudioDebugGizmos {
+    /// Spheres at min and max distance of each [`AudioReverbSphere`]
+    pub reverb: bool,
+
+    /// Outline of each polygon of each active [`AudioGeometry`]
+    pub geometry: bool,
+
+    /// Spheres at min and max distance around each playing spatial sound
+    pub emitters: bool,
+}
+
+/// Set to true when application is moved to background on mobile platforms,
+/// and back to false when it returns. This suspends the whole mixer, as
+/// required by the OS.
+///
+/// _Bevy doesn't report application lifecycle events yet, so this must be
+/// done from platform-specific code._
+///
+/// Unlike [`AudioSettings::paused`] and
+/// [`FmodAudioPlugin::pause_on_focus_loss`], this doesn't pause sounds
+/// individually, but all of them still stop advancing.
+#[derive(Resource, Clone, Copy, Default, Debug)]
+pub struct AudioSuspended(pub bool);
+
+/// Direct control over the mixer output, i.e. for offline rendering or for
+/// tests which check what was actually played.
+///
+/// Use with non-realtime output type ([`AudioOutputType::NoSoundNrt`] or
+/// [`AudioOutputType::WavWriterNrt`]) to mix audio only when requested.
+/// Note that engine is still updated by [`AudioSystem`] each frame, which
+/// also mixes one DSP buffer.
+///
+/// All functions do nothing if audio is disabled (see [`AudioState`]).
+pub struct AudioOutput;
+
+impl AudioOutput {
+    /// Update engine enough times to mix specified duration of audio
+    /// (rounded up to DSP buffer length, see [`AudioEngineInfo`]).
+    ///
+    /// Has no effect with realtime output types.
+    pub fn advance(duration: Duration) {
+        let mut bridge = BRIDGE.lock().unwrap();
+        let Some(bridge) = bridge.as_mut() else {
+            return;
+        };
+        let milliseconds = duration.as_millis().try_into().unwrap_or(u32::MAX);
+        bridge.pin_mut().advance_mixer(milliseconds);
+    }
+
+    /// Start or stop copying the final mix into memory.
+    ///
+    /// It's captured after all effects on the master group, except those
+    /// which were added after capture was started (i.e. [`AudioLimiter`]).
+    ///
+    /// _Captured samples are kept until taken, so memory usage grows
+    /// indefinitely if they never are._
+    pub fn set_capture(enabled: bool) {
+        let mut bridge = BRIDGE.lock().unwrap();
+        let Some(bridge) = bridge.as_mut() else {
+            return;
+        };
+        bridge.pin_mut().set_output_capture(enabled);
+    }
+
+    /// Returns all samples captured since the last call (see
+    /// [`AudioOutput::set_capture`]) and clears them.
+    pub fn take_captured() -> AudioCapturedOutput {
+        let mut bridge = BRIDGE.lock().unwrap();
+        let Some(bridge) = bridge.as_mut() else {
+            return default();
+        };
+        let output = bridge.pin_mut().take_captured_output();
+        AudioCapturedOutput {
+            sample_rate: output.sample_rate.max(0) as u32,
+            channels: output.channels.max(0) as u32,
+            samples: output.samples.into_iter().collect(),
+        }
+    }
+}
+
+/// See [`AudioOutput::take_captured`]
+#[derive(Clone, Default, Debug)]
+pub struct AudioCapturedOutput {
+    /// Hz
+    pub sample_rate: u32,
+
+    /// Zero if nothing was captured
+    pub channels: u32,
+
+    /// Interleaved (i.e. left-right-left-right for stereo)
+    pub samples: Vec<f32>,
+}
+
+impl AudioCapturedOutput {
+    /// Duration of captured audio
+    pub fn duration(&self) -> Duration {
+        if self.channels == 0 || self.sample_rate == 0 {
+            return Duration::ZERO;
+        }
+        let frames = self.samples.len() as u64 / self.channels as u64;
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+}
+
+/// Whether audio engine was initialized successfully.
+///
+/// If it wasn't, all systems still run but do nothing: sounds are never
+/// started (entities are despawned as if playback failed), and
+/// [`AudioSource`] assets fail to load.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioState {
+    Enabled,
+    Disabled,
+}
+
+/// Send this event to destroy audio engine and initialize it again with new
+/// settings (or to recover from audio device errors).
+///
+/// All [`AudioSource`] assets, [`AudioGeometry`] and [`AudioReverbSphere`]
+/// are loaded into the new engine, so nothing needs to be respawned.
+/// Looped sounds are restarted from the beginning; other sounds are stopped
+/// as if they finished playing (entities are despawned, without advancing
+/// [`AudioQueue`]).
+///
+/// [`AudioState`] and [`AudioEngineInfo`] are updated accordingly.
+///
+/// _Assets are loaded again from memory, so this may cause a hitch. All
+/// [`AudioSource`] and [`AudioBank`] assets are reported as modified._
+#[derive(Event, Clone, Default, Debug)]
+pub struct ReinitAudioEngine {
+    pub settings: AudioEngineInitSettings,
+}
+
+/// Audio engine and all related systems.
+///
+/// Failure to initialize the engine (e.g. if there is no audio device) isn't
+/// fatal, see [`AudioState`].
+#[derive(Default)]
+pub struct FmodAudioPlugin {
+    pub settings: AudioEngineInitSettings,
+
+    /// Pause all sounds while application window isn't focused.
+    ///
+    /// This doesn't change [`AudioSettings::paused`], and sounds stay paused
+    /// if it is set when focus is regained.
+    pub pause_on_focus_loss: bool,
+
+    /// How many times per second engine is updated (including listener
+    /// position). If `None`, it's updated every frame.
+    ///
+    /// _At high frame rates updating every frame is excessive, and at low
+    /// frame rates spatial audio lags._
+    pub update_hz: Option<f32>,
+
+    /// Decode sounds loaded via [`AssetServer`] in background, as with
+    /// [`AudioSource::from_memory_nonblocking`]. Assets are reported as
+    /// loaded before they can be played, see [`AudioSource::state`].
+    pub nonblocking_load: bool,
+
+    /// How sounds loaded via [`AssetServer`] are stored.
+    ///
+    /// Silence trimming and loudness normalization work only with
+    /// [`AudioLoadMode::Memory`].
+    pub load_mode: AudioLoadMode,
+
+    /// Call [`AudioSource::trim_silence`] with this threshold for sounds
+    /// loaded via [`AssetServer`]. Ignored if [`FmodAudioPlugin::nonblocking_load`]
+    /// is set, since sounds aren't decoded yet when they are loaded.
+    pub trim_silence: Option<f32>,
+
+    /// Call [`AudioSource::normalize_loudness`] with this target loudness (in
+    /// LUFS) for sounds loaded via [`AssetServer`]. Ignored if
+    /// [`FmodAudioPlugin::nonblocking_load`] is set.
+    pub normalize_lufs: Option<f32>,
+
+    /// Settings for sounds loaded via [`AssetServer`] from specific files or
+    /// directories, i.e. to stream music. The first one which matches the
+    /// path is used.
+    pub asset_settings: Vec<AudioAssetSettings>,
+}
+
+/// Settings for sounds loaded via [`AssetServer`] from a specific file or
+/// directory, see [`FmodAudioPlugin::asset_settings`]
+// TODO(later): replace with loader settings (they are supported only since
+// bevy 0.12), so they can be specified in .meta files
+#[derive(Clone, Default)]
+pub struct AudioAssetSettings {
+    /// Path relative to the assets directory. Directory matches all files in
+    /// it, including ones in subdirectories.
+    pub path: PathBuf,
+
+    /// Overrides [`FmodAudioPlugin::load_mode`]. With
+    /// [`AudioLoadMode::Stream`] file is still kept in memory, use
+    /// [`AudioSource::stream_asset`] to stream it from disk.
+    pub load_mode: Option<AudioLoadMode>,
+
+    /// Sets [`AudioSource::params`]. Volume is still adjusted by
+    /// [`FmodAudioPlugin::normalize_lufs`].
+    pub params: Option<AudioParameters>,
+
+    /// Sets [`AudioSource::looped`]
+    pub looped: bool,
+
+    /// Sets [`AudioSource::loop_region`]. Overrides the one set by
+    /// [`FmodAudioPlugin::trim_silence`].
+    pub loop_region: Option<(Duration, Duration)>,
+}
+
+impl AudioAssetSettings {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ..default()
+        }
+    }
+
+    pub fn with_load_mode(mut self, mode: AudioLoadMode) -> Self {
+        self.load_mode = Some(mode);
+        self
+    }
+
+    pub fn with_params(mut self, params: AudioParameters) -> Self {
+        self.params = Some(params);
+        self
+    }
+
+    pub fn with_loop(mut self) -> Self {
+        self.looped = true;
+        self
+    }
+
+    pub fn with_loop_region(mut self, start: Duration, end: Duration) -> Self {
+        self.loop_region = Some((start, end));
+        self
+    }
+
+    fn apply(&self, source: &mut AudioSource) {
+        if let Some(params) = self.params {
+            source.params = params;
+        }
+        source.looped |= self.looped;
+        if let Some(region) = self.loop_region {
+            source.loop_region = Some(region);
+        }
+    }
+}
+
+impl Plugin for FmodAudioPlugin {
+    fn build(&self, app: &mut App) {
+        let (bridge, state, info) = init_engine(&self.settings);
+        *BRIDGE.lock().unwrap() = bridge;
+        let loader_errors = LoaderErrors::default();
+
+        app.insert_resource(state)
+            .insert_resource(info)
+            .add_event::<ReinitAudioEngine>()
+            .add_event::<AudioError>()
+            .add_event::<AudioBeat>()
+            .add_event::<AudioPlaylistTrack>()
+            // no audio system may see channels of the old engine
+            .add_systems(
+                PostUpdate,
+                (reinit_engine, apply_deferred).chain().before(AudioSystem),
+            )
+            .add_systems(Last, send_error_events);
+
+        app.configure_set(PostUpdate, AudioSystem)
+            .init_resource::<AudioSettings>()
+            .init_resource::<AudioGroupStats>()
+            .init_resource::<AudioMemoryStats>()
+            .init_resource::<AudioChannelUsage>()
+            .init_resource::<GroupVolumeModifiers>()
+            .init_resource::<RampedGroupVolumes>()
+            .init_resource::<AudioSuspended>()
+            .insert_resource(EngineUpdateTimer {
+                period: self
+                    .update_hz
+                    .map(|hz| Duration::from_secs_f32(1. / hz.max(1.))),
+                since_update: default(),
+                is_due: true,
+            })
+            .add_asset::<AudioSource>()
+            .add_asset_loader(AudioFileLoader {
+                nonblocking: self.nonblocking_load,
+                load_mode: self.load_mode,
+                trim_silence: self.trim_silence,
+                normalize_lufs: self.normalize_lufs,
+                settings: self.asset_settings.clone(),
+                errors: loader_errors.clone(),
+            })
+            .add_asset::<AudioPlaylist>()
+            .add_asset::<AudioBank>()
+            .add_asset_loader(AudioBankLoader(loader_errors.clone()))
+            .add_asset::<AudioGeometry>()
+            .add_asset_loader(AudioGeometryLoader(loader_errors.clone()))
+            .insert_resource(loader_errors);
+
+        #[cfg(feature = "diagnostics")]
+        {
+            use bevy::diagnostic::{Diagnostic, RegisterDiagnostic};
+            app.register_diagnostic(Diagnostic::new(
+                AudioDiagnostics::CHANNELS_PLAYING,
+                "audio/channels_playing",
+                20,
+            ))
+            .register_diagnostic(
+                Diagnostic::new(AudioDiagnostics::CPU_DSP, "audio/cpu_dsp", 20).with_suffix("%"),
+            )
+            .register_diagnostic(
+                Diagnostic::new(AudioDiagnostics::CPU_UPDATE, "audio/cpu_update", 20)
+                    .with_suffix("%"),
+            )
+            .add_systems(
+                PostUpdate,
+                record_diagnostics.after(update_system).in_set(AudioSystem),
+            );
+        }
+
+        // system update
+        app.add_systems(
+            PostUpdate,
+            (
+                tick_engine_update.before(update_listener),
+                update_listener
+                    .after(TransformSystem::TransformPropagate)
+                    .run_if(engine_update_due),
+                update_system
+                    .after(update_listener)
+                    .run_if(engine_update_due),
+                update_group_stats.after(update_system),
+                read_pending_tags.after(update_system),
+                update_memory_stats.after(update_system),
+                update_channel_usage.after(update_system),
+                update_master_lowpass.before(update_system),
+                update_ducking
+                    .before(update_system)
+                    .after(update_engine_settings),
+                update_engine_settings
+                    .before(update_system)
+                    .run_if(resource_changed::<AudioSettings>()),
+                // groups are created by play_audio, which runs before
+                // update_engine_settings
+                update_default_group_volume
+                    .before(play_audio)
+                    .run_if(resource_changed::<AudioSettings>()),
+                ramp_group_volumes
+                    .before(update_system)
+                    .before(update_engine_settings)
+                    .before(update_ducking),
+                suspend_mixer
+                    .before(update_system)
+                    .run_if(resource_changed::<AudioSuspended>()),
+            )
+                .in_set(AudioSystem),
+        );
+
+        if self.pause_on_focus_loss {
+            app.add_event::<bevy::window::WindowFocused>()
+                .init_resource::<FocusLost>()
+                .add_systems(
+                    PostUpdate,
+                    pause_on_focus_loss
+                        .before(update_system)
+                        .in_set(AudioSystem),
+                );
+        }
+
+        // playback
+        app.init_resource::<AudioInstanceMapping>()
+            .init_resource::<AudioGroupRegistry>()
+            .init_resource::<PendingChannelUpdates>()
+            .init_resource::<AttenuationCenter>()
+            .add_systems(
+                PostUpdate,
+                (
+                    update_attenuation_center
+                        .after(TransformSystem::TransformPropagate)
+                        .before(play_audio)
+                        .before(detect_stopped_audio)
+                        .before(update_spatial_audio)
+                        .before(update_channel_lowpass),
+                    (
+                        (pick_random_audio, load_audio_bytes),
+                        resolve_group_names,
+                        apply_deferred,
+                    )
+                        .chain()
+                        .before(play_audio),
+                    play_audio
+                        .before(update_engine_settings)
+                        .after(TransformSystem::TransformPropagate),
+                    stop_audio,
+                    detect_stopped_audio,
+                    restart_reloaded_audio.before(detect_stopped_audio),
+                    restart_audio.before(detect_stopped_audio),
+                    // runs every frame to keep previous transforms, but
+                    // sends positions only when the engine update is due
+                    update_spatial_audio
+                        .after(TransformSystem::TransformPropagate)
+                        .after(tick_engine_update)
+                        .before(update_system),
+                    update_audio_parameters,
+                    // channels are freed and their IDs reused after that
+                    flush_channel_updates
+                        .after(update_spatial_audio)
+                        .after(update_audio_parameters)
+                        .before(stop_audio)
+                        .before(detect_stopped_audio)
+                        .before(restart_reloaded_audio)
+                        .before(restart_audio)
+                        .before(play_audio),
+                    update_audio_group,
+                    update_audio_mute,
+                    update_audio_distortion,
+                    update_audio_chorus,
+                    update_channel_lowpass.after(TransformSystem::TransformPropagate),
+                    update_occlusion_readout
+                        .after(flush_channel_updates)
+                        .after(update_listener),
+                    send_beat_events.after(play_audio),
+                    update_playlists.before(play_audio),
+                )
+                    .in_set(AudioSystem)
+                    .before(update_system),
+            );
+
+        // geometry
+        app.init_resource::<GeometryInstanceMapping>().add_systems(
+            PostUpdate,
+            (
+                // old geometry must be freed before re-added one is created
+                (insert_geometry_assets, apply_deferred, remove_geometry)
+                    .chain()
+                    .before(add_geometry),
+                add_geometry.after(TransformSystem::TransformPropagate),
+                move_geometry
+                    .after(TransformSystem::TransformPropagate)
+                    .after(add_geometry),
+                update_geometry.after(add_geometry),
+            )
+                .in_set(AudioSystem),
+        );
+
+        #[cfg(feature = "physics-integration")]
+        app.add_systems(
+            PostUpdate,
+            (insert_collider_audio, apply_deferred)
+                .chain()
+                .after(TransformSystem::TransformPropagate)
+                .before(add_geometry)
+                .before(add_reverb)
+                .in_set(AudioSystem),
+        );
+
+        // debug
+        #[cfg(feature = "gizmos")]
+        app.add_systems(
+            PostUpdate,
+            draw_debug_gizmos
+                .run_if(resource_exists::<AudioDebugGizmos>())
+                .after(TransformSystem::TransformPropagate)
+                .in_set(AudioSystem),
+        );
+
+        // reverb
+        app.init_resource::<ReverbInstanceMapping>().add_systems(
+            PostUpdate,
+            (
+                add_reverb.after(TransformSystem::TransformPropagate),
+                remove_reverb,
+                blend_reverb
+                    .after(TransformSystem::TransformPropagate)
+                    .before(update_system),
+            )
+                .in_set(AudioSystem),
+        );
+    }
+}
+
+/// Returns new engine (or [`None`] on error) with its state
+fn init_engine(
+    settings: &AudioEngineInitSettings,
+) -> (
+    Option<cxx::UniquePtr<bridge::Bridge>>,
+    AudioState,
+    AudioEngineInfo,
+) {
+    match create_bridge(settings) {
+        Ok(mut bridge) => {
+            let info = engine_info(&mut bridge);
+            (Some(bridge), AudioState::Enabled, info)
+        }
+        Err(error) => {
+            error!("Failed to initialize audio, it will be disabled: {error}");
+            let info = AudioEngineInfo {
+                init_error: Some(error),
+                ..default()
+            };
+            (None, AudioState::Disabled, info)
+        }
+    }
+}
+
+fn create_bridge(
+    settings: &AudioEngineInitSettings,
+) -> Result<cxx::UniquePtr<bridge::Bridge>, AudioInitError> {
+    let (settings, errors) = settings.sanitize();
+    for error in errors {
+        warn!("Invalid audio engine settings: {error}");
+    }
+
+    super::bridge::LOG_INFO.store(
+        settings.log_level >= AudioLogLevel::Info,
+        std::sync::atomic::Ordering::Relaxed,
+    );
+
+    let mut error = bridge::InitError::default();
+    let bridge = bridge::create(
+        bridge::InitParams {
+            max_virtual_channels: settings.max_virtual_channels as i32,
+            max_active_channels: settings.max_active_channels as i32,
+            max_streams: settings.max_streams.min(i32::MAX as usize) as i32,
+            sample_rate: settings.sample_rate.unwrap_or(0).min(i32::MAX as u32) as i32,
+            speaker_mode: match settings.speaker_mode {
+                AudioSpeakerMode::Default => bridge::SpeakerMode::Default,
+                AudioSpeakerMode::Mono => bridge::SpeakerMode::Mono,
+                AudioSpeakerMode::Stereo => bridge::SpeakerMode::Stereo,
+                AudioSpeakerMode::Quad => bridge::SpeakerMode::Quad,
+                AudioSpeakerMode::Surround51 => bridge::SpeakerMode::Surround51,
+                AudioSpeakerMode::Surround71 => bridge::SpeakerMode::Surround71,
+                AudioSpeakerMode::Raw(_) => bridge::SpeakerMode::Raw,
+            },
+            raw_speakers: match settings.speaker_mode {
+                AudioSpeakerMode::Raw(count) => count.min(i32::MAX as u32) as i32,
+                _ => 0,
+            },
+            dsp_buffer_length: settings.dsp_buffer.map(|v| v.0 as i32).unwrap_or(0),
+            dsp_buffer_count: settings.dsp_buffer.map(|v| v.1 as i32).unwrap_or(0),
+            output_type: match settings.output_type {
+                AudioOutputType::Auto => bridge::OutputType::Auto,
+                AudioOutputType::NoSound => bridge::OutputType::NoSound,
+                AudioOutputType::NoSoundNrt => bridge::OutputType::NoSoundNrt,
+                AudioOutputType::Wasapi => bridge::OutputType::Wasapi,
+                AudioOutputType::Alsa => bridge::OutputType::Alsa,
+                AudioOutputType::PulseAudio => bridge::OutputType::PulseAudio,
+                AudioOutputType::CoreAudio => bridge::OutputType::CoreAudio,
+                AudioOutputType::WindowsSonic => bridge::OutputType::WindowsSonic,
+                AudioOutputType::WavWriter => bridge::OutputType::WavWriter,
+                AudioOutputType::WavWriterNrt => bridge::OutputType::WavWriterNrt,
+            },
+            wav_output_path: settings.wav_output_path.to_string_lossy().into_owned(),
+            profiler: settings.profiler,
+            profiler_port: settings.profiler_port.unwrap_or(0) as i32,
+            geometry_world_size: settings.geometry_world_size,
+            log_level: match settings.log_level {
+                AudioLogLevel::Error => bridge::LogLevel::Error,
+                AudioLogLevel::Warning | AudioLogLevel::Info => bridge::LogLevel::Warning,
+                AudioLogLevel::Verbose => bridge::LogLevel::Verbose,
+            },
+        },
+        &mut error,
+    );
+    if bridge.is_null() {
+        return Err(AudioInitError::Fmod {
+            code: error.code,
+            message: error.message,
+        });
+    }
+    Ok(bridge)
+}
+
+fn engine_info(bridge: &mut cxx::UniquePtr<bridge::Bridge>) -> AudioEngineInfo {
+    let info = bridge.pin_mut().get_engine_info();
+    AudioEngineInfo {
+        sample_rate: info.sample_rate.max(0) as u32,
+        speaker_mode: match info.speaker_mode {
+            bridge::SpeakerMode::Mono => AudioSpeakerMode::Mono,
+            bridge::SpeakerMode::Stereo => AudioSpeakerMode::Stereo,
+            bridge::SpeakerMode::Quad => AudioSpeakerMode::Quad,
+            bridge::SpeakerMode::Surround51 => AudioSpeakerMode::Surround51,
+            bridge::SpeakerMode::Surround71 => AudioSpeakerMode::Surround71,
+            bridge::SpeakerMode::Raw => AudioSpeakerMode::Raw(info.speaker_count.max(0) as u32),
+            _ => AudioSpeakerMode::Default,
+        },
+        speaker_count: info.speaker_count.max(0) as u32,
+        dsp_buffer_length: info.dsp_buffer_length.max(0) as u32,
+        dsp_buffer_count: info.dsp_buffer_count.max(0) as u32,
+        fmod_version: fmod_version(info.version),
+        fmod_header_version: fmod_version(info.header_version),
+        output_type: match info.output_type {
+            bridge::OutputType::NoSound => AudioOutputType::NoSound,
+            bridge::OutputType::NoSoundNrt => AudioOutputType::NoSoundNrt,
+            bridge::OutputType::Wasapi => AudioOutputType::Wasapi,
+            bridge::OutputType::Alsa => AudioOutputType::Alsa,
+            bridge::OutputType::PulseAudio => AudioOutputType::PulseAudio,
+            bridge::OutputType::CoreAudio => AudioOutputType::CoreAudio,
+            bridge::OutputType::WindowsSonic => AudioOutputType::WindowsSonic,
+            bridge::OutputType::WavWriter => AudioOutputType::WavWriter,
+            bridge::OutputType::WavWriterNrt => AudioOutputType::WavWriterNrt,
+            _ => AudioOutputType::Auto,
+        },
+        driver_name: info.driver_name,
+        geometry_world_size: info.geometry_world_size,
+        init_error: None,
+    }
+}
+
+/// Unpacks FMOD version format (0xaaaabbcc)
+fn fmod_version(version: u32) -> (u32, u32, u32) {
+    (version >> 16, (version >> 8) & 0xff, version & 0xff)
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn reinit_engine(
+    mut events: EventReader<ReinitAudioEngine>,
+    mut state: ResMut<AudioState>,
+    mut info: ResMut<AudioEngineInfo>,
+    mut settings: ResMut<AudioSettings>,
+    mut suspended: ResMut<AudioSuspended>,
+    mut sources: ResMut<Assets<AudioSource>>,
+    mut banks: ResMut<Assets<AudioBank>>,
+    instances: Query<(
+        Entity,
+        &AudioInstance,
+        Option<&AudioLoop>,
+        Option<&AudioForceOneShot>,
+    )>,
+    geometries: Query<(Entity, &AudioGeometry, &GlobalTransform)>,
+    reverbs: Query<(Entity, &AudioReverbSphere, &GlobalTransform)>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut audio_mapping: ResMut<AudioInstanceMapping>,
+    (mut geometry_mapping, mut reverb_mapping): (
+        ResMut<GeometryInstanceMapping>,
+        ResMut<ReverbInstanceMapping>,
+    ),
+    asset_server: Res<AssetServer>,
+    mut errors: EventWriter<AudioError>,
+) {
+    let Some(event) = events.iter().last() else {
+        return;
+    };
+    info!("Re-initializing audio engine");
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    // only one engine instance can exist at once
+    *bridge = None;
+    (*bridge, *state, *info) = init_engine(&event.settings);
+
+    // all engine objects are gone
+    audio_mapping.ids.clear();
+    geometry_mapping.0.clear();
+    reverb_mapping.0.clear();
+
+    for (entity, instance, looped, one_shot) in instances.iter() {
+        let mut commands = commands.entity(entity);
+        let looped = one_shot.is_none()
+            && (looped.is_some() || sources.get(&instance._source).is_some_and(|s| s.looped));
+        if looped {
+            commands.remove::<AudioInstance>().insert(AudioPendingLoad {
+                since: time.elapsed(),
+                resume_at: None,
+            });
+            audio_mapping.pending.insert(entity);
+        } else {
+            despawn_finished(commands.commands(), entity, &settings);
+            audio_mapping.just_removed.insert(entity);
+        }
+    }
+
+    // force all settings to be applied again
+    settings.set_changed();
+    suspended.set_changed();
+
+    let Some(bridge) = bridge.as_mut() else {
+        for (_, source) in sources.iter_mut() {
+            source.id = -1;
+        }
+        for (_, bank) in banks.iter_mut() {
+            bank.id = -1;
+        }
+        for (_, id) in DSP_PLUGINS.lock().unwrap().iter_mut() {
+            *id = -1;
+        }
+        return;
+    };
+
+    for (id, bank) in banks.iter_mut() {
+        let first_error = bridge.error_count();
+        bank.load(bridge.pin_mut());
+        if bank.id == -1 {
+            let context = asset_context(&asset_server, id);
+            errors.send(AudioError::engine(context, bridge.pin_mut(), first_error));
+        }
+    }
+
+    let mut reload = vec![];
+    for (id, source) in sources.iter_mut() {
+        let first_error = bridge.error_count();
+        source.id = match &source.data {
+            AudioSourceData::BankEntry(bank, index) => banks
+                .get(bank)
+                .map_or(-1, |bank| bank.load_entry(bridge.pin_mut(), *index)),
+            AudioSourceData::AssetFile(_) => {
+                reload.push(id);
+                continue;
+            }
+            data => data.load(bridge.pin_mut(), source.nonblocking),
+        };
+        if source.id == -1 {
+            let context = asset_context(&asset_server, id);
+            errors.send(AudioError::engine(context, bridge.pin_mut(), first_error));
+        }
+    }
+    for id in reload {
+        match asset_server.get_handle_path(id) {
+            Some(path) => asset_server.reload_asset(path),
+            None => warn!("AudioSource {id:?} has no asset path and can't be reloaded"),
+        }
+    }
+
+    for (filename, id) in DSP_PLUGINS.lock().unwrap().iter_mut() {
+        *id = bridge.pin_mut().load_plugin(&filename.to_string_lossy());
+    }
+
+    for (entity, geometry, transform) in geometries.iter() {
+        let first_error = bridge.error_count();
+        match bridge
+            .pin_mut()
+            .add_geometry(geometry_params(geometry, transform))
+        {
+            -1 => errors.send(AudioError::engine(
+                AudioErrorContext::Entity(entity),
+                bridge.pin_mut(),
+                first_error,
+            )),
+            instance => {
+                geometry_mapping.0.insert(entity, instance);
+            }
+        }
+    }
+
+    // blended reverb is updated every frame
+    if settings.reverb_blending.is_none() {
+        for (entity, reverb, transform) in reverbs.iter() {
+            let first_error = bridge.error_count();
+            match bridge
+                .pin_mut()
+                .add_reverb(reverb_params(reverb, transform))
+            {
+                -1 => errors.send(AudioError::engine(
+                    AudioErrorContext::Entity(entity),
+                    bridge.pin_mut(),
+                    first_error,
+                )),
+                instance => {
+                    reverb_mapping.0.insert(entity, instance);
+                }
+            }
+        }
+    }
+}
+
+/// For errors related to the asset, if it's loaded from a file
+fn asset_context(
+    asset_server: &AssetServer,
+    id: impl Into<bevy::asset::HandleId>,
+) -> AudioErrorContext {
+    match asset_server.get_handle_path(id) {
+        Some(path) => AudioErrorContext::Asset(path.path().to_path_buf()),
+        None => AudioErrorContext::Engine,
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Engine instance (C++ wrapper).
+    ///
+    /// Each system locks it once and keeps it locked while running. Audio
+    /// systems may run in parallel and wait for each other here, so systems
+    /// which make many small updates should queue them instead (see
+    /// [`PendingChannelUpdates`]).
+    static ref BRIDGE: Mutex<Option<cxx::UniquePtr<bridge::Bridge>>> = default();
+}
+
+/// IDs used for sounds, channels and spatial objects
+type EngineId = i32;
+
+//
+// assets
+
+// TODO(later): select silence trimming and loudness normalization per file
+struct AudioFileLoader {
+    nonblocking: bool,
+    load_mode: AudioLoadMode,
+    trim_silence: Option<f32>,
+    normalize_lufs: Option<f32>,
+    settings: Vec<AudioAssetSettings>,
+    errors: LoaderErrors,
+}
+
+impl AudioFileLoader {
+    fn settings(&self, path: &Path) -> Option<&AudioAssetSettings> {
+        self.settings
+            .iter()
+            .find(|settings| path.starts_with(&settings.path))
+    }
+}
+
+/// Load failures, shared by asset loaders with `send_error_events`
+#[derive(Resource, Clone, Default)]
+struct LoaderErrors(Arc<Mutex<Vec<AudioError>>>);
+
+impl LoaderErrors {
+    /// Record the error and pass it through
+    fn report(&self, error: AudioLoadError, path: &Path) -> AudioLoadError {
+        let context = AudioErrorContext::Asset(path.to_path_buf());
+        let event = AudioError::from_load_error(context, error.clone());
+        self.0.lock().unwrap().push(event);
+        error.with_path(path)
+    }
+}
+
+impl bevy::asset::AssetLoader for AudioFileLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::asset::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let settings = self.settings(load_context.path());
+            let load_mode = settings
+                .and_then(|settings| settings.load_mode)
+                .unwrap_or(self.load_mode);
+
+            let mut asset = AudioSource::from_asset_file(bytes, load_mode, self.nonblocking)
+                .map_err(|error| self.errors.report(error, load_context.path()))?;
+            if let Some(settings) = settings {
+                settings.apply(&mut asset);
+            }
+            if !self.nonblocking {
+                if let Some(threshold) = self.trim_silence {
+                    asset.trim_silence(threshold);
+                }
+                if let Some(target) = self.normalize_lufs {
+                    asset.normalize_loudness(target);
+                }
+            }
+            load_context.set_default_asset(bevy::asset::LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        AUDIO_FILE_EXTENSIONS
+    }
+}
+
+struct AudioBankLoader(LoaderErrors);
+
+struct AudioGeometryLoader(LoaderErrors);
+
+impl bevy::asset::AssetLoader for AudioGeometryLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::asset::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset = AudioGeometry::from_bytes(bytes)
+                .map_err(|error| self.0.report(error, load_context.path()))?;
+            load_context.set_default_asset(bevy::asset::LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["fmodgeo"]
+    }
+}
+
+impl bevy::asset::AssetLoader for AudioBankLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::asset::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset = AudioBank::from_memory(bytes)
+                .map_err(|error| self.0.report(error, load_context.path()))?;
+            load_context.set_default_asset(bevy::asset::LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["fsb"]
+    }
+}
+
+//
+// system update
+
+/// Max number of [`AudioListener`] entities (`FMOD_MAX_LISTENERS`)
+const MAX_LISTENERS: usize = 8;
+
+struct ListenerData {
+    data: bridge::ListenerParams,
+    old_position: Option<Vec3>,
+}
+
+impl Default for ListenerData {
+    fn default() -> Self {
+        Self {
+            data: bridge::ListenerParams {
+                forward: Vec3::NEG_Z.into(),
+                up: Vec3::Y.into(),
+                ..default()
+            },
+            old_position: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Listeners {
+    entities: HashMap<Entity, ListenerData>,
+    /// Used if there are no listeners
+    last: ListenerData,
+    /// [`Time::elapsed`] when listeners were updated last time
+    last_update: Duration,
+    /// Warning about too many listeners was logged
+    warned: bool,
+}
+
+/// See [`FmodAudioPlugin::update_hz`]
+#[derive(Resource)]
+struct EngineUpdateTimer {
+    /// Update every frame if `None`
+    period: Option<Duration>,
+    /// Time since last update
+    since_update: Duration,
+    is_due: bool,
+}
+
+fn tick_engine_update(mut timer: ResMut<EngineUpdateTimer>, time: Res<Time>) {
+    timer.since_update += time.delta();
+    timer.is_due = match timer.period {
+        Some(period) => timer.since_update >= period,
+        None => true,
+    };
+}
+
+fn engine_update_due(timer: Res<EngineUpdateTimer>) -> bool {
+    timer.is_due
+}
+
+fn update_listener(
+    listener_entities: Query<(Entity, &GlobalTransform), With<AudioListener>>,
+    mut listeners: Local<Listeners>,
+    time: Res<Time>,
+) {
+    // listener may be updated less often than every frame
+    let delta = time.elapsed() - listeners.last_update;
+    listeners.last_update = time.elapsed();
+
+    // sorted for deterministic order. Indices of listeners shift when one is
+    // removed, but attributes of all of them are sent every time.
+    let mut entities: Vec<_> = listener_entities.iter().collect();
+    entities.sort_unstable_by_key(|(entity, _)| *entity);
+    if entities.len() > MAX_LISTENERS {
+        if !listeners.warned {
+            warn!("Only {MAX_LISTENERS} audio listeners are supported, others are ignored");
+            listeners.warned = true;
+        }
+        entities.truncate(MAX_LISTENERS);
+    }
+
+    listeners
+        .entities
+        .retain(|entity, _| entities.iter().any(|(e, _)| e == entity));
+
+    let mut params = Vec::with_capacity(entities.len());
+    for (entity, transform) in entities {
+        let listener = listeners.entities.entry(entity).or_default();
+
+        let position = transform.translation();
+        let velocity = if delta != default() {
+            (position - listener.old_position.unwrap_or(position)) / delta.as_secs_f32()
+        } else {
+            Vec3::ZERO
+        };
+        listener.old_position = position.into();
+
+        let listener = &mut listener.data;
+        listener.position = position.into();
+        listener.velocity = velocity.into();
+        listener.forward = transform.forward().into();
+        listener.up = transform.up().into();
+        params.push(listener.clone());
+    }
+
+    if let Some(first) = params.first() {
+        listeners.last.data = first.clone();
+    } else {
+        listeners.last.data.velocity = default();
+        params.push(listeners.last.data.clone());
+    }
+
+    if let Some(bridge) = BRIDGE.lock().unwrap().as_mut() {
+        bridge.pin_mut().set_listener_count(params.len() as i32);
+        for (index, params) in params.into_iter().enumerate() {
+            bridge.pin_mut().update_listener(index as i32, params);
+        }
+    }
+}
+
+/// Position of the nearest [`AudioListener`], [`None`] if there are none
+fn nearest_listener(
+    listeners: &Query<&GlobalTransform, With<AudioListener>>,
+    position: Vec3,
+) -> Option<Vec3> {
+    listeners
+        .iter()
+        .map(|transform| transform.translation())
+        .min_by(|a, b| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+}
+
+fn update_system(mut timer: ResMut<EngineUpdateTimer>) {
+    if let Some(bridge) = BRIDGE.lock().unwrap().as_mut() {
+        bridge.pin_mut().update();
+    }
+
+    timer.since_update = match timer.period {
+        // keep the phase, but don't try to catch up after long frames
+        Some(period) => timer.since_update.saturating_sub(period).min(period),
+        None => Duration::ZERO,
+    };
+}
+
+fn update_group_stats(mut stats: ResMut<AudioGroupStats>) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    stats.groups = bridge
+        .pin_mut()
+        .get_group_stats()
+        .into_iter()
+        .map(|group| {
+            (
+                AudioGroup(group.user_id),
+                GroupStats {
+                    playing: group.playing as usize,
+                    virtualized: group.virtualized as usize,
+                    audibility: group.audibility,
+                },
+            )
+        })
+        .collect();
+}
+
+/// IDs of diagnostics registered by the plugin (only with `diagnostics`
+/// feature enabled).
+///
+/// Values are recorded every frame and can be displayed with
+/// `LogDiagnosticsPlugin`.
+#[cfg(feature = "diagnostics")]
+pub struct AudioDiagnostics;
+
+#[cfg(feature = "diagnostics")]
+impl AudioDiagnostics {
+    /// Number of non-virtual playing sounds
+    pub const CHANNELS_PLAYING: bevy::diagnostic::DiagnosticId =
+        bevy::diagnostic::DiagnosticId::from_u128(57992411291366061192908928521715114897);
+
+    /// CPU usage of the mixer, percent
+    pub const CPU_DSP: bevy::diagnostic::DiagnosticId =
+        bevy::diagnostic::DiagnosticId::from_u128(75612969382558774665389351955476899431);
+
+    /// CPU usage of the engine update, percent
+    pub const CPU_UPDATE: bevy::diagnostic::DiagnosticId =
+        bevy::diagnostic::DiagnosticId::from_u128(127105257506438708360398389053179764489);
+}
+
+#[cfg(feature = "diagnostics")]
+fn record_diagnostics(mut diagnostics: bevy::diagnostic::Diagnostics) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    let stats = bridge.pin_mut().get_stats();
+    diagnostics.add_measurement(AudioDiagnostics::CHANNELS_PLAYING, || {
+        stats.real_channels_playing as f64
+    });
+    diagnostics.add_measurement(AudioDiagnostics::CPU_DSP, || stats.cpu_dsp as f64);
+    diagnostics.add_measurement(AudioDiagnostics::CPU_UPDATE, || stats.cpu_update as f64);
+}
+
+/// Multipliers for group volume, applied in addition to settings
+#[derive(Resource, Default)]
+struct GroupVolumeModifiers(HashMap<AudioGroup, f32>);
+
+/// Current values of [`AudioSettings::effective_volume`] while
+/// [`AudioSettings::volume_ramp`] is enabled
+#[derive(Resource, Default)]
+struct RampedGroupVolumes(HashMap<AudioGroup, f32>);
+
+fn global_speed(settings: &AudioSettings) -> f32 {
+    settings.global_speed.max(f32::EPSILON)
+}
+
+fn group_params(
+    settings: &AudioSettings,
+    modifiers: &GroupVolumeModifiers,
+    ramped: &RampedGroupVolumes,
+    group: AudioGroup,
+) -> bridge::GroupParams {
+    let params = settings.groups.get(&group).cloned().unwrap_or_default();
+    let modifier = modifiers.0.get(&group).copied().unwrap_or(1.);
+    let volume = ramped
+        .0
+        .get(&group)
+        .copied()
+        .unwrap_or_else(|| settings.effective_volume(group));
+
+    bridge::GroupParams {
+        user_id: group.0,
+        volume: volume * modifier,
+        // global speed is applied to the master group, so groups which ignore
+        // it must compensate
+        pitch: if params.ignore_global_speed {
+            1. / global_speed(settings)
+        } else {
+            1.
+        },
+    }
+}
+
+/// Sets volume of groups which are created when sound is played in them
+fn update_default_group_volume(settings: Res<AudioSettings>) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+    bridge
+        .pin_mut()
+        .set_default_group_volume(settings.effective_master_volume());
+}
+
+/// Configured groups, groups with volume modifiers and groups which exist
+/// in the engine
+fn updated_groups(
+    settings: &AudioSettings,
+    modifiers: &GroupVolumeModifiers,
+    stats: &AudioGroupStats,
+) -> HashSet<AudioGroup> {
+    settings
+        .groups
+        .keys()
+        .chain(modifiers.0.keys())
+        .chain(stats.groups.keys())
+        .copied()
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_engine_settings(
+    settings: Res<AudioSettings>,
+    focus_lost: Option<Res<FocusLost>>,
+    modifiers: Res<GroupVolumeModifiers>,
+    ramped: Res<RampedGroupVolumes>,
+    stats: Res<AudioGroupStats>,
+    mut old_ignore_geometry: Local<HashMap<AudioGroup, bool>>,
+    mut had_ambient_reverb: Local<bool>,
+    mut had_effects: Local<HashSet<AudioGroup>>,
+    mut had_builtin_effects: Local<HashSet<AudioGroup>>,
+) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for (group, params) in settings.groups.iter() {
+        let old = old_ignore_geometry.insert(*group, params.ignore_geometry);
+        let group_stats = stats.get(*group);
+        let is_playing = group_stats.playing + group_stats.virtualized != 0;
+        if old.unwrap_or_default() != params.ignore_geometry && is_playing {
+            warn!("AudioGroupParameters::ignore_geometry changed for {group:?}, but it won't affect sounds which are already playing");
+        }
+    }
+
+    bridge
+        .pin_mut()
+        .set_master_pitch(global_speed(&settings) * settings.master_pitch.max(f32::EPSILON));
+    bridge
+        .pin_mut()
+        .set_master_paused(settings.paused || focus_lost.map(|f| f.0).unwrap_or_default());
+
+    // groups which exist in the engine are updated even if they aren't
+    // configured, since master volume applies to them too
+    for group in updated_groups(&settings, &modifiers, &stats) {
+        bridge
+            .pin_mut()
+            .update_group(group_params(&settings, &modifiers, &ramped, group));
+    }
+
+    let plugins = DSP_PLUGINS.lock().unwrap();
+    let mut has_effects = HashSet::new();
+    for (group, params) in settings.groups.iter() {
+        if params.effects.is_empty() {
+            continue;
+        }
+        let effects: Vec<_> = params
+            .effects
+            .iter()
+            .filter_map(|effect| match effect {
+                AudioEffect::Plugin { plugin, params } => {
+                    let plugin_id = plugins[plugin.0].1;
+                    (plugin_id != -1).then(|| bridge::GroupEffect {
+                        plugin_id,
+                        params: params.clone(),
+                    })
+                }
+            })
+            .collect();
+        bridge.pin_mut().set_group_effects(group.0, &effects);
+        has_effects.insert(*group);
+    }
+    for group in had_effects.difference(&has_effects) {
+        bridge.pin_mut().set_group_effects(group.0, &[]);
+    }
+    *had_effects = has_effects;
+
+    // effects which are None are removed
+    let mut has_builtin_effects = HashSet::new();
+    for (group, params) in settings.groups.iter() {
+        let has_any = params.eq.is_some() || params.distortion.is_some();
+        if !has_any && !had_builtin_effects.contains(group) {
+            continue;
+        }
+
+        let bands = params.eq.map(|eq| {
+            eq.map(|band| bridge::EqBand {
+                frequency: band.frequency,
+                gain: band.gain_db,
+                q: band.q,
+            })
+        });
+        bridge
+            .pin_mut()
+            .set_group_eq(group.0, bands.as_ref().map_or(&[], |bands| bands));
+        bridge
+            .pin_mut()
+            .set_group_distortion(group.0, params.distortion.unwrap_or(0.).clamp(0., 1.));
+
+        if has_any {
+            has_builtin_effects.insert(*group);
+        }
+    }
+    for group in had_builtin_effects.difference(&has_builtin_effects) {
+        if !settings.groups.contains_key(group) {
+            bridge.pin_mut().set_group_eq(group.0, &[]);
+            bridge.pin_mut().set_group_distortion(group.0, 0.);
+        }
+    }
+    *had_builtin_effects = has_builtin_effects;
+
+    let limiter = settings.master_limiter.unwrap_or_default();
+    bridge.pin_mut().set_master_limiter(bridge::LimiterParams {
+        enabled: settings.master_limiter.is_some(),
+        ceiling: limiter.ceiling_db.clamp(-12., 0.),
+        release_time: limiter.release_ms.clamp(1., 1000.),
+    });
+
+    // don't create reverb instance if it's never used
+    if settings.ambient_reverb.is_some() || *had_ambient_reverb {
+        let props = settings.ambient_reverb.clone().unwrap_or_default();
+        bridge.pin_mut().set_global_reverb(
+            AMBIENT_REVERB_INSTANCE,
+            settings.ambient_reverb.is_some(),
+            (&props).into(),
+        );
+        *had_ambient_reverb = settings.ambient_reverb.is_some();
+    }
+
+    let engine = &settings.engine;
+    bridge.pin_mut().update_engine(bridge::EngineParams {
+        doppler_scale: engine.doppler_scale,
+        distance_scale: engine.distance_scale,
+        rolloff_scale: engine.rolloff_scale,
+    });
+}
+
+/// Window isn't focused, see [`FmodAudioPlugin::pause_on_focus_loss`]
+#[derive(Resource, Default)]
+struct FocusLost(bool);
+
+fn pause_on_focus_loss(
+    mut events: EventReader<bevy::window::WindowFocused>,
+    mut focus_lost: ResMut<FocusLost>,
+    settings: Res<AudioSettings>,
+) {
+    // if focus moved to another window of the application, last event is
+    // about gaining focus
+    let Some(event) = events.iter().last() else {
+        return;
+    };
+    if focus_lost.0 != event.focused {
+        return;
+    }
+    focus_lost.0 = !event.focused;
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+    bridge
+        .pin_mut()
+        .set_master_paused(settings.paused || focus_lost.0);
+}
+
+fn suspend_mixer(suspended: Res<AudioSuspended>) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+    bridge.pin_mut().set_mixer_suspended(suspended.0);
+}
+
+/// Identifies ducking rule in `update_ducking`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum DuckingKey {
+    /// Index in [`AudioSettings::ducking`]
+    Settings(usize),
+    /// [`AudioGroupParameters::duck_when`]
+    Group(AudioGroup),
+}
+
+fn update_ducking(
+    settings: Res<AudioSettings>,
+    stats: Res<AudioGroupStats>,
+    time: Res<Time>,
+    mut modifiers: ResMut<GroupVolumeModifiers>,
+    ramped: Res<RampedGroupVolumes>,
+    mut envelopes: Local<HashMap<DuckingKey, f32>>,
+) {
+    let duckings = settings
+        .ducking
+        .iter()
+        .enumerate()
+        .map(|(index, ducking)| (DuckingKey::Settings(index), ducking, None))
+        .chain(settings.groups.iter().filter_map(|(group, params)| {
+            let ducking = params.duck_when.as_ref()?;
+            Some((DuckingKey::Group(*group), ducking, Some(*group)))
+        }));
+
+    let mut new_modifiers: HashMap<AudioGroup, f32> = default();
+    let mut new_envelopes: HashMap<DuckingKey, f32> = default();
+    for (key, ducking, group) in duckings {
+        let is_active = stats.get(ducking.trigger).playing != 0;
+        let (target, duration) = if is_active {
+            (1., ducking.attack)
+        } else {
+            (0., ducking.release)
+        };
+
+        // 0 - not ducked at all, 1 - fully ducked
+        let envelope = new_envelopes
+            .entry(key)
+            .or_insert(envelopes.get(&key).copied().unwrap_or_default());
+        let step = if duration.is_zero() {
+            1.
+        } else {
+            time.delta_seconds() / duration.as_secs_f32()
+        };
+        *envelope += (target - *envelope).clamp(-step, step);
+
+        let volume = 10_f32.powf(-ducking.amount_db.max(0.) * *envelope / 20.);
+        for group in ducking.targets.iter().chain(group.as_ref()) {
+            *new_modifiers.entry(*group).or_insert(1.) *= volume;
+        }
+    }
+    *envelopes = new_envelopes;
+
+    // update only groups which are changed
+    let mut changed: HashSet<AudioGroup> = default();
+    for (group, volume) in new_modifiers.iter() {
+        if modifiers.0.get(group) != Some(volume) {
+            changed.insert(*group);
+        }
+    }
+    for group in modifiers.0.keys() {
+        if !new_modifiers.contains_key(group) {
+            changed.insert(*group);
+        }
+    }
+    if changed.is_empty() {
+        return;
+    }
+    modifiers.0 = new_modifiers;
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+    for group in changed {
+        bridge
+            .pin_mut()
+            .update_group(group_params(&settings, &modifiers, &ramped, group));
+    }
+}
+
+/// See [`AudioSettings::volume_ramp`]
+fn ramp_group_volumes(
+    settings: Res<AudioSettings>,
+    modifiers: Res<GroupVolumeModifiers>,
+    stats: Res<AudioGroupStats>,
+    time: Res<Time>,
+    mut ramped: ResMut<RampedGroupVolumes>,
+) {
+    if settings.volume_ramp.is_zero() {
+        // update_engine_settings applies actual values
+        ramped.0.clear();
+        return;
+    }
+
+    let step = time.delta_seconds() / settings.volume_ramp.as_secs_f32();
+    let groups = updated_groups(&settings, &modifiers, &stats);
+
+    let mut changed = vec![];
+    ramped.0.retain(|group, _| groups.contains(group));
+    for group in groups {
+        let target = settings.effective_volume(group);
+        let Some(volume) = ramped.0.get_mut(&group) else {
+            ramped.0.insert(group, target); // new groups aren't ramped
+            continue;
+        };
+        if *volume != target {
+            *volume += (target - *volume).clamp(-step, step);
+            changed.push(group);
+        }
+    }
+    if changed.is_empty() {
+        return;
+    }
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+    for group in changed {
+        bridge
+            .pin_mut()
+            .update_group(group_params(&settings, &modifiers, &ramped, group));
+    }
+}
+
+fn update_master_lowpass(
+    settings: Res<AudioSettings>,
+    time: Res<Time>,
+    mut current: Local<Option<f32>>,
+) {
+    // range of cutoff frequencies, Hz
+    const MIN: f32 = 10.;
+    const MAX: f32 = 22_000.;
+
+    if current.is_none() && settings.master_lowpass.is_none() {
+        return;
+    }
+
+    // ramp is linear for logarithm of frequency
+    let target = settings
+        .master_lowpass
+        .unwrap_or(MAX)
+        .clamp(MIN, MAX)
+        .log2();
+    let old = current.unwrap_or(MAX).log2();
+    let step = if settings.master_lowpass_ramp.is_zero() {
+        f32::INFINITY
+    } else {
+        (MAX.log2() - MIN.log2()) * time.delta_seconds()
+            / settings.master_lowpass_ramp.as_secs_f32()
+    };
+    let new = old + (target - old).clamp(-step, step);
+
+    if new == old && current.is_some() && !settings.is_changed() {
+        return;
+    }
+
+    let disable = settings.master_lowpass.is_none() && new >= MAX.log2();
+    *current = (!disable).then_some(new.exp2());
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+    bridge.pin_mut().set_master_lowpass(current.unwrap_or(0.));
+}
+
+//
+// playback
+
+#[derive(Resource, Default)]
+struct AudioInstanceMapping {
+    ids: HashMap<Entity, EngineId>,
+    just_removed: HashSet<Entity>,
+    /// Entities with [`AudioPendingLoad`]
+    pending: HashSet<Entity>,
+}
+
+/// Sound asset isn't loaded yet, playback will start when it is
+#[derive(Component)]
+struct AudioPendingLoad {
+    /// [`Time::elapsed`] when playback was requested
+    since: Duration,
+
+    /// Position from which to continue playback of hot-reloaded asset
+    resume_at: Option<Duration>,
+}
+
+/// Sound currently being played.
+///
+/// Added by the plugin to entities with [`AudioSource`] handle when playback starts
+/// and removed when it stops. Can be queried (read-only) to get the engine channel ID.
+#[derive(Component)]
+pub struct AudioInstance {
+    id: EngineId,
+
+    /// [`AudioSource`] engine ID, changes if asset is reloaded
+    file_id: EngineId,
+
+    /// For spatial: position sent to the engine last time
+    old_position: Vec3,
+
+    /// For spatial: [`Time::elapsed`] at which `old_position` was sampled
+    old_position_at: Duration,
+
+    /// For spatial: transform position in the previous frame
+    frame_position: Vec3,
+
+    /// For spatial: [`Time::elapsed`] of the previous frame
+    frame_position_at: Duration,
+
+    /// Ensure handle always outlives the sound
+    _source: Handle<AudioSource>,
+}
+
+impl AudioInstance {
+    /// ID of the channel in the audio engine, i.e. to tell apart playbacks
+    /// of the same entity or to match log messages of the engine.
+    ///
+    /// Stays the same while the sound is playing; a new ID is assigned if
+    /// playback is restarted. IDs of stopped sounds are reused.
+    pub fn engine_id(&self) -> i32 {
+        self.id
+    }
+}
+
+/// How to start playback, see [`PlaybackQueryItem::play`]
+#[derive(Default)]
+struct PlaybackStart {
+    /// [`Time::elapsed`]
+    now: Duration,
+
+    /// From `get_sync_clock`, or 0 to start as soon as possible
+    clock: u64,
+
+    /// Overrides [`AudioStartOffset`]. If sound isn't looped and it's beyond
+    /// the end, sound isn't played.
+    resume_at: Option<Duration>,
+}
+
+/// Components which affect how playback is started
+#[derive(WorldQuery)]
+struct PlaybackQuery {
+    transform: Option<&'static GlobalTransform>,
+    looped: Option<&'static AudioLoop>,
+    one_shot: Option<&'static AudioForceOneShot>,
+    mute: Option<&'static AudioMute>,
+    parameters: Option<&'static AudioParameters>,
+    startup_delay: Option<&'static AudioStartupDelay>,
+    start_offset: Option<&'static AudioStartOffset>,
+    sync_start: Option<&'static AudioSyncStart>,
+    scheduled_start: Option<&'static AudioScheduledStart>,
+    group: Option<&'static AudioGroup>,
+}
+
+impl PlaybackQueryItem<'_> {
+    /// Either [`AudioLoop`] is present or [`AudioSource::looped`] is set, and
+    /// [`AudioForceOneShot`] isn't present. If source isn't loaded yet, only
+    /// components are checked.
+    fn is_looped(&self, source: &Handle<AudioSource>, sounds: &Assets<AudioSource>) -> bool {
+        self.one_shot.is_none()
+            && (self.looped.is_some() || sounds.get(source).is_some_and(|sound| sound.looped))
+    }
+
+    /// Start playback of the sound. Returns [`None`] if it's not ready or
+    /// can't be played right now, which isn't an error.
+    fn play(
+        &self,
+        mut bridge: std::pin::Pin<&mut bridge::Bridge>,
+        source: &Handle<AudioSource>,
+        sounds: &Assets<AudioSource>,
+        settings: &AudioSettings,
+        attenuation: &AttenuationCenter,
+        start: PlaybackStart,
+    ) -> Result<Option<AudioInstance>, bridge::BridgeError> {
+        let Some(sound) = sounds
+            .get(source)
+            .filter(|sound| sound.is_ready(bridge.as_mut()))
+        else {
+            return Ok(None);
+        };
+        let group = self.group.copied().unwrap_or_default();
+        let group_params = settings.groups.get(&group).cloned().unwrap_or_default();
+        let parameters = self.parameters.copied().unwrap_or_else(|| sound.params());
+        let position = self
+            .transform
+            .map(|t| t.translation())
+            .unwrap_or(Vec3::ZERO);
+
+        let start_offset = if let Some(offset) = start.resume_at {
+            let offset = offset.as_millis().try_into().unwrap_or(u32::MAX);
+            match bridge.as_mut().get_sound_info(sound.id).length_ms {
+                0 | u32::MAX => 0,
+                length if self.is_looped(source, sounds) => offset % length,
+                length if offset >= length => return Ok(None), // would have already finished
+                _ => offset,
+            }
+        } else if let Some(offset) = self.start_offset {
+            let offset = offset.0.as_millis().try_into().unwrap_or(u32::MAX);
+            match bridge.as_mut().get_sound_info(sound.id).length_ms {
+                0 | u32::MAX => offset,
+                length if self.is_looped(source, sounds) => offset % length,
+                length if offset >= length => {
+                    warn!("AudioStartOffset of {offset} ms is beyond the end of the sound ({length} ms)");
+                    length - 1
+                }
+                _ => offset,
+            }
+        } else if sound.randomize_start_offset {
+            match bridge.as_mut().get_sound_info(sound.id).length_ms {
+                0 | u32::MAX => 0,
+                length => thread_rng().gen_range(0..length),
+            }
+        } else {
+            sound
+                .start_offset
+                .as_millis()
+                .try_into()
+                .unwrap_or(u32::MAX)
+        };
+
+        let first_error = bridge.error_count();
+        let instance = bridge.as_mut().play_channel(bridge::ChannelParams {
+            file_id: sound.id,
+            group_id: group.0,
+            priority: parameters.priority as i32,
+            steal: match parameters.steal_behavior {
+                AudioSteal::Priority => bridge::StealBehavior::Priority,
+                AudioSteal::Oldest => bridge::StealBehavior::Oldest,
+                AudioSteal::Quietest => bridge::StealBehavior::Quietest,
+                AudioSteal::FailNew => bridge::StealBehavior::FailNew,
+            },
+            is_positional: self.transform.is_some(),
+            ignore_geometry: group_params.ignore_geometry,
+            position: position.into(),
+            velocity: Vec3::ZERO.into(),
+            min_distance: parameters.min_distance * attenuation.distance_scale(position),
+            max_distance: parameters.max_distance * attenuation.distance_scale(position),
+            spread: parameters.spread.clamp(0., 360.),
+            looped: self.is_looped(source, sounds),
+            volume: parameters.volume,
+            pitch: parameters.speed,
+            reverse: parameters.reverse,
+            start_offset,
+            loop_start: sound
+                .loop_region
+                .map_or(0, |(start, _)| start.as_millis() as u32),
+            loop_end: sound
+                .loop_region
+                .map_or(0, |(_, end)| end.as_millis() as u32),
+            startup_delay: self
+                .startup_delay
+                .map(|v| v.0)
+                .unwrap_or_default()
+                .as_micros() as i32,
+            start_clock: start.clock,
+        });
+
+        if instance == -1 {
+            if let Some(error) = bridge.as_mut().take_errors(first_error).pop() {
+                return Err(error);
+            }
+            if parameters.steal_behavior == AudioSteal::FailNew {
+                return Ok(None); // channel limit is reached
+            }
+            let info = sound.info_with(bridge.as_mut());
+            let message = format!("failed to play {source:?} ({info})");
+            error!("{message}");
+            return Err(bridge::BridgeError { message, code: 0 });
+        }
+
+        if self.mute.is_some() {
+            bridge.as_mut().set_channel_mute(instance, true);
+        }
+
+        Ok(Some(AudioInstance {
+            id: instance,
+            file_id: sound.id,
+            old_position: position,
+            old_position_at: start.now,
+            frame_position: position,
+            frame_position_at: start.now,
+            _source: {
+                let mut source = source.clone();
+                source.make_strong(sounds);
+                source
+            },
+        }))
+    }
+}
+
+fn resolve_group_names(
+    names: Query<(Entity, &AudioGroupName), Changed<AudioGroupName>>,
+    mut registry: ResMut<AudioGroupRegistry>,
+    mut commands: Commands,
+) {
+    for (entity, name) in names.iter() {
+        commands.entity(entity).insert(registry.register(&name.0));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn pick_random_audio(
+    pools: Query<
+        (Entity, &AudioRandomPool),
+        (Added<AudioRandomPool>, Without<Handle<AudioSource>>),
+    >,
+    mut last_picked: Local<HashMap<Vec<bevy::asset::HandleId>, usize>>,
+    sounds: Res<Assets<AudioSource>>,
+    asset_server: Res<AssetServer>,
+    settings: Res<AudioSettings>,
+    mut commands: Commands,
+) {
+    if pools.is_empty() {
+        return;
+    }
+    // forget pools whose sounds were all unloaded
+    last_picked.retain(|variants, _| {
+        variants.iter().any(|id| {
+            sounds.contains(&Handle::weak(*id))
+                || asset_server.get_load_state(*id) == bevy::asset::LoadState::Loading
+        })
+    });
+
+    for (entity, pool) in pools.iter() {
+        let key = || pool.variants.iter().map(|v| v.0.id()).collect::<Vec<_>>();
+        let exclude = match pool.no_immediate_repeat {
+            true => last_picked.get(&key()).copied(),
+            false => None,
+        };
+
+        match pool.pick_index(exclude) {
+            Some(index) => {
+                if pool.no_immediate_repeat {
+                    last_picked.insert(key(), index);
+                }
+                commands
+                    .entity(entity)
+                    .insert(pool.variants[index].0.clone());
+            }
+            None => {
+                warn!("AudioRandomPool of {entity:?} is empty");
+                despawn_finished(&mut commands, entity, &settings);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn load_audio_bytes(
+    files: Query<(Entity, &AudioBytes), (Added<AudioBytes>, Without<Handle<AudioSource>>)>,
+    mut sounds: ResMut<Assets<AudioSource>>,
+    mut errors: EventWriter<AudioError>,
+    // key is hash of the file, sources keep the file to compare it
+    mut loaded: Local<HashMap<u64, Vec<Handle<AudioSource>>>>,
+    settings: Res<AudioSettings>,
+    mut commands: Commands,
+) {
+    if files.is_empty() {
+        return;
+    }
+    loaded.retain(|_, handles| {
+        handles.retain(|handle| sounds.contains(handle));
+        !handles.is_empty()
+    });
+
+    for (entity, bytes) in files.iter() {
+        let hash = {
+            use std::hash::{Hash, Hasher};
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            bytes.0.hash(&mut hasher);
+            hasher.finish()
+        };
+        let cached = loaded.get(&hash).into_iter().flatten().find(|handle| {
+            sounds.get(*handle).is_some_and(|sound| {
+                matches!(&sound.data, AudioSourceData::Memory(file, _) if *file == *bytes.0)
+            })
+        });
+
+        let handle = match cached {
+            Some(handle) => sounds.get_handle(handle),
+            None => match AudioSource::from_memory(&bytes.0) {
+                Ok(source) => {
+                    let handle = sounds.add(source);
+                    loaded.entry(hash).or_default().push(handle.clone_weak());
+                    handle
+                }
+                Err(error) => {
+                    let context = AudioErrorContext::Entity(entity);
+                    errors.send(AudioError::from_load_error(context, error));
+                    despawn_finished(&mut commands, entity, &settings);
+                    continue;
+                }
+            },
+        };
+        commands.entity(entity).insert(handle);
+    }
+}
+
+/// Sound considered for stopping by [`AudioGroupParameters::max_audible`]
+struct Voice {
+    id: EngineId,
+    priority: u8,
+    distance: f32,
+}
+
+impl Voice {
+    /// Higher value means sound is more likely to be stopped
+    fn unimportance(&self) -> f64 {
+        // lower value means higher priority; distance is used only for equal priorities
+        self.priority as f64 * 1e9 + (self.distance as f64).min(1e9 - 1.)
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn play_audio(
+    new_audio: Query<
+        (
+            Entity,
+            &Handle<AudioSource>,
+            PlaybackQuery,
+            Option<&AudioPendingLoad>,
+        ),
+        Or<(Added<Handle<AudioSource>>, With<AudioPendingLoad>)>,
+    >,
+    live_audio: Query<(
+        &AudioInstance,
+        Option<&AudioGroup>,
+        Option<&AudioParameters>,
+        Option<&GlobalTransform>,
+    )>,
+    listener: Query<&GlobalTransform, With<AudioListener>>,
+    sounds: Res<Assets<AudioSource>>,
+    asset_server: Res<AssetServer>,
+    settings: Res<AudioSettings>,
+    attenuation: Res<AttenuationCenter>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut mapping: ResMut<AudioInstanceMapping>,
+    mut errors: EventWriter<AudioError>,
+) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        // audio is disabled, sounds are never started
+        for (entity, source, playback, _) in new_audio.iter() {
+            if !playback.is_looped(source, &sounds) {
+                despawn_finished(&mut commands, entity, &settings);
+            }
+        }
+        return;
+    };
+
+    let voice = |parameters: Option<&AudioParameters>,
+                 source: &Handle<AudioSource>,
+                 transform: Option<&GlobalTransform>| Voice {
+        id: -1,
+        priority: parameters
+            .map(|p| p.priority)
+            .or_else(|| sounds.get(source).map(|s| s.params.priority))
+            .unwrap_or_else(|| AudioParameters::default().priority),
+        distance: transform
+            .map(|t| {
+                let position = t.translation();
+                position.distance(nearest_listener(&listener, position).unwrap_or_default())
+            })
+            .unwrap_or(0.),
+    };
+
+    // playing sounds in groups which have polyphony limit, filled on demand
+    let mut voices: HashMap<AudioGroup, Vec<Voice>> = default();
+    // start clocks for AudioSyncStart, filled on demand
+    let mut sync_clocks: HashMap<AudioSyncStart, u64> = default();
+
+    for (entity, source, playback, pending) in new_audio.iter() {
+        let Some(mut commands) = commands.get_entity(entity) else {
+            continue;
+        };
+
+        let looped = playback.is_looped(source, &sounds);
+
+        let loading = match sounds.get(source) {
+            Some(sound) => sound.state_with(bridge.pin_mut()) == AudioSourceState::Loading,
+            None => true,
+        };
+        if loading {
+            // wait for the asset to load, unless it's not going to
+            let failed = asset_server.get_load_state(source) == bevy::asset::LoadState::Failed;
+            let since = pending.map(|p| p.since).unwrap_or(time.elapsed());
+            let timed_out = time.elapsed() - since > settings.load_timeout;
+
+            if failed || timed_out {
+                warn!("AudioSource asset {source:?} not loaded! Sound won't be played");
+                commands.remove::<AudioPendingLoad>();
+                mapping.pending.remove(&entity);
+                if !looped {
+                    despawn_finished(commands.commands(), entity, &settings);
+                }
+            } else if pending.is_none() {
+                commands.insert(AudioPendingLoad {
+                    since,
+                    resume_at: None,
+                });
+                mapping.pending.insert(entity);
+            }
+            continue;
+        }
+
+        if pending.is_some() {
+            commands.remove::<AudioPendingLoad>();
+            mapping.pending.remove(&entity);
+        }
+
+        // enforce polyphony limit
+        let group = playback.group.copied().unwrap_or_default();
+        let new_voice = voice(playback.parameters, source, playback.transform);
+        if let Some(max_audible) = settings.groups.get(&group).and_then(|p| p.max_audible) {
+            let group_voices = voices.entry(group).or_insert_with(|| {
+                live_audio
+                    .iter()
+                    .filter(|(_, g, _, _)| g.copied().unwrap_or_default() == group)
+                    .map(|(instance, _, parameters, transform)| Voice {
+                        id: instance.id,
+                        ..voice(parameters, &instance._source, transform)
+                    })
+                    .collect()
+            });
+
+            if group_voices.len() >= max_audible {
+                let least_important = group_voices
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.unimportance().total_cmp(&b.1.unimportance()))
+                    .map(|(index, _)| index);
+
+                match least_important {
+                    Some(index)
+                        if new_voice.unimportance() <= group_voices[index].unimportance() =>
+                    {
+                        // detect_stopped_audio will handle it
+                        let stolen = group_voices.swap_remove(index);
+                        bridge.pin_mut().stop_channel(stolen.id);
+                    }
+                    _ => {
+                        if !looped {
+                            despawn_finished(commands.commands(), entity, &settings);
+                        }
+                        continue;
+                    }
+                }
+            }
+        }
+
+        let clock = match (playback.scheduled_start, playback.sync_start) {
+            (Some(scheduled), _) => scheduled.0,
+            (None, Some(key)) => *sync_clocks
+                .entry(*key)
+                .or_insert_with(|| bridge.pin_mut().get_sync_clock(group.0)),
+            (None, None) => 0,
+        };
+
+        let instance = playback.play(
+            bridge.pin_mut(),
+            source,
+            &sounds,
+            &settings,
+            &attenuation,
+            PlaybackStart {
+                now: time.elapsed(),
+                clock,
+                resume_at: pending.and_then(|p| p.resume_at),
+            },
+        );
+        let instance = instance.unwrap_or_else(|error| {
+            errors.send(AudioError::new(AudioErrorContext::Entity(entity), error));
+            None
+        });
+        let Some(instance) = instance else {
+            if !looped {
+                despawn_finished(commands.commands(), entity, &settings);
+            }
+            continue;
+        };
+
+        if let Some(group_voices) = voices.get_mut(&group) {
+            group_voices.push(Voice {
+                id: instance.id,
+                ..new_voice
+            });
+        }
+
+        mapping.ids.insert(entity, instance.id);
+        commands.insert(instance);
+    }
+}
+
+// entity was despawned, stop the sound
+fn stop_audio(
+    mut removed: RemovedComponents<Handle<AudioSource>>,
+    mut mapping: ResMut<AudioInstanceMapping>,
+    mut updates: ResMut<PendingChannelUpdates>,
+    mut commands: Commands,
+) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for entity in removed.iter() {
+        let just_removed = mapping.just_removed.remove(&entity);
+        if mapping.pending.remove(&entity) {
+            if let Some(mut commands) = commands.get_entity(entity) {
+                commands.remove::<AudioPendingLoad>();
+            }
+            continue;
+        }
+
+        match mapping.ids.remove(&entity) {
+            Some(instance) => {
+                if let Some(mut commands) = commands.get_entity(entity) {
+                    commands.remove::<AudioInstance>();
+                }
+                updates.discard(instance);
+                bridge.pin_mut().free_channel(instance);
+            }
+            None => {
+                if !just_removed {
+                    error!("removing non-existent sound for entity {entity:?}")
+                }
+            }
+        }
+    }
+}
+
+// asset was reloaded, which stopped the playback
+fn restart_reloaded_audio(
+    instances: Query<(Entity, &AudioInstance)>,
+    sounds: Res<Assets<AudioSource>>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut mapping: ResMut<AudioInstanceMapping>,
+    mut updates: ResMut<PendingChannelUpdates>,
+) {
+    // asset events are sent only after this frame, but the old sound has
+    // already stopped and would be despawned by `detect_stopped_audio`
+    if !sounds.is_changed() {
+        return;
+    }
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for (entity, instance) in instances.iter() {
+        // engine re-initialization modifies all assets, but restarts sounds
+        // by itself
+        if mapping.ids.get(&entity) != Some(&instance.id) {
+            continue;
+        }
+        // asset can be modified without being reloaded
+        let Some(sound) = sounds.get(&instance._source) else {
+            continue;
+        };
+        if sound.id == instance.file_id {
+            continue;
+        }
+
+        // old sound was already freed, but engine keeps the position at
+        // which it was stopped
+        let position = bridge.pin_mut().get_channel_position(instance.id);
+        let position = (position >= 0.).then(|| Duration::from_secs_f64(position));
+
+        updates.discard(instance.id);
+        bridge.pin_mut().free_channel(instance.id);
+        mapping.ids.remove(&entity);
+        mapping.pending.insert(entity);
+        commands
+            .entity(entity)
+            .remove::<AudioInstance>()
+            .insert(AudioPendingLoad {
+                since: time.elapsed(),
+                resume_at: position,
+            });
+    }
+}
+
+fn restart_audio(
+    instances: Query<(Entity, Option<&AudioInstance>), Added<AudioRestart>>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut mapping: ResMut<AudioInstanceMapping>,
+    mut updates: ResMut<PendingChannelUpdates>,
+) {
+    for (entity, _) in instances.iter() {
+        commands.entity(entity).remove::<AudioRestart>();
+    }
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for (entity, instance) in instances.iter() {
+        // not started yet
+        let Some(instance) = instance else {
+            continue;
+        };
+        let mut commands = commands.entity(entity);
+
+        updates.discard(instance.id);
+        bridge.pin_mut().free_channel(instance.id);
+        mapping.ids.remove(&entity);
+        mapping.pending.insert(entity);
+        commands.remove::<AudioInstance>().insert(AudioPendingLoad {
+            since: time.elapsed(),
+            resume_at: None,
+        });
+    }
+}
+
+/// Start playback at this DSP clock of the group
+#[derive(Component)]
+struct AudioScheduledStart(u64);
+
+/// Playback state of [`AudioPlaylistPlayer`]
+#[derive(Component, Default)]
+struct PlaylistState {
+    /// Track indices in order of playback
+    order: Vec<usize>,
+    /// Index in `order` of the last started track
+    position: usize,
+    /// Entity and track index
+    current: Option<(Entity, usize)>,
+    /// Entity and track index, scheduled to start when current one ends
+    next: Option<(Entity, usize)>,
+}
+
+impl PlaylistState {
+    /// Returns index of the next track, or [`None`] if playlist has ended
+    fn advance(&mut self, player: &AudioPlaylistPlayer, track_count: usize) -> Option<usize> {
+        if self.order.len() != track_count {
+            // first track
+            self.order = (0..track_count).collect();
+            if player.shuffle {
+                self.order.shuffle(&mut thread_rng());
+            }
+            self.position = 0;
+        } else if self.position + 1 < self.order.len() {
+            self.position += 1;
+        } else if player.repeat {
+            if player.shuffle {
+                self.order.shuffle(&mut thread_rng());
+            }
+            self.position = 0;
+        } else {
+            return None;
+        }
+        self.order.get(self.position).copied()
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_playlists(
+    mut players: Query<(
+        Entity,
+        &AudioPlaylistPlayer,
+        Option<&mut PlaylistState>,
+        Option<&AudioGroup>,
+        Option<&AudioParameters>,
+    )>,
+    tracks: Query<Option<&AudioInstance>, With<Handle<AudioSource>>>,
+    playlists: Res<Assets<AudioPlaylist>>,
+    settings: Res<AudioSettings>,
+    mut events: EventWriter<AudioPlaylistTrack>,
+    mut commands: Commands,
+) {
+    // how long before the end of the track next one is scheduled
+    const SCHEDULE_AHEAD: f64 = 1.;
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for (entity, player, state, group, parameters) in players.iter_mut() {
+        let Some(playlist) = playlists.get(&player.playlist) else {
+            continue;
+        };
+
+        let mut spawn_track = |index: usize, clock: Option<u64>| {
+            let track = playlist.tracks[index].clone();
+            let mut commands = commands.spawn(track.clone());
+            if let Some(group) = group {
+                commands.insert(*group);
+            }
+            if let Some(parameters) = parameters {
+                commands.insert(*parameters);
+            }
+            if let Some(clock) = clock {
+                commands.insert(AudioScheduledStart(clock));
+            }
+            let track_entity = commands.id();
+            commands.commands().entity(entity).add_child(track_entity);
+            (track_entity, index, track)
+        };
+
+        let Some(mut state) = state else {
+            let mut state = PlaylistState::default();
+            match state.advance(player, playlist.tracks.len()) {
+                Some(index) => {
+                    let (track_entity, index, track) = spawn_track(index, None);
+                    state.current = Some((track_entity, index));
+                    events.send(AudioPlaylistTrack {
+                        entity,
+                        index,
+                        track,
+                    });
+                    commands.entity(entity).insert(state);
+                }
+                None => {
+                    warn!("AudioPlaylist {:?} is empty", player.playlist);
+                    despawn_finished(&mut commands, entity, &settings);
+                }
+            }
+            continue;
+        };
+
+        let current = state
+            .current
+            .and_then(|(track_entity, _)| tracks.get(track_entity).ok());
+        match current {
+            // current track has ended
+            None => {
+                let ended = state.current;
+                let next = state.next.take().or_else(|| {
+                    state
+                        .advance(player, playlist.tracks.len())
+                        .map(|index| spawn_track(index, None))
+                        .map(|(track_entity, index, _)| (track_entity, index))
+                });
+                state.current = next;
+
+                match next {
+                    Some((_, index)) => events.send(AudioPlaylistTrack {
+                        entity,
+                        index,
+                        track: playlist.tracks[index].clone(),
+                    }),
+                    None => despawn_finished(&mut commands, entity, &settings),
+                }
+
+                // track entities belong to the player
+                if let Some(track) =
+                    ended.and_then(|(track_entity, _)| commands.get_entity(track_entity))
+                {
+                    track.despawn_recursive();
+                }
+            }
+
+            // schedule next track
+            Some(Some(instance)) if state.next.is_none() => {
+                let end = bridge.pin_mut().get_channel_end(instance.id);
+                if end.remaining < 0. || end.remaining > SCHEDULE_AHEAD {
+                    continue;
+                }
+                if let Some(index) = state.advance(player, playlist.tracks.len()) {
+                    let (track_entity, index, _) = spawn_track(index, Some(end.clock));
+                    state.next = Some((track_entity, index));
+                }
+            }
+
+            // not started yet or next one is already scheduled
+            Some(_) => (),
+        }
+    }
+}
+
+/// Sound on the entity won't be played: despawn it, or only remove audio
+/// components if [`AudioSettings::auto_despawn`] is disabled
+fn despawn_finished(commands: &mut Commands, entity: Entity, settings: &AudioSettings) {
+    let Some(mut commands) = commands.get_entity(entity) else {
+        return;
+    };
+    if settings.auto_despawn {
+        commands.despawn_recursive();
+    } else {
+        commands.remove::<(
+            Handle<AudioSource>,
+            AudioInstance,
+            AudioPendingLoad,
+            AudioRandomPool,
+            AudioBytes,
+            (AudioPlaylistPlayer, PlaylistState),
+        )>();
+    }
+}
+
+// sound stopped, despawn the entity or start the next one from the queue
+#[allow(clippy::too_many_arguments)]
+fn detect_stopped_audio(
+    mut mapping: ResMut<AudioInstanceMapping>,
+    mut updates: ResMut<PendingChannelUpdates>,
+    mut queues: Query<(&mut AudioQueue, PlaybackQuery)>,
+    sounds: Res<Assets<AudioSource>>,
+    settings: Res<AudioSettings>,
+    attenuation: Res<AttenuationCenter>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut errors: EventWriter<AudioError>,
+) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    let mapping = &mut *mapping;
+    mapping.ids.retain(|entity, instance| {
+        if bridge.pin_mut().is_playing_channel(*instance) {
+            return true;
+        }
+        updates.discard(*instance);
+        bridge.pin_mut().free_channel(*instance);
 
-    /// Expected max coordinate values.
-    ///
-    /// _This isn't a hard limitation, but apparently exceeding it results in
-    /// worse performance._
-    pub max_world_size: f32,
+        if let Ok((mut queue, playback)) = queues.get_mut(*entity) {
+            while let Some(next) = queue.next.pop_front() {
+                if sounds.get(&next).is_none() {
+                    warn!("AudioSource asset {next:?} in AudioQueue not loaded yet! Skipping it");
+                    continue;
+                }
+                let next_instance = playback.play(
+                    bridge.pin_mut(),
+                    &next,
+                    &sounds,
+                    &settings,
+                    &attenuation,
+                    PlaybackStart {
+                        now: time.elapsed(),
+                        ..default()
+                    },
+                );
+                let next_instance = next_instance.unwrap_or_else(|error| {
+                    errors.send(AudioError::new(AudioErrorContext::Entity(*entity), error));
+                    None
+                });
+                if let Some(next_instance) = next_instance {
+                    *instance = next_instance.id;
+                    // replacing component doesn't trigger `play_audio`
+                    commands.entity(*entity).insert((next, next_instance));
+                    return true;
+                }
+            }
+        }
+
+        despawn_finished(&mut commands, *entity, &settings);
+        mapping.just_removed.insert(*entity);
+        false
+    });
 }
 
-impl Default for AudioEngineSettings {
-    fn default() -> Self {
-        Self {
-            doppler_scale: 0.33,
-            distance_scale: 1.,
-            rolloff_scale: 1.,
-            max_world_size: 500.,
-        }
+/// Channel updates which are sent to the engine at once by
+/// `flush_channel_updates`
+#[derive(Resource, Default)]
+struct PendingChannelUpdates(Vec<bridge::ChannelUpdate>);
+
+impl PendingChannelUpdates {
+    /// Drop updates for the channel which is being freed, as its ID can be
+    /// reused by another sound
+    fn discard(&mut self, id: EngineId) {
+        self.0.retain(|update| update.id != id);
     }
 }
 
-//
-// plugin
-//
+fn flush_channel_updates(mut updates: ResMut<PendingChannelUpdates>) {
+    if updates.0.is_empty() {
+        return;
+    }
 
-/// All systems are executed in this set in [`PostUpdate`]
-#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
-pub struct AudioSystem;
+    let mut bridge = BRIDGE.lock().unwrap();
+    if let Some(bridge) = bridge.as_mut() {
+        bridge.pin_mut().update_channels(&updates.0);
+    }
+    updates.0.clear();
+}
 
-/// File extensions of supported audio files, lowercase without leading dot.
-///
-/// _Actually more types are supported, but why would you use anything else?_
-pub const AUDIO_FILE_EXTENSIONS: &'static [&'static str] = &["flac", "mp3", "ogg", "wav"];
+/// See [`AudioAttenuationCenter`]
+#[derive(Resource, Default)]
+struct AttenuationCenter {
+    /// Listener and attenuation center positions, if both exist
+    positions: Option<(Vec3, Vec3)>,
+}
 
-/// Engine configuration which cannot be changed after initialization
-#[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct AudioEngineInitSettings {
-    /// How many sounds may exist at once.
-    ///
-    /// Only active ones will be played, based on priority and calculated
-    /// volume. Max value is `4095`.
-    pub max_virtual_channels: usize,
+impl AttenuationCenter {
+    /// Multiplier for min and max distance of the sound, so it's attenuated
+    /// as if listener was at the attenuation center
+    fn distance_scale(&self, position: Vec3) -> f32 {
+        // engine requires non-zero distances
+        const LIMIT: f32 = 1000.;
 
-    /// How many sounds can be played at once.
-    ///
-    /// If there are more sounds than active channels, sounds with lower
-    /// priority will be muted.
-    ///
-    /// Must be lower than `max_virtual_channels`.
-    pub max_active_channels: usize,
-}
+        match self.positions {
+            Some((listener, center)) => {
+                let ratio = position.distance(listener) / position.distance(center).max(1. / LIMIT);
+                ratio.clamp(1. / LIMIT, LIMIT)
+            }
+            None => 1.,
+        }
+    }
 
-impl Default for AudioEngineInitSettings {
-    fn default() -> Self {
-        Self {
-            max_virtual_channels: 1024,
-            max_active_channels: 32,
+    /// Distance which is used for attenuation of the sound
+    fn distance(&self, position: Vec3, listener: Vec3) -> f32 {
+        match self.positions {
+            Some((_, center)) => position.distance(center),
+            None => position.distance(listener),
         }
     }
 }
 
-/// Audio engine and all related systems
-#[derive(Default)]
-pub struct FmodAudioPlugin {
-    pub settings: AudioEngineInitSettings,
+fn update_attenuation_center(
+    listener: Query<&GlobalTransform, With<AudioListener>>,
+    center: Query<&GlobalTransform, With<AudioAttenuationCenter>>,
+    mut attenuation: ResMut<AttenuationCenter>,
+) {
+    attenuation.positions = listener
+        .get_single()
+        .ok()
+        .zip(center.get_single().ok())
+        .map(|(listener, center)| (listener.translation(), center.translation()));
 }
 
-impl Plugin for FmodAudioPlugin {
-    fn build(&self, app: &mut App) {
-        // TODO(later): allow re-init of everything
-
-        *BRIDGE.lock().unwrap() = {
-            let p = bridge::create(bridge::InitParams {
-                max_virtual_channels: self.settings.max_virtual_channels.min(4095) as i32,
-                max_active_channels: self
-                    .settings
-                    .max_active_channels
-                    .min(self.settings.max_virtual_channels)
-                    as i32,
-            });
-            // TODO(later): allow bridge to be None
-            if p.is_null() {
-                panic!("Failed to initialize audio");
-            }
-            Some(p)
-        };
-
-        app.configure_set(PostUpdate, AudioSystem)
-            .init_resource::<AudioSettings>()
-            .add_asset::<AudioSource>()
-            .add_asset_loader(AudioFileLoader);
-
-        // system update
-        app.add_systems(
-            PostUpdate,
-            (
-                update_listener.after(TransformSystem::TransformPropagate),
-                update_system.after(update_listener),
-                update_engine_settings
-                    .before(update_system)
-                    .run_if(resource_changed::<AudioSettings>()),
-            )
-                .in_set(AudioSystem),
-        );
+#[allow(clippy::too_many_arguments)]
+fn update_spatial_audio(
+    mut sounds: Query<(
+        &GlobalTransform,
+        &mut AudioInstance,
+        Option<&AudioParameters>,
+    )>,
+    sources: Res<Assets<AudioSource>>,
+    listener: Query<&GlobalTransform, With<AudioListener>>,
+    attenuation: Res<AttenuationCenter>,
+    settings: Res<AudioSettings>,
+    timer: Res<EngineUpdateTimer>,
+    time: Res<Time>,
+    mut updates: ResMut<PendingChannelUpdates>,
+) {
+    let now = time.elapsed();
 
-        // playback
-        app.init_resource::<AudioInstanceMapping>().add_systems(
-            PostUpdate,
-            (
-                play_audio
-                    .before(update_engine_settings)
-                    .after(TransformSystem::TransformPropagate),
-                stop_audio,
-                detect_stopped_audio,
-                update_spatial_audio.after(TransformSystem::TransformPropagate),
-                update_audio_parameters,
-            )
-                .in_set(AudioSystem)
-                .before(update_system),
-        );
+    // time for which positions are sent to the engine
+    let sample_at = match settings.emitter_interpolation {
+        AudioInterpolation::None => now,
+        AudioInterpolation::Interpolate => {
+            let late = timer.period.map_or(Duration::ZERO, |period| {
+                timer.since_update.saturating_sub(period)
+            });
+            now.saturating_sub(late.min(time.delta()))
+        }
+        AudioInterpolation::Extrapolate => {
+            let until_next = match timer.period {
+                // same as in update_system
+                Some(period) => period - timer.since_update.saturating_sub(period).min(period),
+                None => time.delta(),
+            };
+            now + until_next
+        }
+    };
 
-        // geometry
-        app.init_resource::<GeometryInstanceMapping>().add_systems(
-            PostUpdate,
-            (
-                add_geometry.after(TransformSystem::TransformPropagate),
-                remove_geometry,
-            )
-                .in_set(AudioSystem),
-        );
+    for (transform, mut instance, parameters) in sounds.iter_mut() {
+        let current = transform.translation();
+        let previous = std::mem::replace(&mut instance.frame_position, current);
+        let previous_at = std::mem::replace(&mut instance.frame_position_at, now);
 
-        // reverb
-        app.init_resource::<ReverbInstanceMapping>().add_systems(
-            PostUpdate,
-            (
-                add_reverb.after(TransformSystem::TransformPropagate),
-                remove_reverb,
-            )
-                .in_set(AudioSystem),
-        );
-    }
-}
+        if !timer.is_due {
+            continue;
+        }
 
-lazy_static::lazy_static! {
-    /// Engine instance (C++ wrapper)
-    static ref BRIDGE: Mutex<Option<cxx::UniquePtr<bridge::Bridge>>> = default();
-}
+        let frame = now.saturating_sub(previous_at);
+        let position = if frame != default() {
+            let t = sample_at.saturating_sub(previous_at).as_secs_f32() / frame.as_secs_f32();
+            previous.lerp(current, t)
+        } else {
+            current
+        };
 
-/// IDs used for sounds, channels and spatial objects
-type EngineId = i32;
+        // engine may be updated less often than every frame
+        let delta = sample_at.saturating_sub(instance.old_position_at);
+        let velocity = if delta != default() {
+            (position - instance.old_position) / delta.as_secs_f32()
+        } else {
+            Vec3::ZERO
+        };
+        instance.old_position = position;
+        instance.old_position_at = sample_at;
 
-//
-// assets
+        let mut params = bridge::ChannelUpdateParams {
+            set_position: true,
+            position: position.into(),
+            velocity: velocity.into(),
+            ..default()
+        };
 
-struct AudioFileLoader;
+        // attenuation center can move even if the sound doesn't
+        if let Some(distances) = parameters
+            .copied()
+            .or_else(|| sources.get(&instance._source).map(|source| source.params))
+        {
+            let scale = attenuation.distance_scale(position);
+            params.set_distance = true;
+            params.min_distance = distances.min_distance * scale;
+            params.max_distance = distances.max_distance * scale;
+        }
 
-impl bevy::asset::AssetLoader for AudioFileLoader {
-    fn load<'a>(
-        &'a self,
-        bytes: &'a [u8],
-        load_context: &'a mut bevy::asset::LoadContext,
-    ) -> bevy::asset::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
-        Box::pin(async move {
-            AudioSource::from_memory(bytes)
-                .map(|asset| load_context.set_default_asset(bevy::asset::LoadedAsset::new(asset)))
-                .ok_or_else(|| {
-                    bevy::asset::Error::msg(format!(
-                        "'{}': failed to load",
-                        load_context.path().display()
-                    ))
-                })
-        })
-    }
+        if let Some(parameters) = parameters.filter(|parameters| parameters.auto_priority) {
+            let distance = nearest_listener(&listener, position)
+                .map_or(f32::INFINITY, |listener| listener.distance(position));
+            params.set_volume_etc = true;
+            params.volume = parameters.volume;
+            params.pitch = parameters.speed;
+            params.priority = distance_priority(parameters, distance) as i32;
+            params.spread = parameters.spread.clamp(0., 360.);
+        }
 
-    fn extensions(&self) -> &[&str] {
-        AUDIO_FILE_EXTENSIONS
+        updates.0.push(bridge::ChannelUpdate {
+            id: instance.id,
+            params,
+        });
     }
 }
 
-//
-// system update
+/// Priority for [`AudioParameters::auto_priority`]
+fn distance_priority(parameters: &AudioParameters, distance: f32) -> u8 {
+    let range = parameters.max_distance - parameters.min_distance;
+    let t = if range > 0. {
+        ((distance - parameters.min_distance) / range).clamp(0., 1.)
+    } else if distance > parameters.min_distance {
+        1.
+    } else {
+        0.
+    };
 
-struct ListenerData {
-    data: bridge::ListenerParams,
-    old_position: Option<Vec3>,
+    let nearest = parameters.priority as f32;
+    let farthest = u8::from(AudioPriority::Ambient) as f32;
+    (nearest + (farthest - nearest).max(0.) * t).round() as u8
 }
 
-impl Default for ListenerData {
-    fn default() -> Self {
-        Self {
-            data: bridge::ListenerParams {
-                forward: Vec3::NEG_Z.into(),
-                up: Vec3::Y.into(),
+fn update_audio_parameters(
+    sounds: Query<
+        (&AudioParameters, &AudioInstance, Option<&GlobalTransform>),
+        Changed<AudioParameters>,
+    >,
+    mut updates: ResMut<PendingChannelUpdates>,
+) {
+    for (parameters, instance, transform) in sounds.iter() {
+        if parameters.auto_priority && transform.is_some() {
+            continue; // updated every frame in update_spatial_audio
+        }
+
+        updates.0.push(bridge::ChannelUpdate {
+            id: instance.id,
+            params: bridge::ChannelUpdateParams {
+                set_volume_etc: true,
+                volume: parameters.volume,
+                pitch: parameters.speed,
+                priority: parameters.priority as i32,
+                spread: parameters.spread.clamp(0., 360.),
                 ..default()
             },
-            old_position: None,
-        }
+        });
     }
 }
 
-fn update_listener(
-    listener_entity: Query<&GlobalTransform, With<AudioListener>>,
-    mut listener: Local<ListenerData>,
-    time: Res<Time>,
+/// Low-pass filter for air absorption and occlusion
+fn update_channel_lowpass(
+    sounds: Query<(&GlobalTransform, &AudioInstance, Option<&AudioGroup>)>,
+    listener: Query<&GlobalTransform, With<AudioListener>>,
+    attenuation: Res<AttenuationCenter>,
+    settings: Res<AudioSettings>,
+    mut was_enabled: Local<bool>,
 ) {
-    if let Ok(transform) = listener_entity.get_single() {
+    const MAX_CUTOFF: f32 = 22_000.; // upper limit of human hearing
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    let is_enabled =
+        settings.air_absorption.is_some() || settings.lowpass_cutoff_when_occluded.is_some();
+    if !is_enabled {
+        if *was_enabled {
+            for (_, instance, _) in sounds.iter() {
+                bridge.pin_mut().set_channel_lowpass(instance.id, 0.);
+            }
+        }
+        *was_enabled = false;
+        return;
+    }
+    *was_enabled = true;
+
+    for (transform, instance, group) in sounds.iter() {
         let position = transform.translation();
-        let velocity = if time.delta() != default() {
-            (position - listener.old_position.unwrap_or(position)) / time.delta_seconds()
-        } else {
-            Vec3::ZERO
+        let Some(listener_position) = nearest_listener(&listener, position) else {
+            return;
         };
-        listener.old_position = position.into();
+        let mut cutoff = MAX_CUTOFF;
 
-        let listener = &mut listener.data;
-        listener.position = position.into();
-        listener.velocity = velocity.into();
-        listener.forward = transform.forward().into();
-        listener.up = transform.up().into();
-    } else {
-        listener.data.velocity = default();
-        listener.old_position = None;
-    }
+        if let Some(absorption) = settings.air_absorption {
+            let distance = attenuation.distance(position, listener_position);
+            cutoff = cutoff.min(absorption.cutoff(distance));
+        }
 
-    BRIDGE
-        .lock()
-        .unwrap()
-        .as_mut()
-        .unwrap()
-        .pin_mut()
-        .update_listener(listener.data.clone());
-}
+        let ignore_geometry = settings
+            .groups
+            .get(&group.copied().unwrap_or_default())
+            .map(|p| p.ignore_geometry)
+            .unwrap_or_default();
+        if let (Some(occluded_cutoff), false) =
+            (settings.lowpass_cutoff_when_occluded, ignore_geometry)
+        {
+            let occlusion = bridge
+                .pin_mut()
+                .get_geometry_occlusion(listener_position.into(), position.into());
+            // linear for logarithm of frequency
+            let occluded_cutoff = occluded_cutoff.clamp(10., MAX_CUTOFF);
+            let t = occlusion.direct.clamp(0., 1.);
+            cutoff = cutoff.min(MAX_CUTOFF * (occluded_cutoff / MAX_CUTOFF).powf(t));
+        }
 
-fn update_system() {
-    BRIDGE.lock().unwrap().as_mut().unwrap().pin_mut().update();
+        bridge.pin_mut().set_channel_lowpass(instance.id, cutoff);
+    }
 }
 
-fn update_engine_settings(settings: Res<AudioSettings>) {
+// streamed sources get tags only after engine update
+fn read_pending_tags(mut sounds: ResMut<Assets<AudioSource>>) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
-    let master_volume = settings
-        .enabled
-        .then_some(settings.master_volume)
-        .unwrap_or(0.);
+    let pending: Vec<_> = sounds
+        .iter()
+        .filter(|(_, sound)| sound.tags_pending)
+        .map(|(id, _)| id)
+        .collect();
 
-    for (id, params) in settings.groups.iter() {
-        bridge.pin_mut().update_group(bridge::GroupParams {
-            user_id: id.0,
-            volume: params.volume * master_volume,
-        })
+    for id in pending {
+        let Some(sound) = sounds.get_mut(&Handle::weak(id)) else {
+            continue;
+        };
+        match sound.state_with(bridge.pin_mut()) {
+            AudioSourceState::Loading => continue,
+            AudioSourceState::Ready => sound.read_tags(bridge.pin_mut()),
+            AudioSourceState::Failed => (),
+        }
+        sound.tags_pending = false;
     }
-
-    let engine = &settings.engine;
-    bridge.pin_mut().update_engine(bridge::EngineParams {
-        doppler_scale: engine.doppler_scale,
-        distance_scale: engine.distance_scale,
-        rolloff_scale: engine.rolloff_scale,
-        max_world_size: engine.max_world_size,
-    });
-}
-
-//
-// playback
-
-#[derive(Resource, Default)]
-struct AudioInstanceMapping {
-    ids: HashMap<Entity, EngineId>,
-    just_removed: HashSet<Entity>,
 }
 
-/// Sound currently being played
-#[derive(Component)]
-struct AudioInstance {
-    id: EngineId,
-
-    /// For spatial: position in previous frame
-    old_position: Vec3,
+fn update_channel_usage(mut usage: ResMut<AudioChannelUsage>) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
-    /// Ensure handle always outlives the sound
-    _source: Handle<AudioSource>,
+    let stats = bridge.pin_mut().get_stats();
+    *usage = AudioChannelUsage {
+        virtual_playing: stats.channels_playing.max(0) as u32,
+        real_playing: stats.real_channels_playing.max(0) as u32,
+    };
 }
 
-fn play_audio(
-    new_audio: Query<
-        (
-            Entity,
-            &Handle<AudioSource>,
-            Option<&GlobalTransform>,
-            Option<&AudioLoop>,
-            Option<&AudioParameters>,
-            Option<&AudioStartupDelay>,
-            Option<&AudioGroup>,
-        ),
-        Added<Handle<AudioSource>>,
-    >,
+fn update_memory_stats(
+    mut stats: ResMut<AudioMemoryStats>,
     sounds: Res<Assets<AudioSource>>,
-    mut commands: Commands,
-    mut mapping: ResMut<AudioInstanceMapping>,
+    mut events: EventReader<AssetEvent<AudioSource>>,
+    mut pending: Local<HashSet<bevy::asset::HandleId>>,
 ) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
-    for (entity, source, transform, looped, parameters, startup_delay, group) in new_audio.iter() {
-        let Some(mut commands) = commands.get_entity(entity) else {
-            continue
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                pending.insert(handle.id());
+            }
+            AssetEvent::Removed { handle } => {
+                pending.remove(&handle.id());
+                stats.sources.remove(&handle.id());
+            }
+        }
+    }
+
+    // size of non-blocking sources is known only after they are loaded
+    pending.retain(|id| {
+        let Some(sound) = sounds.get(&Handle::weak(*id)) else {
+            return false;
+        };
+        let engine_bytes = match sound.state_with(bridge.pin_mut()) {
+            AudioSourceState::Loading => return true,
+            AudioSourceState::Ready => bridge.pin_mut().get_sound_memory(sound.id) as usize,
+            AudioSourceState::Failed => 0,
+        };
+        let file_bytes = match &sound.data {
+            AudioSourceData::Memory(file_contents, _) => file_contents.len(),
+            _ => 0,
         };
+        stats.sources.insert(*id, engine_bytes + file_bytes);
+        false
+    });
 
-        let looped = looped.is_some();
+    let memory = bridge.pin_mut().get_memory_stats();
+    stats.current = memory.current.max(0) as usize;
+    stats.max = memory.max.max(0) as usize;
+}
 
-        let sound = match sounds.get(source) {
-            Some(v) => v,
-            None => {
-                warn!("AudioSource asset {source:?} not loaded yet! Sound won't be played");
-                if !looped {
-                    commands.despawn_recursive();
-                }
-                continue;
-            }
-        };
+/// Errors which weren't already sent with more specific context
+fn send_error_events(loader_errors: Res<LoaderErrors>, mut events: EventWriter<AudioError>) {
+    events.send_batch(std::mem::take(&mut *loader_errors.0.lock().unwrap()));
 
-        let parameters = parameters.copied().unwrap_or_else(|| sound.params());
-        let position = transform.map(|t| t.translation()).unwrap_or(Vec3::ZERO);
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+    events.send_batch(
+        bridge
+            .pin_mut()
+            .take_errors(0)
+            .into_iter()
+            .map(|error| AudioError::new(AudioErrorContext::Engine, error)),
+    );
+}
 
-        let instance = bridge.pin_mut().play_channel(bridge::ChannelParams {
-            file_id: sound.id,
-            group_id: group.copied().unwrap_or_default().0,
-            priority: parameters.priority as i32,
-            is_positional: transform.is_some(),
-            position: position.into(),
-            velocity: Vec3::ZERO.into(),
-            min_distance: parameters.min_distance,
-            max_distance: parameters.max_distance,
-            looped,
-            volume: parameters.volume,
-            pitch: parameters.speed,
-            startup_delay: startup_delay.map(|v| v.0).unwrap_or_default().as_micros() as i32,
-        });
+/// Last reported beat of [`AudioBeatSync`]
+struct BeatSyncState {
+    /// Instance for which beats were reported
+    id: EngineId,
+    /// Seconds
+    position: f64,
+    /// `None` if position is before the first beat
+    beat: Option<u32>,
+}
 
-        if instance == -1 {
-            if !looped {
-                commands.despawn_recursive();
-            }
+fn send_beat_events(
+    sounds: Query<(Entity, &AudioInstance, &AudioBeatSync)>,
+    mut states: Local<HashMap<Entity, BeatSyncState>>,
+    mut events: EventWriter<AudioBeat>,
+) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    states.retain(|entity, _| sounds.contains(*entity));
+
+    for (entity, instance, sync) in sounds.iter() {
+        let position = bridge.pin_mut().get_channel_position(instance.id);
+        if position < 0. || sync.bpm <= 0. {
             continue;
         }
 
-        commands.insert(AudioInstance {
-            id: instance,
-            old_position: position,
-            _source: {
-                let mut source = source.clone();
-                source.make_strong(&sounds);
-                source
+        let beat_length = 60. / sync.bpm as f64;
+        let beat = (position - sync.offset.as_secs_f64()) / beat_length;
+        let beat = (beat >= 0.).then_some(beat as u32);
+
+        if let Some(beat) = beat {
+            let first = match states.get(&entity).filter(|state| state.id == instance.id) {
+                // if position went back, sound has looped around
+                Some(state) if state.position > position => 0,
+                Some(state) => state.beat.map_or(0, |beat| beat + 1),
+                // just started, possibly not from the beginning
+                None => beat,
+            };
+            events.send_batch((first..=beat).map(|index| AudioBeat { entity, index }));
+        }
+
+        states.insert(
+            entity,
+            BeatSyncState {
+                id: instance.id,
+                position,
+                beat,
             },
+        );
+    }
+}
+
+fn update_occlusion_readout(mut sounds: Query<(&AudioInstance, &mut AudioOcclusionReadout)>) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for (instance, mut readout) in sounds.iter_mut() {
+        let occlusion = bridge.pin_mut().get_channel_occlusion(instance.id);
+        readout.set_if_neq(AudioOcclusionReadout {
+            direct: occlusion.direct,
+            reverb: occlusion.reverb,
         });
-        mapping.ids.insert(entity, instance);
     }
 }
 
-// entity was despawned, stop the sound
-fn stop_audio(
-    mut removed: RemovedComponents<Handle<AudioSource>>,
-    mut mapping: ResMut<AudioInstanceMapping>,
-    mut commands: Commands,
+/// Mute on start is set in `play`
+fn update_audio_mute(
+    muted: Query<&AudioInstance, Added<AudioMute>>,
+    mut removed: RemovedComponents<AudioMute>,
+    instances: Query<&AudioInstance>,
 ) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for instance in muted.iter() {
+        bridge.pin_mut().set_channel_mute(instance.id, true);
+    }
 
     for entity in removed.iter() {
-        let just_removed = mapping.just_removed.remove(&entity);
-        match mapping.ids.remove(&entity) {
-            Some(instance) => {
-                if let Some(mut commands) = commands.get_entity(entity) {
-                    commands.remove::<AudioInstance>();
-                }
-                bridge.pin_mut().free_channel(instance);
-            }
-            None => {
-                if !just_removed {
-                    error!("removing non-existent sound for entity {entity:?}")
-                }
-            }
+        if let Ok(instance) = instances.get(entity) {
+            bridge.pin_mut().set_channel_mute(instance.id, false);
         }
     }
 }
 
-// sound stopped, despawn the entity
-fn detect_stopped_audio(mut mapping: ResMut<AudioInstanceMapping>, mut commands: Commands) {
+#[allow(clippy::type_complexity)]
+fn update_audio_distortion(
+    sounds: Query<
+        (&AudioDistortion, &AudioInstance),
+        Or<(Changed<AudioDistortion>, Added<AudioInstance>)>,
+    >,
+    mut removed: RemovedComponents<AudioDistortion>,
+    instances: Query<&AudioInstance>,
+) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
-    let mapping = &mut *mapping;
-    mapping.ids.retain(|entity, instance| {
-        let keep = bridge.pin_mut().is_playing_channel(*instance);
-        if !keep {
-            if let Some(commands) = commands.get_entity(*entity) {
-                commands.despawn_recursive();
-            }
-            bridge.pin_mut().free_channel(*instance);
-            mapping.just_removed.insert(*entity);
+    for (distortion, instance) in sounds.iter() {
+        bridge
+            .pin_mut()
+            .set_channel_distortion(instance.id, distortion.level.clamp(0., 1.));
+    }
+
+    for entity in removed.iter() {
+        if let Ok(instance) = instances.get(entity) {
+            bridge.pin_mut().set_channel_distortion(instance.id, 0.);
         }
-        keep
-    });
+    }
 }
 
-fn update_spatial_audio(
-    mut sounds: Query<(&GlobalTransform, &mut AudioInstance)>,
-    time: Res<Time>,
+#[allow(clippy::type_complexity)]
+fn update_audio_chorus(
+    sounds: Query<(&AudioChorus, &AudioInstance), Or<(Changed<AudioChorus>, Added<AudioInstance>)>>,
+    mut removed: RemovedComponents<AudioChorus>,
+    instances: Query<&AudioInstance>,
 ) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
-    for (transform, mut instance) in sounds.iter_mut() {
-        let position = transform.translation();
-        let velocity = if time.delta() != default() {
-            (position - instance.old_position) / time.delta_seconds()
-        } else {
-            Vec3::ZERO
-        };
-        instance.old_position = position.into();
-
-        bridge.pin_mut().update_channel(
+    for (chorus, instance) in sounds.iter() {
+        bridge.pin_mut().set_channel_chorus(
             instance.id,
-            bridge::ChannelUpdateParams {
-                set_position: true,
-                position: position.into(),
-                velocity: velocity.into(),
-                ..default()
-            },
+            chorus.rate.clamp(0., 20.),
+            chorus.depth.clamp(0., 1.),
+            chorus.mix.clamp(0., 1.),
         );
     }
+
+    for entity in removed.iter() {
+        if let Ok(instance) = instances.get(entity) {
+            bridge.pin_mut().set_channel_chorus(instance.id, 0., 0., 0.);
+        }
+    }
 }
 
-fn update_audio_parameters(
-    sounds: Query<(&AudioParameters, &AudioInstance), Changed<AudioParameters>>,
+fn update_audio_group(
+    sounds: Query<(&AudioGroup, &AudioInstance), Changed<AudioGroup>>,
+    mut removed: RemovedComponents<AudioGroup>,
+    instances: Query<&AudioInstance>,
 ) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
-    for (parameters, instance) in sounds.iter() {
-        bridge.pin_mut().update_channel(
-            instance.id,
-            bridge::ChannelUpdateParams {
-                set_volume_etc: true,
-                volume: parameters.volume,
-                pitch: parameters.speed,
-                priority: parameters.priority as i32,
-                ..default()
-            },
-        );
+    for (group, instance) in sounds.iter() {
+        bridge.pin_mut().set_channel_group(instance.id, group.0);
+    }
+
+    // return to the default group
+    for entity in removed.iter() {
+        if let Ok(instance) = instances.get(entity) {
+            bridge
+                .pin_mut()
+                .set_channel_group(instance.id, AudioGroup::default().0);
+        }
     }
 }
 
@@ -940,42 +6418,304 @@ fn update_audio_parameters(
 #[derive(Resource, Default)]
 struct GeometryInstanceMapping(HashMap<Entity, EngineId>);
 
+fn geometry_params(geometry: &AudioGeometry, transform: &GlobalTransform) -> bridge::Geometry {
+    bridge::Geometry {
+        direct_occlusion: geometry.params.direct_occlusion.clamp(0., 1.),
+        reverb_occlusion: geometry.params.reverb_occlusion.clamp(0., 1.),
+        polygons: geometry
+            .polygon_vertices
+            .iter()
+            .flat_map(|polygon| match check_polygon(polygon) {
+                Err(_) if geometry.auto_triangulate => triangulate_polygon(polygon),
+                _ => vec![polygon.clone()],
+            })
+            .map(|polygon| bridge::Polygon {
+                vertices: polygon.into_iter().map(Vec3::into).collect(),
+            })
+            .collect(),
+        transform: geometry_transform(transform),
+        active: geometry.active,
+    }
+}
+
+/// Why polygon can't be used by the engine as is
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PolygonError {
+    TooFewVertices,
+    NotPlanar,
+    NotConvex,
+}
+
+impl std::fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolygonError::TooFewVertices => write!(f, "has less than 3 vertices"),
+            PolygonError::NotPlanar => write!(f, "isn't planar"),
+            PolygonError::NotConvex => write!(f, "isn't convex"),
+        }
+    }
+}
+
+/// Normal computed with Newell's method, not normalized. Works for concave and
+/// slightly non-planar polygons; length is twice the area.
+fn polygon_normal(polygon: &[Vec3]) -> Vec3 {
+    let next = polygon.iter().cycle().skip(1);
+    polygon.iter().zip(next).map(|(a, b)| a.cross(*b)).sum()
+}
+
+fn check_polygon(polygon: &[Vec3]) -> Result<(), PolygonError> {
+    // relative to the polygon size
+    const EPSILON: f32 = 1e-3;
+
+    if polygon.len() < 3 {
+        return Err(PolygonError::TooFewVertices);
+    }
+
+    let center = polygon.iter().sum::<Vec3>() / polygon.len() as f32;
+    let size = polygon
+        .iter()
+        .map(|vertex| vertex.distance(center))
+        .fold(0., f32::max);
+    let Some(normal) = polygon_normal(polygon).try_normalize() else {
+        return Err(PolygonError::NotPlanar); // degenerate
+    };
+
+    if polygon
+        .iter()
+        .any(|vertex| (*vertex - center).dot(normal).abs() > size * EPSILON)
+    {
+        return Err(PolygonError::NotPlanar);
+    }
+
+    let len = polygon.len();
+    let is_reflex = |i: usize| {
+        let (a, b, c) = (polygon[i], polygon[(i + 1) % len], polygon[(i + 2) % len]);
+        (b - a).cross(c - b).dot(normal) < -size * size * EPSILON
+    };
+    if (0..len).any(is_reflex) {
+        return Err(PolygonError::NotConvex);
+    }
+
+    Ok(())
+}
+
+/// Split polygon into triangles with the same winding. Planar polygons are
+/// split with ear clipping, others with a fan.
+fn triangulate_polygon(polygon: &[Vec3]) -> Vec<Vec<Vec3>> {
+    if polygon.len() < 3 {
+        return vec![];
+    }
+
+    let fan = |indices: &[usize]| {
+        (1..indices.len() - 1)
+            .map(|i| {
+                vec![
+                    polygon[indices[0]],
+                    polygon[indices[i]],
+                    polygon[indices[i + 1]],
+                ]
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut indices: Vec<usize> = (0..polygon.len()).collect();
+    let normal = match check_polygon(polygon) {
+        Err(PolygonError::NotPlanar) => return fan(&indices),
+        _ => polygon_normal(polygon),
+    };
+
+    let turn = |a: Vec3, b: Vec3, c: Vec3| (b - a).cross(c - b).dot(normal);
+    let is_convex = |a: Vec3, b: Vec3, c: Vec3| turn(a, b, c) > 0.;
+    // including edges, so ear can't be cut through a vertex
+    let is_inside = |p: Vec3, a: Vec3, b: Vec3, c: Vec3| {
+        turn(a, b, p) >= 0. && turn(b, c, p) >= 0. && turn(c, a, p) >= 0.
+    };
+
+    let mut triangles = vec![];
+    while indices.len() > 3 {
+        let len = indices.len();
+        let corner = |i: usize| {
+            (
+                polygon[indices[(i + len - 1) % len]],
+                polygon[indices[i]],
+                polygon[indices[(i + 1) % len]],
+            )
+        };
+        let is_ear = |i: usize| {
+            let (a, b, c) = corner(i);
+            is_convex(a, b, c)
+                && indices
+                    .iter()
+                    .map(|index| polygon[*index])
+                    .filter(|p| *p != a && *p != b && *p != c)
+                    .all(|p| !is_inside(p, a, b, c))
+        };
+
+        let Some(ear) = (0..len).find(|i| is_ear(*i)) else {
+            break; // self-intersecting polygon
+        };
+        let (a, b, c) = corner(ear);
+        triangles.push(vec![a, b, c]);
+        indices.remove(ear);
+    }
+    triangles.extend(fan(&indices));
+    triangles
+}
+
+fn geometry_transform(transform: &GlobalTransform) -> bridge::GeometryTransform {
+    let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+    bridge::GeometryTransform {
+        position: translation.into(),
+        forward: (rotation * Vec3::Z).into(),
+        up: (rotation * Vec3::Y).into(),
+        scale: scale.into(),
+    }
+}
+
+/// See [`AudioGeometry`] docs
+fn insert_geometry_assets(
+    mut events: EventReader<AssetEvent<AudioGeometry>>,
+    entities: Query<(Entity, &Handle<AudioGeometry>, Option<&AudioGeometry>)>,
+    assets: Res<Assets<AudioGeometry>>,
+    mut commands: Commands,
+) {
+    let modified: HashSet<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } => Some(handle.id()),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, handle, current) in entities.iter() {
+        let Some(geometry) = assets.get(handle) else {
+            continue;
+        };
+        match current {
+            None => {
+                commands.entity(entity).insert(geometry.clone());
+            }
+            // polygons can't be changed, so geometry is re-added
+            Some(current) if modified.contains(&handle.id()) => {
+                let geometry = AudioGeometry {
+                    active: current.active,
+                    ..geometry.clone()
+                };
+                commands
+                    .entity(entity)
+                    .remove::<AudioGeometry>()
+                    .insert(geometry);
+            }
+            Some(_) => (),
+        }
+    }
+}
+
 fn add_geometry(
     new_geometries: Query<(Entity, &AudioGeometry, &GlobalTransform), Added<AudioGeometry>>,
+    info: Res<AudioEngineInfo>,
     mut mapping: ResMut<GeometryInstanceMapping>,
+    mut errors: EventWriter<AudioError>,
 ) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
     for (entity, geometry, transform) in new_geometries.iter() {
-        let instance = bridge.pin_mut().add_geometry(bridge::Geometry {
-            direct_occlusion: geometry.params.direct_occlusion.clamp(0., 1.),
-            reverb_occlusion: geometry.params.reverb_occlusion.clamp(0., 1.),
-            polygons: geometry
-                .polygon_vertices
-                .iter()
-                .map(|polygon| bridge::Polygon {
-                    vertices: polygon
-                        .iter()
-                        .map(|vertex| (*transform * *vertex).into())
-                        .collect(),
-                })
-                .collect(),
-        });
+        for (index, polygon) in geometry.polygon_vertices.iter().enumerate() {
+            if let Err(error) = check_polygon(polygon) {
+                if geometry.auto_triangulate {
+                    warn!("AudioGeometry of {entity:?}: polygon {index} {error}, it will be triangulated");
+                } else {
+                    warn!("AudioGeometry of {entity:?}: polygon {index} {error}, occlusion may be incorrect");
+                }
+            }
+        }
+
+        let is_outside = |v: &Vec3| {
+            let max = (*transform * *v).abs().max_element();
+            max > info.geometry_world_size
+        };
+        if geometry
+            .polygon_vertices
+            .iter()
+            .any(|polygon| polygon.iter().any(is_outside))
+        {
+            warn!(
+                "AudioGeometry of {entity:?} exceeds geometry_world_size ({}), occlusion may be incorrect",
+                info.geometry_world_size
+            );
+        }
+
+        let first_error = bridge.error_count();
+        let instance = bridge
+            .pin_mut()
+            .add_geometry(geometry_params(geometry, transform));
         if instance == -1 {
-            error!("failed to create geometry object for {entity:?}");
+            let context = AudioErrorContext::Entity(entity);
+            errors.send(AudioError::engine(context, bridge.pin_mut(), first_error));
             continue;
         }
         mapping.0.insert(entity, instance);
     }
 }
 
+/// Only geometry which actually moved is updated
+fn move_geometry(
+    moved: Query<(Entity, Ref<AudioGeometry>, &GlobalTransform), Changed<GlobalTransform>>,
+    mapping: Res<GeometryInstanceMapping>,
+) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for (entity, geometry, transform) in moved.iter() {
+        if geometry.is_added() {
+            continue; // already has that transform
+        }
+        if let Some(id) = mapping.0.get(&entity) {
+            bridge
+                .pin_mut()
+                .set_geometry_transform(*id, geometry_transform(transform));
+        }
+    }
+}
+
+/// Applies changes to occlusion and active flag; geometry object is kept
+fn update_geometry(
+    geometries: Query<(Entity, Ref<AudioGeometry>), Changed<AudioGeometry>>,
+    mapping: Res<GeometryInstanceMapping>,
+) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    for (entity, geometry) in geometries.iter() {
+        if geometry.is_added() {
+            continue; // created with that value
+        }
+        if let Some(id) = mapping.0.get(&entity) {
+            bridge.pin_mut().set_geometry_occlusion(
+                *id,
+                geometry.params.direct_occlusion.clamp(0., 1.),
+                geometry.params.reverb_occlusion.clamp(0., 1.),
+            );
+            bridge.pin_mut().set_geometry_active(*id, geometry.active);
+        }
+    }
+}
+
 fn remove_geometry(
     mut removed: RemovedComponents<AudioGeometry>,
     mut mapping: ResMut<GeometryInstanceMapping>,
 ) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
     for entity in removed.iter() {
         match mapping.0.remove(&entity) {
@@ -985,40 +6725,256 @@ fn remove_geometry(
     }
 }
 
+//
+// physics
+
+/// Segments used to approximate curved collider shapes
+#[cfg(feature = "physics-integration")]
+const COLLIDER_SEGMENTS: u32 = 16;
+
+/// See [`AudioColliderGeometry`] and [`AudioColliderReverb`] docs
+#[cfg(feature = "physics-integration")]
+#[allow(clippy::type_complexity)]
+fn insert_collider_audio(
+    geometries: Query<
+        (
+            Entity,
+            &bevy_rapier3d::prelude::Collider,
+            &AudioColliderGeometry,
+        ),
+        Or<(
+            Added<bevy_rapier3d::prelude::Collider>,
+            Added<AudioColliderGeometry>,
+        )>,
+    >,
+    reverbs: Query<
+        (
+            Entity,
+            &bevy_rapier3d::prelude::Collider,
+            &AudioColliderReverb,
+            &GlobalTransform,
+        ),
+        Or<(
+            Added<bevy_rapier3d::prelude::Collider>,
+            Added<AudioColliderReverb>,
+        )>,
+    >,
+    mut commands: Commands,
+) {
+    use bevy_rapier3d::prelude::ColliderView;
+
+    for (entity, collider, params) in geometries.iter() {
+        let mut polygons = vec![];
+        if !collider_polygons(
+            collider.as_unscaled_typed_shape(),
+            Transform::IDENTITY,
+            &mut polygons,
+        ) {
+            warn!("AudioColliderGeometry of {entity:?}: collider shape isn't supported");
+        }
+        commands.entity(entity).insert(AudioGeometry {
+            polygon_vertices: polygons,
+            params: params.0,
+            ..default()
+        });
+    }
+
+    for (entity, collider, reverb, transform) in reverbs.iter() {
+        let ColliderView::Ball(ball) = collider.as_unscaled_typed_shape() else {
+            warn!("AudioColliderReverb of {entity:?}: collider isn't a ball");
+            continue;
+        };
+        // collider is scaled by the transform
+        let radius = ball.radius() * transform.compute_transform().scale.max_element();
+        commands.entity(entity).insert(AudioReverbSphere {
+            min_distance: (radius - reverb.falloff).max(0.),
+            max_distance: radius,
+            props: reverb.props.clone(),
+        });
+    }
+}
+
+/// Appends polygons of the shape in local space of the collider. Returns false
+/// if the shape (or part of the compound shape) isn't supported.
+#[cfg(feature = "physics-integration")]
+fn collider_polygons(
+    shape: bevy_rapier3d::prelude::ColliderView,
+    transform: Transform,
+    polygons: &mut AudioGeometryData,
+) -> bool {
+    use bevy_rapier3d::{prelude::ColliderView, rapier::math::Point};
+
+    let mut push = |polygon: Vec<Vec3>| {
+        polygons.push(polygon.into_iter().map(|v| transform * v).collect());
+    };
+    let mut push_trimesh = |(points, indices): (Vec<Point<f32>>, Vec<[u32; 3]>)| {
+        for triangle in indices {
+            push(
+                triangle
+                    .iter()
+                    .map(|index| {
+                        let point = points[*index as usize];
+                        Vec3::new(point.x, point.y, point.z)
+                    })
+                    .collect(),
+            );
+        }
+    };
+
+    match shape {
+        ColliderView::Cuboid(cuboid) => {
+            AudioGeometry::cuboid(cuboid.half_extents(), default())
+                .polygon_vertices
+                .into_iter()
+                .for_each(push);
+        }
+        ColliderView::RoundCuboid(cuboid) => {
+            let half_extents =
+                cuboid.inner_shape().half_extents() + Vec3::splat(cuboid.border_radius());
+            AudioGeometry::cuboid(half_extents, default())
+                .polygon_vertices
+                .into_iter()
+                .for_each(push);
+        }
+        ColliderView::Cylinder(cylinder) => {
+            AudioGeometry::cylinder(
+                cylinder.radius(),
+                cylinder.half_height() * 2.,
+                COLLIDER_SEGMENTS as usize,
+                default(),
+            )
+            .polygon_vertices
+            .into_iter()
+            .for_each(push);
+        }
+        ColliderView::Triangle(triangle) => push(triangle.vertices().to_vec()),
+        ColliderView::TriMesh(trimesh) => {
+            for (a, b, c) in trimesh.triangles() {
+                push(vec![a, b, c]);
+            }
+        }
+        ColliderView::ConvexPolyhedron(polyhedron) => {
+            let polyhedron = polyhedron.raw;
+            let points = polyhedron.points();
+            for face in polyhedron.faces() {
+                let first = face.first_vertex_or_edge as usize;
+                let vertices = &polyhedron.vertices_adj_to_face()
+                    [first..first + face.num_vertices_or_edges as usize];
+                push(
+                    vertices
+                        .iter()
+                        .map(|index| {
+                            let point = points[*index as usize];
+                            Vec3::new(point.x, point.y, point.z)
+                        })
+                        .collect(),
+                );
+            }
+        }
+        ColliderView::Ball(ball) => push_trimesh(
+            ball.raw
+                .to_trimesh(COLLIDER_SEGMENTS, COLLIDER_SEGMENTS / 2),
+        ),
+        ColliderView::Capsule(capsule) => push_trimesh(
+            capsule
+                .raw
+                .to_trimesh(COLLIDER_SEGMENTS, COLLIDER_SEGMENTS / 2),
+        ),
+        ColliderView::Cone(cone) => push_trimesh(cone.raw.to_trimesh(COLLIDER_SEGMENTS)),
+        ColliderView::Compound(compound) => {
+            let mut is_supported = true;
+            for (translation, rotation, shape) in compound.shapes() {
+                let shape_transform =
+                    Transform::from_translation(translation).with_rotation(rotation);
+                is_supported &= collider_polygons(shape, transform * shape_transform, polygons);
+            }
+            return is_supported;
+        }
+        _ => return false,
+    }
+    true
+}
+
 //
 // reverb
 
 #[derive(Resource, Default)]
 struct ReverbInstanceMapping(HashMap<Entity, EngineId>);
 
+impl From<&AudioReverbProps> for bridge::ReverbProps {
+    fn from(props: &AudioReverbProps) -> Self {
+        Self {
+            decay_time: props.decay_time,
+            early_delay: props.early_delay,
+            late_delay: props.late_delay,
+            hf_reference: props.hf_reference,
+            hf_decay_ratio: props.hf_decay_ratio,
+            diffusion: props.diffusion,
+            density: props.density,
+            low_shelf_frequency: props.low_shelf_frequency,
+            low_shelf_gain: props.low_shelf_gain,
+            high_cut: props.high_cut,
+            early_late_mix: props.early_late_mix,
+            wet_level: props.wet_level,
+        }
+    }
+}
+
+/// Reverb instance used for [`AudioSettings::reverb_blending`]
+/// (instance 0 is used by FMOD for reverb spheres)
+const BLENDED_REVERB_INSTANCE: i32 = 1;
+
+/// Reverb instance used for [`AudioSettings::ambient_reverb`]
+const AMBIENT_REVERB_INSTANCE: i32 = 2;
+
+fn reverb_params(reverb: &AudioReverbSphere, transform: &GlobalTransform) -> bridge::Reverb {
+    bridge::Reverb {
+        min_dist: reverb.min_distance,
+        max_dist: reverb.max_distance,
+        position: transform.translation().into(),
+        props: (&reverb.props).into(),
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn add_reverb(
     new_reverbs: Query<(Entity, &AudioReverbSphere, &GlobalTransform), Added<AudioReverbSphere>>,
+    all_reverbs: Query<(Entity, &AudioReverbSphere, &GlobalTransform)>,
+    settings: Res<AudioSettings>,
+    mut was_blending: Local<bool>,
     mut mapping: ResMut<ReverbInstanceMapping>,
+    mut errors: EventWriter<AudioError>,
 ) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
-
-    for (entity, reverb, transform) in new_reverbs.iter() {
-        let instance = bridge.pin_mut().add_reverb(bridge::Reverb {
-            min_dist: reverb.min_distance,
-            max_dist: reverb.max_distance,
-            position: transform.translation().into(),
-
-            decay_time: reverb.props.decay_time,
-            early_delay: reverb.props.early_delay,
-            late_delay: reverb.props.late_delay,
-            hf_reference: reverb.props.hf_reference,
-            hf_decay_ratio: reverb.props.hf_decay_ratio,
-            diffusion: reverb.props.diffusion,
-            density: reverb.props.density,
-            low_shelf_frequency: reverb.props.low_shelf_frequency,
-            low_shelf_gain: reverb.props.low_shelf_gain,
-            high_cut: reverb.props.high_cut,
-            early_late_mix: reverb.props.early_late_mix,
-            wet_level: reverb.props.wet_level,
-        });
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    // spheres exist in the engine only when blending is disabled
+    let is_blending = settings.reverb_blending.is_some();
+    let new_reverbs: Vec<_> = if is_blending != *was_blending {
+        *was_blending = is_blending;
+        if is_blending {
+            for (_, id) in mapping.0.drain() {
+                bridge.pin_mut().free_reverb(id);
+            }
+            return;
+        }
+        all_reverbs.iter().collect()
+    } else if is_blending {
+        return;
+    } else {
+        new_reverbs.iter().collect()
+    };
+
+    for (entity, reverb, transform) in new_reverbs {
+        let first_error = bridge.error_count();
+        let instance = bridge
+            .pin_mut()
+            .add_reverb(reverb_params(reverb, transform));
         if instance == -1 {
-            error!("failed to create reverb object for entity {entity:?}");
+            let context = AudioErrorContext::Entity(entity);
+            errors.send(AudioError::engine(context, bridge.pin_mut(), first_error));
             continue;
         }
         mapping.0.insert(entity, instance);
@@ -1027,15 +6983,180 @@ fn add_reverb(
 
 fn remove_reverb(
     mut removed: RemovedComponents<AudioReverbSphere>,
+    settings: Res<AudioSettings>,
     mut mapping: ResMut<ReverbInstanceMapping>,
 ) {
     let mut bridge = BRIDGE.lock().unwrap();
-    let bridge = bridge.as_mut().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
 
     for entity in removed.iter() {
         match mapping.0.remove(&entity) {
             Some(id) => bridge.pin_mut().free_reverb(id),
-            None => error!("removing non-existent reverb for entity {entity:?}"),
+            None => {
+                if settings.reverb_blending.is_none() {
+                    error!("removing non-existent reverb for entity {entity:?}")
+                }
+            }
+        }
+    }
+}
+
+/// Weighted average of reverb properties. Weights must be normalized.
+fn blend_reverb_props(props: &[(&AudioReverbProps, f32)]) -> AudioReverbProps {
+    let blend = |field: fn(&AudioReverbProps) -> f32| -> f32 {
+        props.iter().map(|(p, weight)| field(p) * weight).sum()
+    };
+    AudioReverbProps {
+        decay_time: blend(|p| p.decay_time),
+        early_delay: blend(|p| p.early_delay),
+        late_delay: blend(|p| p.late_delay),
+        hf_reference: blend(|p| p.hf_reference),
+        hf_decay_ratio: blend(|p| p.hf_decay_ratio),
+        diffusion: blend(|p| p.diffusion),
+        density: blend(|p| p.density),
+        low_shelf_frequency: blend(|p| p.low_shelf_frequency),
+        low_shelf_gain: blend(|p| p.low_shelf_gain),
+        high_cut: blend(|p| p.high_cut),
+        early_late_mix: blend(|p| p.early_late_mix),
+        wet_level: blend(|p| p.wet_level),
+    }
+}
+
+fn blend_reverb(
+    reverbs: Query<(&AudioReverbSphere, &GlobalTransform)>,
+    listener: Query<&GlobalTransform, With<AudioListener>>,
+    settings: Res<AudioSettings>,
+    mut was_enabled: Local<bool>,
+) {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let Some(bridge) = bridge.as_mut() else {
+        return;
+    };
+
+    let Some(blending) = settings.reverb_blending else {
+        if *was_enabled {
+            *was_enabled = false;
+            bridge.pin_mut().set_global_reverb(
+                BLENDED_REVERB_INSTANCE,
+                false,
+                (&AudioReverbProps::default()).into(),
+            );
+        }
+        return;
+    };
+    *was_enabled = true;
+
+    // with multiple listeners, only one can be used
+    let listener_position = listener
+        .iter()
+        .next()
+        .map(|t| t.translation())
+        .unwrap_or_default();
+
+    // (sphere, distance to listener, influence)
+    let mut spheres: Vec<_> = reverbs
+        .iter()
+        .filter_map(|(reverb, transform)| {
+            let distance = transform.translation().distance(listener_position);
+            let influence = if distance <= reverb.min_distance {
+                1.
+            } else {
+                (reverb.max_distance - distance)
+                    / (reverb.max_distance - reverb.min_distance).max(f32::EPSILON)
+            };
+            (influence > 0.).then_some((reverb, distance, influence.min(1.)))
+        })
+        .collect();
+    spheres.sort_by(|a, b| a.1.total_cmp(&b.1));
+    spheres.truncate(blending.max_spheres);
+
+    let total: f32 = spheres.iter().map(|(_, _, influence)| influence).sum();
+    if total <= 0. {
+        bridge.pin_mut().set_global_reverb(
+            BLENDED_REVERB_INSTANCE,
+            false,
+            (&AudioReverbProps::default()).into(),
+        );
+        return;
+    }
+
+    let weights: Vec<_> = spheres
+        .iter()
+        .map(|(reverb, _, influence)| (&reverb.props, influence / total))
+        .collect();
+    let mut props = blend_reverb_props(&weights);
+
+    // listener is only partially within the spheres
+    let presence = spheres
+        .iter()
+        .map(|(_, _, influence)| *influence)
+        .fold(0., f32::max);
+    props.wet_level = (props.wet_level + 20. * presence.log10()).max(-80.);
+
+    bridge
+        .pin_mut()
+        .set_global_reverb(BLENDED_REVERB_INSTANCE, true, (&props).into());
+}
+
+//
+// debug
+
+#[cfg(feature = "gizmos")]
+fn draw_debug_gizmos(
+    config: Res<AudioDebugGizmos>,
+    reverbs: Query<(&AudioReverbSphere, &GlobalTransform)>,
+    geometries: Query<(&AudioGeometry, &GlobalTransform)>,
+    emitters: Query<(&AudioInstance, &GlobalTransform, Option<&AudioParameters>)>,
+    sounds: Res<Assets<AudioSource>>,
+    mut gizmos: Gizmos,
+) {
+    if config.reverb {
+        for (reverb, transform) in reverbs.iter() {
+            let center = transform.translation();
+            gizmos.sphere(center, Quat::IDENTITY, reverb.min_distance, Color::CYAN);
+            gizmos.sphere(center, Quat::IDENTITY, reverb.max_distance, Color::BLUE);
+        }
+    }
+
+    if config.geometry {
+        for (geometry, transform) in geometries.iter() {
+            if !geometry.active {
+                continue;
+            }
+            for polygon in &geometry.polygon_vertices {
+                let vertices = polygon.iter().map(|vertex| *transform * *vertex);
+                let first = vertices.clone().take(1);
+                gizmos.linestrip(vertices.chain(first), Color::ORANGE);
+            }
+        }
+    }
+
+    if config.emitters {
+        for (instance, transform, parameters) in emitters.iter() {
+            let Some(parameters) = parameters
+                .copied()
+                .or_else(|| sounds.get(&instance._source).map(|s| s.params))
+            else {
+                continue;
+            };
+            let center = transform.translation();
+            gizmos.sphere(
+                center,
+                Quat::IDENTITY,
+                parameters.min_distance,
+                Color::GREEN,
+            );
+            gizmos.sphere(
+                center,
+                Quat::IDENTITY,
+                parameters.max_distance,
+                Color::YELLOW,
+            );
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,1377 @@
+use super::*;
+use std::sync::MutexGuard;
+
+/// Engine is global, so tests which use it can't run in parallel
+static ENGINE: Mutex<()> = Mutex::new(());
+
+/// Headless app with the plugin. Engine must be kept locked while app exists.
+fn test_app(settings: AudioEngineInitSettings) -> (MutexGuard<'static, ()>, App) {
+    test_app_with(
+        FmodAudioPlugin {
+            settings,
+            ..default()
+        },
+        AssetPlugin::default(),
+    )
+}
+
+fn test_app_with(plugin: FmodAudioPlugin, assets: AssetPlugin) -> (MutexGuard<'static, ()>, App) {
+    let engine = ENGINE.lock().unwrap_or_else(|error| error.into_inner());
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, assets, TransformPlugin, plugin));
+    (engine, app)
+}
+
+/// Directory in which test files are written, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("bevy_fmod_simple_{name}"));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn write(&self, path: &str, contents: &[u8]) {
+        let path = self.0.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// Asset plugin which loads files from this directory
+    fn assets(&self) -> AssetPlugin {
+        AssetPlugin {
+            asset_folder: self.0.to_string_lossy().into_owned(),
+            ..default()
+        }
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Update app until the asset is loaded or fails to load
+fn wait_for_asset<T: bevy::asset::Asset>(app: &mut App, handle: &Handle<T>) {
+    for _ in 0..500 {
+        app.update();
+        match app.world.resource::<AssetServer>().get_load_state(handle) {
+            bevy::asset::LoadState::Loaded | bevy::asset::LoadState::Failed => return,
+            _ => std::thread::sleep(Duration::from_millis(2)),
+        }
+    }
+    panic!("asset took too long to load");
+}
+
+/// Sine tone as a WAV file
+fn tone_file(frequency: f32, duration: Duration) -> Vec<u8> {
+    let mut samples = vec![0.; test_tone_length(duration)];
+    AudioSineGenerator::new(frequency).fill(&mut samples);
+    wav_file(&samples, TEST_TONE_SAMPLE_RATE)
+}
+
+/// Engine which doesn't output anything and is advanced only by
+/// [`AudioOutput::advance`]
+fn offline_settings() -> AudioEngineInitSettings {
+    AudioEngineInitSettings {
+        output_type: AudioOutputType::NoSoundNrt,
+        ..default()
+    }
+}
+
+/// Let the engine mix that much audio and run all systems
+fn advance(app: &mut App, duration: Duration) {
+    AudioOutput::advance(duration);
+    app.update();
+}
+
+/// Checks that all polygons are planar, convex and wound counter-clockwise
+/// when viewed from outside of the shape centered at the origin.
+fn assert_closed_shape(geometry: &AudioGeometry) {
+    for (index, polygon) in geometry.polygon_vertices.iter().enumerate() {
+        assert_eq!(check_polygon(polygon), Ok(()), "polygon {index}");
+        let center = polygon.iter().sum::<Vec3>() / polygon.len() as f32;
+        assert!(
+            polygon_normal(polygon).dot(center) > 0.,
+            "polygon {index} faces inwards"
+        );
+    }
+}
+
+#[test]
+fn geometry_cuboid() {
+    let geometry = AudioGeometry::cuboid(Vec3::new(1., 2., 3.), default());
+    assert_eq!(geometry.polygon_vertices.len(), 6);
+    assert_closed_shape(&geometry);
+
+    for polygon in &geometry.polygon_vertices {
+        for vertex in polygon {
+            assert_eq!(vertex.abs(), Vec3::new(1., 2., 3.));
+        }
+    }
+}
+
+#[test]
+fn geometry_quad() {
+    for normal in [Vec3::Y, Vec3::NEG_X, Vec3::new(1., 2., 3.)] {
+        let geometry = AudioGeometry::quad(Vec2::new(2., 4.), normal, default());
+        let [polygon] = geometry.polygon_vertices.as_slice() else {
+            panic!("expected one polygon");
+        };
+        assert_eq!(check_polygon(polygon), Ok(()));
+
+        let polygon_normal = polygon_normal(polygon);
+        assert!(polygon_normal
+            .normalize()
+            .abs_diff_eq(normal.normalize(), 1e-5));
+        // twice the area
+        assert!((polygon_normal.length() - 16.).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn geometry_cylinder() {
+    let geometry = AudioGeometry::cylinder(2., 3., 8, default());
+    assert_eq!(geometry.polygon_vertices.len(), 8 + 2);
+    assert_closed_shape(&geometry);
+
+    for vertex in geometry.polygon_vertices.iter().flatten() {
+        assert!((Vec2::new(vertex.x, vertex.z).length() - 2.).abs() < 1e-5);
+        assert_eq!(vertex.y.abs(), 1.5);
+    }
+
+    // clamped to a triangular prism
+    let geometry = AudioGeometry::cylinder(1., 1., 0, default());
+    assert_eq!(geometry.polygon_vertices.len(), 3 + 2);
+    assert_closed_shape(&geometry);
+}
+
+#[test]
+fn concave_polygon() {
+    // L shape in XY plane, counter-clockwise when viewed from +Z
+    let shape = [
+        Vec2::new(0., 0.),
+        Vec2::new(2., 0.),
+        Vec2::new(2., 1.),
+        Vec2::new(1., 1.),
+        Vec2::new(1., 2.),
+        Vec2::new(0., 2.),
+    ];
+    let rotation = Quat::from_euler(EulerRot::XYZ, 0.3, -1.2, 0.7);
+    let offset = Vec3::new(5., -2., 1.);
+    let to_3d = |point: Vec2| rotation * point.extend(0.) + offset;
+    let normal = rotation * Vec3::Z;
+    let polygon: Vec<Vec3> = shape.iter().copied().map(to_3d).collect();
+
+    assert_eq!(check_polygon(&polygon), Err(PolygonError::NotConvex));
+    assert_eq!(check_polygon(&polygon[..4]), Ok(()));
+    assert_eq!(
+        check_polygon(&polygon[..2]),
+        Err(PolygonError::TooFewVertices)
+    );
+    let mut bent = polygon.clone();
+    bent[4] += normal;
+    assert_eq!(check_polygon(&bent), Err(PolygonError::NotPlanar));
+
+    let triangles = triangulate_polygon(&polygon);
+    assert_eq!(triangles.len(), polygon.len() - 2);
+    let mut area = 0.;
+    for triangle in &triangles {
+        assert_eq!(check_polygon(triangle), Ok(()));
+        assert!(triangle.iter().all(|vertex| polygon.contains(vertex)));
+
+        // same winding as the polygon
+        let triangle_normal = polygon_normal(triangle);
+        assert!(triangle_normal.normalize().abs_diff_eq(normal, 1e-4));
+        area += triangle_normal.length() / 2.;
+
+        // nothing covers the notch
+        let center = triangle.iter().sum::<Vec3>() / 3.;
+        let center = rotation.inverse() * (center - offset);
+        assert!(center.x < 1. || center.y < 1., "{triangle:?}");
+    }
+    assert!((area - 3.).abs() < 1e-4);
+}
+
+#[cfg(feature = "physics-integration")]
+#[test]
+fn collider_geometry() {
+    use bevy_rapier3d::prelude::Collider;
+
+    let polygons = |collider: Collider| {
+        let mut polygons = vec![];
+        let is_supported = collider_polygons(
+            collider.as_unscaled_typed_shape(),
+            Transform::IDENTITY,
+            &mut polygons,
+        );
+        (is_supported, polygons)
+    };
+
+    let (is_supported, cuboid) = polygons(Collider::cuboid(1., 2., 3.));
+    assert!(is_supported);
+    assert_eq!(cuboid.len(), 6);
+
+    let (is_supported, compound) = polygons(Collider::compound(vec![
+        (Vec3::X * 10., Quat::IDENTITY, Collider::cuboid(1., 1., 1.)),
+        (Vec3::ZERO, Quat::IDENTITY, Collider::ball(1.)),
+    ]));
+    assert!(is_supported);
+    assert!(compound.len() > 6);
+    assert!(compound[..6].iter().flatten().all(|v| v.x >= 9.));
+
+    let hull = Collider::convex_hull(&[Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::Z, Vec3::ONE]).unwrap();
+    for collider in [
+        hull,
+        Collider::cylinder(1., 1.),
+        Collider::capsule_y(1., 1.),
+    ] {
+        let (is_supported, polygons) = polygons(collider);
+        assert!(is_supported);
+        let vertices: Vec<_> = polygons.iter().flatten().collect();
+        let shape_center = vertices.iter().copied().sum::<Vec3>() / vertices.len() as f32;
+        for polygon in &polygons {
+            assert_eq!(check_polygon(polygon), Ok(()));
+            // wound counter-clockwise when viewed from outside
+            let center = polygon.iter().sum::<Vec3>() / polygon.len() as f32;
+            assert!(polygon_normal(polygon).dot(center - shape_center) > 0.);
+        }
+    }
+
+    let (is_supported, _) = polygons(Collider::heightfield(vec![0.; 4], 2, 2, Vec3::ONE));
+    assert!(!is_supported);
+}
+
+#[test]
+fn attenuation_distance_scale() {
+    let sound = Vec3::new(3., 0., 4.);
+    assert_eq!(AttenuationCenter::default().distance_scale(sound), 1.);
+
+    let attenuation = AttenuationCenter {
+        positions: Some((Vec3::ZERO, Vec3::new(3., 0., 0.))),
+    };
+    // 5 units from the listener, 4 from the center
+    assert!((attenuation.distance_scale(sound) - 5. / 4.).abs() < 1e-6);
+    assert_eq!(attenuation.distance(sound, Vec3::ZERO), 4.);
+
+    // sound at the center is never attenuated
+    let scale = attenuation.distance_scale(Vec3::new(3., 0., 0.));
+    assert!(scale.is_finite() && scale > 1.);
+    // distances must stay non-zero
+    let scale = attenuation.distance_scale(Vec3::ZERO);
+    assert!(scale > 0.);
+}
+
+#[test]
+fn headless_disabled() {
+    // output file can't be created
+    let (_engine, mut app) = test_app(AudioEngineInitSettings {
+        output_type: AudioOutputType::WavWriterNrt,
+        wav_output_path: "/nonexistent/directory/output.wav".into(),
+        ..default()
+    });
+    assert_eq!(*app.world.resource::<AudioState>(), AudioState::Disabled);
+    assert!(app.world.resource::<AudioEngineInfo>().init_error.is_some());
+    assert!(matches!(
+        AudioSource::sine(440., Duration::from_secs(1)),
+        Err(AudioLoadError::Disabled)
+    ));
+
+    let one_shot = app.world.spawn(Handle::<AudioSource>::default()).id();
+    let looped = app
+        .world
+        .spawn((Handle::<AudioSource>::default(), AudioLoop))
+        .id();
+    app.update();
+    app.update();
+
+    // looped sounds are kept, as if they were playing
+    assert!(app.world.get_entity(one_shot).is_none());
+    assert!(app.world.get_entity(looped).is_some());
+    assert!(app.world.get::<AudioInstance>(looped).is_none());
+
+    // restart request is consumed even though nothing is playing
+    app.world.entity_mut(looped).insert(AudioRestart);
+    app.update();
+    assert!(app.world.get::<AudioRestart>(looped).is_none());
+}
+
+#[test]
+fn headless_playback() {
+    let (_engine, mut app) = test_app(offline_settings());
+    assert_eq!(*app.world.resource::<AudioState>(), AudioState::Enabled);
+
+    let source = AudioSource::sine(440., Duration::from_millis(200)).unwrap();
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    let one_shot = app.world.spawn(source.clone()).id();
+    let looped = app.world.spawn((source, AudioLoop)).id();
+    app.update();
+
+    assert!(app.world.get::<AudioInstance>(one_shot).is_some());
+    assert!(app.world.get::<AudioInstance>(looped).is_some());
+
+    advance(&mut app, Duration::from_millis(500));
+    app.update();
+    assert!(app.world.get_entity(one_shot).is_none());
+    assert!(app.world.get::<AudioInstance>(looped).is_some());
+
+    // despawning the entity stops the sound
+    app.world.despawn(looped);
+    app.update();
+    assert!(app.world.resource::<AudioInstanceMapping>().ids.is_empty());
+}
+
+#[test]
+fn reinit_restarts_looped_once() {
+    let (_engine, mut app) = test_app(offline_settings());
+
+    let source = AudioSource::sine(440., Duration::from_secs(1)).unwrap();
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    let one_shot = app.world.spawn(source.clone()).id();
+    let looped = app.world.spawn((source, AudioLoop)).id();
+    app.update();
+
+    app.world.send_event(ReinitAudioEngine {
+        settings: offline_settings(),
+    });
+    for _ in 0..4 {
+        advance(&mut app, Duration::from_millis(50));
+    }
+
+    assert!(app.world.get_entity(one_shot).is_none());
+    let instance = app.world.get::<AudioInstance>(looped).unwrap();
+    let mapping = app.world.resource::<AudioInstanceMapping>();
+    assert_eq!(mapping.ids.len(), 1);
+    assert_eq!(mapping.ids.get(&looped), Some(&instance.id));
+    assert_eq!(app.world.resource::<AudioChannelUsage>().virtual_playing, 1);
+}
+
+#[test]
+fn load_error_from_engine() {
+    let (_engine, _app) = test_app(offline_settings());
+
+    let Err(AudioLoadError::Fmod { file, code, .. }) =
+        AudioSource::from_memory(b"not a sound file")
+    else {
+        panic!("expected engine error");
+    };
+    assert_eq!(file, "16 bytes in memory");
+    assert_ne!(code, 0);
+
+    // error is returned, not kept for AudioError
+    let bridge = BRIDGE.lock().unwrap();
+    assert_eq!(bridge.as_ref().unwrap().error_count(), 0);
+}
+
+/// Events sent during the last update
+fn error_events(app: &App) -> Vec<AudioError> {
+    let events = app.world.resource::<Events<AudioError>>();
+    events.iter_current_update_events().cloned().collect()
+}
+
+#[test]
+fn error_events_sent_once() {
+    let (_engine, mut app) = test_app(offline_settings());
+
+    let bytes = app
+        .world
+        .spawn(AudioBytes(Arc::new(b"not a sound file".to_vec())))
+        .id();
+    app.update();
+    let errors = error_events(&app);
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(errors[0].context, AudioErrorContext::Entity(bytes));
+    assert_ne!(errors[0].code, 0);
+    assert!(app.world.get_entity(bytes).is_none());
+
+    // failure without an FMOD error
+    let samples = vec![0.; test_tone_length(Duration::from_secs(1))];
+    let file = wav_file(&samples, TEST_TONE_SAMPLE_RATE);
+    let source = AudioSource::from_memory_with_mode(&file, AudioLoadMode::Stream).unwrap();
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    let reversed = app
+        .world
+        .spawn((
+            source,
+            AudioParameters {
+                reverse: true,
+                ..default()
+            },
+        ))
+        .id();
+    app.update();
+    let errors = error_events(&app);
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(errors[0].context, AudioErrorContext::Entity(reversed));
+    assert_eq!(errors[0].code, 0);
+    assert!(errors[0].message.contains("reverse"), "{errors:?}");
+
+    app.update();
+    assert!(error_events(&app).is_empty());
+}
+
+#[test]
+fn audio_bytes_cache() {
+    let (_engine, mut app) = test_app(offline_settings());
+
+    let tone = |frequency| Arc::new(tone_file(frequency, Duration::from_millis(100)));
+    let first = app.world.spawn((AudioBytes(tone(440.)), AudioLoop)).id();
+    let same = app.world.spawn((AudioBytes(tone(440.)), AudioLoop)).id();
+    let other = app.world.spawn((AudioBytes(tone(880.)), AudioLoop)).id();
+    app.update();
+
+    let handle = |entity| app.world.get::<Handle<AudioSource>>(entity).unwrap();
+    assert_eq!(handle(first), handle(same));
+    assert_ne!(handle(first), handle(other));
+    assert_eq!(app.world.resource::<Assets<AudioSource>>().len(), 2);
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn geometry_active_by_default() {
+    let geometry: AudioGeometry = ron::from_str("(auto_triangulate: true)").unwrap();
+    assert!(geometry.active);
+    assert!(geometry.auto_triangulate);
+
+    let geometry: AudioGeometry = ron::from_str("(active: false)").unwrap();
+    assert!(!geometry.active);
+}
+
+#[test]
+fn group_registry_collisions() {
+    let mut registry = AudioGroupRegistry::default();
+    let music = registry.register("music");
+    assert_eq!(music, AudioGroup::named("music"));
+    assert!(music.0 < 0);
+    assert_eq!(registry.register("music"), music);
+    assert_eq!(registry.get("music"), Some(music));
+    assert_eq!(registry.name(music), Some("music"));
+    assert_eq!(registry.name(AudioGroup(1)), None);
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn group_names_round_trip() {
+    let mut registry = AudioGroupRegistry::default();
+    let music = registry.register("music");
+    let mut settings = AudioSettings::default();
+    settings.groups.entry(music).or_default().volume = 0.25;
+    settings.groups.entry(AudioGroup(3)).or_default().volume = 0.75;
+
+    let path = std::env::temp_dir().join("bevy_fmod_simple_group_names.ron");
+    settings.save_to(&path, &registry).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("\"music\""), "{text}");
+
+    let mut loaded_registry = AudioGroupRegistry::default();
+    let loaded = AudioSettings::load_from(&path, &mut loaded_registry);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.groups[&music].volume, 0.25);
+    assert_eq!(loaded.groups[&AudioGroup(3)].volume, 0.75);
+    assert_eq!(loaded_registry.name(music), Some("music"));
+
+    // names are never parsed as IDs
+    std::fs::write(
+        &path,
+        "(groups: {\"42\": (volume: 0.5), (42): (volume: 0.25)})",
+    )
+    .unwrap();
+    let loaded = AudioSettings::load_from(&path, &mut loaded_registry);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.groups[&AudioGroup::named("42")].volume, 0.5);
+    assert_eq!(loaded.groups[&AudioGroup(42)].volume, 0.25);
+    assert_eq!(loaded_registry.get("42"), Some(AudioGroup::named("42")));
+
+    // names are used only by save_to and load_from
+    let text = ron::to_string(&settings).unwrap();
+    assert!(!text.contains("music"), "{text}");
+    let loaded: AudioSettings = ron::from_str(&text).unwrap();
+    assert_eq!(loaded.groups[&music].volume, 0.25);
+    assert_eq!(ron::to_string(&AudioGroup(3)).unwrap(), "(3)");
+    assert!(ron::from_str::<AudioGroup>("\"music\"").is_err());
+}
+
+#[test]
+fn trim_silence_sets_offset_and_loop_region() {
+    let (_engine, _app) = test_app(offline_settings());
+
+    let silence = vec![0.; test_tone_length(Duration::from_millis(100))];
+    let mut tone = vec![0.; test_tone_length(Duration::from_millis(200))];
+    AudioSineGenerator::new(440.).fill(&mut tone);
+    let samples = [&silence[..], &tone[..], &silence[..]].concat();
+    let mut source = AudioSource::from_memory(&wav_file(&samples, TEST_TONE_SAMPLE_RATE)).unwrap();
+
+    assert!(source.trim_silence(0.01));
+    let close = |a: Duration, b_ms: u64| (a.as_secs_f64() * 1000. - b_ms as f64).abs() < 2.;
+    assert!(close(source.start_offset, 100), "{:?}", source.start_offset);
+    let (start, end) = source.loop_region.unwrap();
+    assert!(close(start, 100) && close(end, 300), "{start:?}..{end:?}");
+}
+
+#[test]
+fn read_pcm24() {
+    let (_engine, _app) = test_app(offline_settings());
+
+    let samples: [i32; 4] = [0, 0x40_0000, -0x40_0000, -0x80_0000];
+    let data_size = samples.len() as u32 * 3;
+    let mut file = vec![];
+    file.extend_from_slice(b"RIFF");
+    file.extend_from_slice(&(36 + data_size).to_le_bytes());
+    file.extend_from_slice(b"WAVEfmt ");
+    file.extend_from_slice(&16u32.to_le_bytes());
+    file.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    file.extend_from_slice(&1u16.to_le_bytes()); // channels
+    file.extend_from_slice(&48000u32.to_le_bytes());
+    file.extend_from_slice(&(48000u32 * 3).to_le_bytes());
+    file.extend_from_slice(&3u16.to_le_bytes());
+    file.extend_from_slice(&24u16.to_le_bytes());
+    file.extend_from_slice(b"data");
+    file.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        file.extend_from_slice(&sample.to_le_bytes()[..3]);
+    }
+
+    let source = AudioSource::from_memory(&file).unwrap();
+    let info = source.info().unwrap();
+    assert_eq!(info.sample_format, AudioSampleFormat::Pcm24);
+    let pcm = source
+        .read_pcm(Duration::ZERO..Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(pcm, [0., 0.5, -0.5, -1.]);
+}
+
+#[test]
+fn asset_settings_by_path() {
+    let dir = TempDir::new("asset_settings");
+    let tone = tone_file(440., Duration::from_millis(100));
+    dir.write("music/ambient/tone.wav", &tone);
+    dir.write("sfx/tone.wav", &tone);
+
+    let plugin = FmodAudioPlugin {
+        settings: offline_settings(),
+        asset_settings: vec![
+            AudioAssetSettings::new("music")
+                .with_load_mode(AudioLoadMode::Stream)
+                .with_loop(),
+            AudioAssetSettings::new("sfx/tone.wav")
+                .with_params(AudioParameters::default().with_priority(AudioPriority::High))
+                .with_loop_region(Duration::from_millis(10), Duration::from_millis(20)),
+        ],
+        ..default()
+    };
+    let (_engine, mut app) = test_app_with(plugin, dir.assets());
+
+    let asset_server = app.world.resource::<AssetServer>().clone();
+    let music: Handle<AudioSource> = asset_server.load("music/ambient/tone.wav");
+    let sfx: Handle<AudioSource> = asset_server.load("sfx/tone.wav");
+    wait_for_asset(&mut app, &music);
+    wait_for_asset(&mut app, &sfx);
+
+    let sounds = app.world.resource::<Assets<AudioSource>>();
+    let mode = |handle| match sounds.get(handle).map(|sound| &sound.data) {
+        Some(AudioSourceData::AssetFile(mode)) => *mode,
+        _ => panic!("asset not loaded"),
+    };
+    assert_eq!(mode(&music), AudioLoadMode::Stream);
+    assert_eq!(mode(&sfx), AudioLoadMode::Memory);
+
+    let music = sounds.get(&music).unwrap();
+    let sfx = sounds.get(&sfx).unwrap();
+    assert!(music.looped);
+    assert_eq!(music.loop_region, None);
+    assert!(!sfx.looped);
+    assert_eq!(sfx.params.priority, AudioPriority::High.into());
+    assert_eq!(
+        sfx.loop_region,
+        Some((Duration::from_millis(10), Duration::from_millis(20)))
+    );
+}
+
+#[test]
+fn emitter_interpolation() {
+    fn move_emitter(mut emitters: Query<&mut Transform, With<AudioLoop>>, time: Res<Time>) {
+        for mut transform in emitters.iter_mut() {
+            transform.translation.x = 2. * time.elapsed_seconds_f64() as f32;
+        }
+    }
+
+    for interpolation in [
+        AudioInterpolation::None,
+        AudioInterpolation::Interpolate,
+        AudioInterpolation::Extrapolate,
+    ] {
+        let (_engine, mut app) = test_app_with(
+            FmodAudioPlugin {
+                settings: offline_settings(),
+                update_hz: Some(10.),
+                ..default()
+            },
+            AssetPlugin::default(),
+        );
+        app.add_systems(Update, move_emitter);
+        app.world
+            .resource_mut::<AudioSettings>()
+            .emitter_interpolation = interpolation;
+
+        let source = AudioSource::sine(440., Duration::from_secs(1)).unwrap();
+        let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+        let emitter = app
+            .world
+            .spawn((source, AudioLoop, TransformBundle::default()))
+            .id();
+
+        // uneven frames, some longer than the update period
+        let mut updates = 0;
+        let mut interpolated = 0;
+        for frame_ms in [30, 70, 45, 160, 20, 90, 35, 55, 120, 10, 80] {
+            app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+                Duration::from_millis(frame_ms),
+            ));
+            app.update();
+
+            let now = app.world.resource::<Time>().elapsed();
+            let instance = app.world.get::<AudioInstance>(emitter).unwrap();
+            if !app.world.resource::<EngineUpdateTimer>().is_due {
+                continue;
+            }
+            updates += 1;
+
+            // motion is linear, so sampled positions are exact
+            let at = instance.old_position_at;
+            assert!(
+                (instance.old_position.x - 2. * at.as_secs_f32()).abs() < 1e-3,
+                "{interpolation:?}: {} at {at:?}",
+                instance.old_position.x
+            );
+            match interpolation {
+                AudioInterpolation::None => assert_eq!(at, now),
+                AudioInterpolation::Interpolate => {
+                    assert!(at <= now);
+                    interpolated += usize::from(at < now);
+                }
+                AudioInterpolation::Extrapolate => assert!(at > now),
+            }
+        }
+        assert!(updates > 3, "{interpolation:?}: only {updates} updates");
+        if interpolation == AudioInterpolation::Interpolate {
+            assert!(interpolated > 0);
+        }
+    }
+}
+
+/// Occlusion between the origin and each point
+fn occlusion_from_origin(points: &[Vec3]) -> Vec<(f32, f32)> {
+    let mut bridge = BRIDGE.lock().unwrap();
+    let bridge = bridge.as_mut().unwrap();
+    points
+        .iter()
+        .map(|point| {
+            let occlusion = bridge
+                .pin_mut()
+                .get_geometry_occlusion(Vec3::ZERO.into(), (*point).into());
+            (occlusion.direct, occlusion.reverb)
+        })
+        .collect()
+}
+
+#[test]
+fn geometry_round_trip() {
+    let dir = TempDir::new("geometry_round_trip");
+    let (_engine, mut app) = test_app_with(
+        FmodAudioPlugin {
+            settings: offline_settings(),
+            ..default()
+        },
+        dir.assets(),
+    );
+
+    let points = [
+        Vec3::new(0., 0., -5.),
+        Vec3::new(0.5, 0.3, -5.),
+        Vec3::new(10., 0., -4.),
+        Vec3::new(0., 0., -1.),
+    ];
+    let geometry = AudioGeometry::cuboid(
+        Vec3::new(1., 1., 0.1),
+        AudioGeometryParams {
+            direct_occlusion: 0.7,
+            reverb_occlusion: 0.4,
+        },
+    );
+    let wall = Transform::from_xyz(0., 0., -2.);
+
+    let built = app
+        .world
+        .spawn((geometry.clone(), TransformBundle::from_transform(wall)))
+        .id();
+    app.update();
+    // both sides of the box are in the way
+    let expected = occlusion_from_origin(&points);
+    assert!((expected[0].0 - 0.91).abs() < 1e-3, "{expected:?}");
+    assert!((expected[0].1 - 0.64).abs() < 1e-3, "{expected:?}");
+    assert_eq!(expected[2], (0., 0.));
+    assert_eq!(expected[3], (0., 0.));
+
+    dir.write("wall.fmodgeo", &geometry.to_bytes().unwrap());
+    app.world.despawn(built);
+    app.update();
+    assert_eq!(occlusion_from_origin(&points[..1]), [(0., 0.)]);
+
+    let handle: Handle<AudioGeometry> = app.world.resource::<AssetServer>().load("wall.fmodgeo");
+    wait_for_asset(&mut app, &handle);
+    let loaded = app
+        .world
+        .spawn((handle.clone(), TransformBundle::from_transform(wall)))
+        .id();
+    app.update();
+    assert_eq!(
+        app.world
+            .get::<AudioGeometry>(loaded)
+            .unwrap()
+            .polygon_vertices,
+        geometry.polygon_vertices
+    );
+    assert_eq!(occlusion_from_origin(&points), expected);
+
+    // modified asset replaces the component and engine geometry
+    let mut component = app.world.get_mut::<AudioGeometry>(loaded).unwrap();
+    component.params.direct_occlusion = 0.2;
+    component.active = false;
+    app.update();
+    let mut moved = geometry.clone();
+    for polygon in &mut moved.polygon_vertices {
+        for vertex in polygon {
+            vertex.x += 5.;
+        }
+    }
+    *app.world
+        .resource_mut::<Assets<AudioGeometry>>()
+        .get_mut(&handle)
+        .unwrap() = moved.clone();
+    // asset events are sent after PostUpdate
+    app.update();
+    app.update();
+
+    let component = app.world.get::<AudioGeometry>(loaded).unwrap();
+    assert_eq!(component.polygon_vertices, moved.polygon_vertices);
+    assert_eq!(component.params.direct_occlusion, 0.7);
+    assert!(!component.active);
+    assert_eq!(app.world.resource::<GeometryInstanceMapping>().0.len(), 1);
+    assert_eq!(occlusion_from_origin(&points[2..3]), [(0., 0.)]);
+
+    app.world.get_mut::<AudioGeometry>(loaded).unwrap().active = true;
+    app.update();
+    let occlusion = occlusion_from_origin(&points);
+    assert_eq!(occlusion[0], (0., 0.));
+    assert!((occlusion[2].0 - 0.91).abs() < 1e-3, "{occlusion:?}");
+}
+
+#[test]
+fn test_tone_wav() {
+    assert_eq!(test_tone_length(Duration::from_secs(1)), 48000);
+    assert_eq!(test_tone_length(Duration::from_millis(250)), 12000);
+    assert_eq!(test_tone_length(Duration::ZERO), 0);
+
+    let file = wav_file(&[0., 1., -2., 0.5], 22050);
+    let u16_at = |offset: usize| u16::from_le_bytes(file[offset..offset + 2].try_into().unwrap());
+    let u32_at = |offset: usize| u32::from_le_bytes(file[offset..offset + 4].try_into().unwrap());
+    assert_eq!(file.len(), 44 + 4 * 2);
+    assert_eq!(&file[0..4], b"RIFF");
+    assert_eq!(u32_at(4) as usize, file.len() - 8);
+    assert_eq!(&file[8..16], b"WAVEfmt ");
+    assert_eq!(u16_at(20), 1); // PCM
+    assert_eq!(u16_at(22), 1); // channels
+    assert_eq!(u32_at(24), 22050);
+    assert_eq!(u32_at(28), 22050 * 2);
+    assert_eq!(u16_at(34), 16);
+    assert_eq!(&file[36..40], b"data");
+    assert_eq!(u32_at(40), 4 * 2);
+    let samples: Vec<i16> = file[44..]
+        .chunks(2)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+        .collect();
+    assert_eq!(samples, [0, i16::MAX, -i16::MAX, i16::MAX / 2]);
+
+    // engine reads it as is
+    let (_engine, _app) = test_app(offline_settings());
+    let info = AudioSource::sine(440., Duration::from_millis(250))
+        .unwrap()
+        .info()
+        .unwrap();
+    assert_eq!(info.duration, Some(Duration::from_millis(250)));
+    assert_eq!(info.channels, 1);
+    assert_eq!(info.sample_rate, TEST_TONE_SAMPLE_RATE);
+    assert_eq!(info.bits, 16);
+    assert_eq!(info.file_type, AudioFileType::Wav);
+}
+
+#[test]
+fn sine_loudness() {
+    let sine = |volume: f32, duration: Duration| {
+        let mut generator = AudioSineGenerator::new(997.);
+        generator.volume = volume;
+        let mut samples = vec![0.; test_tone_length(duration)];
+        generator.fill(&mut samples);
+        samples
+    };
+    let loudness =
+        |samples: &[f32]| integrated_loudness(samples, TEST_TONE_SAMPLE_RATE as f64).unwrap();
+    let assert_near = |lufs: f32, expected: f32| {
+        assert!(
+            (lufs - expected).abs() < 0.05,
+            "{lufs} LUFS, expected {expected}"
+        );
+    };
+
+    // reference value from BS.1770
+    let full = sine(1., Duration::from_secs(3));
+    assert_near(loudness(&full), -3.01);
+    assert_near(loudness(&sine(0.1, Duration::from_secs(3))), -23.01);
+
+    // silence is gated out, except for blocks which overlap the tone;
+    // without gating it would be 3 dB quieter
+    let mut gapped = full.clone();
+    gapped.resize(full.len() * 2, 0.);
+    let lufs = loudness(&gapped);
+    assert!(lufs < -3.01 && lufs > -3.5, "{lufs} LUFS");
+
+    assert_eq!(integrated_loudness(&[], 48000.), None);
+    assert_eq!(integrated_loudness(&vec![0.; 48000], 48000.), None);
+}
+
+#[test]
+fn render_sine() {
+    let (_engine, mut app) = test_app(offline_settings());
+    let source = AudioSource::sine(440., Duration::from_secs(1)).unwrap();
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+
+    AudioOutput::set_capture(true);
+    app.world.spawn(source);
+    for _ in 0..15 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let output = AudioOutput::take_captured();
+    AudioOutput::set_capture(false);
+
+    assert!(output.channels > 0);
+    assert!(output.duration() >= Duration::from_millis(1500));
+
+    // first channel, while the sound was playing
+    let samples: Vec<f32> = output
+        .samples
+        .iter()
+        .step_by(output.channels as usize)
+        .copied()
+        .collect();
+    let first = samples.iter().position(|s| s.abs() > 1e-3).unwrap();
+    let last = samples.iter().rposition(|s| s.abs() > 1e-3).unwrap();
+    let played = &samples[first..=last];
+    let duration = played.len() as f32 / output.sample_rate as f32;
+    assert!((duration - 1.).abs() < 0.01, "played for {duration} s");
+
+    let peak = played.iter().fold(0., |peak: f32, s| peak.max(s.abs()));
+    assert!(peak > 0.5 && peak <= 1., "peak is {peak}");
+
+    let crossings = played
+        .windows(2)
+        .filter(|pair| (pair[0] < 0.) != (pair[1] < 0.))
+        .count();
+    let frequency = crossings as f32 / 2. / duration;
+    assert!((frequency - 440.).abs() < 5., "frequency is {frequency} Hz");
+
+    // silence after the end
+    assert!(samples[last + 1..].len() > output.sample_rate as usize / 10);
+}
+
+#[test]
+fn effective_volume_when_disabled() {
+    let music = AudioGroup::named("music");
+    let mut settings = AudioSettings {
+        master_volume: 0.5,
+        ..default()
+    };
+    settings.groups.entry(music).or_default().volume = 0.4;
+    assert!((settings.effective_volume(music) - 0.2).abs() < 1e-6);
+    assert_eq!(settings.effective_volume(AudioGroup::default()), 0.5);
+
+    settings.enabled = false;
+    assert_eq!(settings.effective_volume(music), 0.);
+    assert_eq!(settings.effective_volume(AudioGroup::default()), 0.);
+
+    // engine agrees, including groups which aren't configured
+    let other = AudioGroup(7);
+    let late = AudioGroup(8);
+    let (_engine, mut app) = test_app(offline_settings());
+    settings.enabled = true;
+    app.insert_resource(settings);
+    let source = AudioSource::sine(440., Duration::from_secs(1)).unwrap();
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    app.world.spawn((source.clone(), music, AudioLoop));
+    app.world.spawn((source.clone(), other, AudioLoop));
+    for _ in 0..3 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let stats = app.world.resource::<AudioGroupStats>();
+    let audibility = stats.get(music).audibility;
+    assert!(
+        (audibility - 0.2).abs() < 1e-3,
+        "audibility is {audibility}"
+    );
+    let audibility = stats.get(other).audibility;
+    assert!(
+        (audibility - 0.5).abs() < 1e-3,
+        "audibility is {audibility}"
+    );
+
+    app.world.resource_mut::<AudioSettings>().enabled = false;
+    advance(&mut app, Duration::from_millis(100));
+    // group created while audio is disabled
+    app.world.spawn((source, late, AudioLoop));
+    for _ in 0..3 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let stats = app.world.resource::<AudioGroupStats>();
+    for group in [music, other, late] {
+        assert!(stats.groups.contains_key(&group), "{group:?} has no stats");
+        assert_eq!(stats.get(group).audibility, 0., "{group:?}");
+    }
+}
+
+#[test]
+fn generator_continuous_playback() {
+    /// Sine which counts generated samples
+    struct Counted(AudioSineGenerator, Arc<Mutex<usize>>);
+
+    impl AudioGenerator for Counted {
+        fn fill(&mut self, out: &mut [f32]) -> usize {
+            *self.1.lock().unwrap() += out.len();
+            self.0.fill(out)
+        }
+    }
+
+    let (_engine, mut app) = test_app(offline_settings());
+    let generated = Arc::new(Mutex::new(0));
+    let generator = Counted(AudioSineGenerator::new(440.), generated.clone());
+    let source = AudioSource::from_generator(Box::new(generator)).unwrap();
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+
+    AudioOutput::set_capture(true);
+    let entity = app.world.spawn(source.clone()).id();
+    for _ in 0..20 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let output = AudioOutput::take_captured();
+    AudioOutput::set_capture(false);
+    assert!(app.world.get::<AudioInstance>(entity).is_some());
+
+    // no gaps in the first channel
+    let samples: Vec<f32> = output
+        .samples
+        .iter()
+        .step_by(output.channels as usize)
+        .copied()
+        .collect();
+    let first = samples.iter().position(|s| s.abs() > 1e-3).unwrap();
+    let block = output.sample_rate as usize / 100;
+    let blocks = samples[first..].chunks_exact(block);
+    assert!(blocks.len() >= 190, "{} blocks", blocks.len());
+    // default master volume is 0.5
+    for (index, block) in blocks.enumerate() {
+        let peak = block.iter().fold(0., |peak: f32, s| peak.max(s.abs()));
+        assert!(peak > 0.45, "block {index} peak is {peak}");
+    }
+    assert!(*generated.lock().unwrap() >= 48000 * 19 / 10);
+
+    // generator is dropped with the source
+    app.world.despawn(entity);
+    app.update();
+    app.world
+        .resource_mut::<Assets<AudioSource>>()
+        .remove(&source);
+    drop(source);
+    app.update();
+    app.update();
+    assert_eq!(Arc::strong_count(&generated), 1);
+}
+
+/// Sine tone as an IMA ADPCM WAV file, which can be kept compressed
+fn adpcm_tone_file(frequency: f32, duration: Duration) -> Vec<u8> {
+    const STEPS: [i32; 89] = [
+        7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60,
+        66, 73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371,
+        408, 449, 494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878,
+        2066, 2272, 2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845,
+        8630, 9493, 10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086,
+        29794, 32767,
+    ];
+    const INDEX_CHANGE: [i32; 8] = [-1, -1, -1, -1, 2, 4, 6, 8];
+    const BLOCK_SIZE: usize = 1024;
+    const BLOCK_SAMPLES: usize = (BLOCK_SIZE - 4) * 2 + 1;
+
+    let mut samples = vec![0.; test_tone_length(duration)];
+    AudioSineGenerator::new(frequency).fill(&mut samples);
+    let samples: Vec<i32> = samples.iter().map(|s| (s * 32767.) as i32).collect();
+
+    let mut data = vec![];
+    // step close to the largest change between samples, so the first block
+    // doesn't start with a ramp
+    let mut index = 56;
+    for block in samples.chunks(BLOCK_SAMPLES) {
+        let mut predicted = block[0];
+        data.extend_from_slice(&(predicted as i16).to_le_bytes());
+        data.extend_from_slice(&[index as u8, 0]);
+
+        let mut nibbles = block[1..].iter().map(|sample| {
+            let step = STEPS[index];
+            let diff = sample - predicted;
+            let nibble = ((diff.abs() * 4 / step).min(7) | if diff < 0 { 8 } else { 0 }) as usize;
+            // same rounding as decoder
+            let delta = (step >> 3)
+                + if nibble & 4 != 0 { step } else { 0 }
+                + if nibble & 2 != 0 { step >> 1 } else { 0 }
+                + if nibble & 1 != 0 { step >> 2 } else { 0 };
+            predicted = (predicted + if nibble & 8 != 0 { -delta } else { delta })
+                .clamp(i16::MIN.into(), i16::MAX.into());
+            index = (index as i32 + INDEX_CHANGE[nibble & 7]).clamp(0, 88) as usize;
+            nibble as u8
+        });
+        for _ in 0..BLOCK_SIZE - 4 {
+            let low = nibbles.next().unwrap_or(0);
+            let high = nibbles.next().unwrap_or(0);
+            data.push(low | high << 4);
+        }
+    }
+
+    let sample_rate = TEST_TONE_SAMPLE_RATE;
+    let mut file = vec![];
+    file.extend_from_slice(b"RIFF");
+    file.extend_from_slice(&(4 + 28 + 12 + 8 + data.len() as u32).to_le_bytes());
+    file.extend_from_slice(b"WAVEfmt ");
+    file.extend_from_slice(&20u32.to_le_bytes()); // chunk size
+    file.extend_from_slice(&0x11u16.to_le_bytes()); // IMA ADPCM
+    file.extend_from_slice(&1u16.to_le_bytes()); // channels
+    file.extend_from_slice(&sample_rate.to_le_bytes());
+    let bytes_per_second = sample_rate as usize * BLOCK_SIZE / BLOCK_SAMPLES;
+    file.extend_from_slice(&(bytes_per_second as u32).to_le_bytes());
+    file.extend_from_slice(&(BLOCK_SIZE as u16).to_le_bytes());
+    file.extend_from_slice(&4u16.to_le_bytes()); // bits per sample
+    file.extend_from_slice(&2u16.to_le_bytes()); // extra size
+    file.extend_from_slice(&(BLOCK_SAMPLES as u16).to_le_bytes());
+    file.extend_from_slice(b"fact");
+    file.extend_from_slice(&4u32.to_le_bytes());
+    file.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+    file.extend_from_slice(b"data");
+    file.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    file.extend_from_slice(&data);
+    file
+}
+
+/// Playback position of the entity's sound, in seconds
+fn playback_position(app: &App, entity: Entity) -> Option<f64> {
+    let instance = app.world.get::<AudioInstance>(entity)?;
+    let mut bridge = BRIDGE.lock().unwrap();
+    let position = bridge.as_mut()?.pin_mut().get_channel_position(instance.id);
+    (position >= 0.).then_some(position)
+}
+
+#[test]
+fn compressed_playback() {
+    let (_engine, mut app) = test_app(offline_settings());
+    let file = adpcm_tone_file(440., Duration::from_secs(1));
+
+    // test file is correct
+    let mut expected = vec![0.; 4800];
+    AudioSineGenerator::new(440.).fill(&mut expected);
+    let decoded = AudioSource::from_memory(&file)
+        .unwrap()
+        .read_pcm(Duration::ZERO..Duration::from_millis(100))
+        .unwrap();
+    assert_eq!(decoded.len(), expected.len());
+    let error = decoded
+        .iter()
+        .zip(&expected)
+        .fold(0., |error: f32, (a, b)| error.max((a - b).abs()));
+    assert!(error < 0.05, "max error is {error}");
+
+    let source = AudioSource::from_memory_compressed(&file).unwrap();
+    let info = source.info().unwrap();
+    assert!(info.decoded_while_playing);
+    assert_eq!(info.sample_format, AudioSampleFormat::Compressed);
+    assert!(source
+        .read_pcm(Duration::ZERO..Duration::from_secs(1))
+        .is_none());
+    // padded to the whole block
+    let length = info.duration.unwrap().as_secs_f64();
+    assert!((1. ..1.05).contains(&length), "{length} s long");
+
+    // within a couple of DSP buffers, since each update mixes one
+    let assert_near = |position: Option<f64>, expected: f64| {
+        let position = position.unwrap();
+        assert!(
+            (position - expected).abs() < 0.05,
+            "at {position} s, expected {expected}"
+        );
+    };
+
+    // looping
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    AudioOutput::set_capture(true);
+    let looped = app.world.spawn((source.clone(), AudioLoop)).id();
+    for _ in 0..3 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let output = AudioOutput::take_captured();
+    AudioOutput::set_capture(false);
+    assert!(playback_position(&app, looped).unwrap() > 0.2);
+
+    let samples: Vec<f32> = output
+        .samples
+        .iter()
+        .step_by(output.channels as usize)
+        .copied()
+        .collect();
+    let first = samples.iter().position(|s| s.abs() > 1e-3).unwrap();
+    let played = &samples[first..];
+    let peak = played.iter().fold(0., |peak: f32, s| peak.max(s.abs()));
+    assert!(peak > 0.5, "peak is {peak}");
+    let crossings = played
+        .windows(2)
+        .filter(|pair| (pair[0] < 0.) != (pair[1] < 0.))
+        .count();
+    let frequency = crossings as f32 / 2. / played.len() as f32 * output.sample_rate as f32;
+    assert!(
+        (frequency - 440.).abs() < 30.,
+        "frequency is {frequency} Hz"
+    );
+
+    // seeking and speed
+    let seeked = app
+        .world
+        .spawn((source.clone(), AudioStartOffset(Duration::from_millis(600))))
+        .id();
+    let fast = app
+        .world
+        .spawn((
+            source,
+            AudioParameters {
+                speed: 2.,
+                ..default()
+            },
+        ))
+        .id();
+    app.update();
+    let started_at = playback_position(&app, looped).unwrap();
+    assert_near(playback_position(&app, seeked), 0.6);
+    assert_near(playback_position(&app, fast), 0.);
+
+    for _ in 0..3 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let looped_at = playback_position(&app, looped).unwrap();
+    let elapsed = looped_at - started_at;
+    assert!(elapsed >= 0.3, "{elapsed} s elapsed");
+    assert_near(playback_position(&app, seeked), 0.6 + elapsed);
+    assert_near(playback_position(&app, fast), 2. * elapsed);
+
+    // looped sound wraps around, others end
+    let instance = app.world.get::<AudioInstance>(looped).unwrap().id;
+    for _ in 0..5 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    assert!(playback_position(&app, looped).unwrap() < looped_at);
+    assert_eq!(app.world.get::<AudioInstance>(looped).unwrap().id, instance);
+    assert!(app.world.get_entity(seeked).is_none());
+    assert!(app.world.get_entity(fast).is_none());
+}
+
+#[test]
+fn large_file_memory() {
+    let dir = TempDir::new("large_file_memory");
+    let file = tone_file(440., Duration::from_secs(45));
+    assert!(file.len() > 4_000_000);
+    dir.write("large.wav", &file);
+
+    let (_engine, mut app) = test_app_with(
+        FmodAudioPlugin {
+            settings: offline_settings(),
+            ..default()
+        },
+        dir.assets(),
+    );
+    app.update();
+    let engine_before = app.world.resource::<AudioMemoryStats>().current;
+
+    // loader doesn't keep the file
+    let loaded: Handle<AudioSource> = app.world.resource::<AssetServer>().load("large.wav");
+    wait_for_asset(&mut app, &loaded);
+    app.update();
+    let stats = app.world.resource::<AudioMemoryStats>();
+    let size = stats.sources[&loaded.id()];
+    assert!(size >= file.len() - 44, "{size} bytes");
+    assert!(size < file.len() * 11 / 10, "{size} bytes");
+    let engine_size = stats.current - engine_before;
+    assert!(engine_size < file.len() * 11 / 10, "{engine_size} bytes");
+
+    // source created from memory keeps its copy
+    let source = AudioSource::from_memory(&file).unwrap();
+    let in_memory = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    app.update();
+    app.update();
+    let size = app.world.resource::<AudioMemoryStats>().sources[&in_memory.id()];
+    assert!(size >= file.len() * 2 - 44, "{size} bytes");
+}
+
+#[test]
+fn force_one_shot() {
+    let (_engine, mut app) = test_app(offline_settings());
+
+    let mut source = AudioSource::sine(440., Duration::from_millis(200)).unwrap();
+    source.looped = true;
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    let looped = app.world.spawn(source.clone()).id();
+    let one_shot = app.world.spawn((source.clone(), AudioForceOneShot)).id();
+    let both = app.world.spawn((source, AudioLoop, AudioForceOneShot)).id();
+    app.update();
+    for entity in [looped, one_shot, both] {
+        assert!(app.world.get::<AudioInstance>(entity).is_some());
+    }
+
+    advance(&mut app, Duration::from_millis(500));
+    app.update();
+    assert!(app.world.get::<AudioInstance>(looped).is_some());
+    assert!(app.world.get_entity(one_shot).is_none());
+    assert!(app.world.get_entity(both).is_none());
+}
+
+#[test]
+fn hot_reload_resumes_position() {
+    let (_engine, mut app) = test_app(offline_settings());
+    let file = tone_file(440., Duration::from_secs(4));
+    let source = AudioSource::from_memory(&file).unwrap();
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    let parameters = AudioParameters {
+        speed: 2.,
+        ..default()
+    };
+    let entity = app.world.spawn((source.clone(), parameters)).id();
+    for _ in 0..5 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+
+    // time while paused doesn't count
+    app.world.resource_mut::<AudioSettings>().paused = true;
+    for _ in 0..5 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let before = playback_position(&app, entity).unwrap();
+    assert!((1.1..1.5).contains(&before), "position is {before}");
+
+    let reloaded = AudioSource::from_memory(&file).unwrap();
+    let _ = app
+        .world
+        .resource_mut::<Assets<AudioSource>>()
+        .set(&source, reloaded);
+    // restarted sound is played by the next `play_audio`
+    app.update();
+    app.update();
+    let reloaded = app.world.resource::<Assets<AudioSource>>().get(&source);
+    let instance = app.world.get::<AudioInstance>(entity).unwrap();
+    assert_eq!(instance.file_id, reloaded.unwrap().id);
+    let after = playback_position(&app, entity).unwrap();
+    assert!((after - before).abs() < 0.01, "{before} -> {after}");
+}
+
+#[test]
+fn group_ducking() {
+    let music = AudioGroup(1);
+    let voice = AudioGroup(2);
+    let mut settings = AudioSettings::default();
+    settings.groups.entry(music).or_default().duck_when = Some(AudioDucking {
+        attack: Duration::ZERO,
+        ..AudioDucking::new(voice, 20.)
+    });
+
+    let (_engine, mut app) = test_app(offline_settings());
+    app.insert_resource(settings);
+    app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+        Duration::from_millis(100),
+    ));
+    let source = AudioSource::sine(440., Duration::from_secs(1)).unwrap();
+    let source = app.world.resource_mut::<Assets<AudioSource>>().add(source);
+    app.world.spawn((source.clone(), music, AudioLoop));
+    for _ in 0..3 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let audibility = app
+        .world
+        .resource::<AudioGroupStats>()
+        .get(music)
+        .audibility;
+    assert!(
+        (audibility - 0.5).abs() < 1e-3,
+        "audibility is {audibility}"
+    );
+
+    let voice = app.world.spawn((source, voice)).id();
+    for _ in 0..3 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let audibility = app
+        .world
+        .resource::<AudioGroupStats>()
+        .get(music)
+        .audibility;
+    assert!(
+        (audibility - 0.05).abs() < 1e-3,
+        "audibility is {audibility}"
+    );
+
+    // restored after release
+    app.world.despawn(voice);
+    for _ in 0..10 {
+        advance(&mut app, Duration::from_millis(100));
+    }
+    let audibility = app
+        .world
+        .resource::<AudioGroupStats>()
+        .get(music)
+        .audibility;
+    assert!(
+        (audibility - 0.5).abs() < 1e-3,
+        "audibility is {audibility}"
+    );
+}
+
+#[test]
+fn random_pool_no_immediate_repeat() {
+    let (_engine, mut app) = test_app(offline_settings());
+    let mut sources = app.world.resource_mut::<Assets<AudioSource>>();
+    let variants: Vec<_> = [440., 880.]
+        .into_iter()
+        .map(|frequency| {
+            let source = AudioSource::sine(frequency, Duration::from_millis(100)).unwrap();
+            (sources.add(source), 1.)
+        })
+        .collect();
+    let pool = AudioRandomPool {
+        variants,
+        no_immediate_repeat: true,
+    };
+
+    let mut last = None;
+    for _ in 0..10 {
+        let entity = app.world.spawn(pool.clone()).id();
+        app.update();
+        let picked = app
+            .world
+            .get::<Handle<AudioSource>>(entity)
+            .unwrap()
+            .clone();
+        assert_ne!(Some(&picked), last.as_ref());
+        last = Some(picked);
+        app.world.despawn(entity);
+    }
+}